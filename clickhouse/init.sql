@@ -9,19 +9,20 @@ USE market_data;
 -- Orderbook Table
 -- Stores order book snapshots with bid/ask levels
 CREATE TABLE IF NOT EXISTS orderbook (
-    timestamp DateTime64(3) CODEC(Delta, ZSTD),
-    ingestion_timestamp DateTime64(3) CODEC(Delta, ZSTD),
+    timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    ingestion_timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    publish_timestamp Nullable(DateTime64(9)),
     venue LowCardinality(String),
     symbol String,
     seq_id UInt64,
     instrument_class Nullable(String),
     bids Nested(
-        price Float64,
-        amount Float64
+        price Decimal(18, 8),
+        amount Decimal(18, 8)
     ),
     asks Nested(
-        price Float64,
-        amount Float64
+        price Decimal(18, 8),
+        amount Decimal(18, 8)
     ),
     date Date DEFAULT toDate(timestamp)
 ) ENGINE = MergeTree()
@@ -33,20 +34,22 @@ SETTINGS index_granularity = 8192;
 -- Trades Table
 -- Stores individual trade executions
 CREATE TABLE IF NOT EXISTS trades (
-    timestamp DateTime64(3) CODEC(Delta, ZSTD),
-    ingestion_timestamp DateTime64(3) CODEC(Delta, ZSTD),
+    timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    ingestion_timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    publish_timestamp Nullable(DateTime64(9)),
     venue LowCardinality(String),
     symbol String,
     trade_id String,
     seq_id Nullable(UInt64),
-    price Float64 CODEC(Gorilla, ZSTD),
-    amount Float64 CODEC(Gorilla, ZSTD),
+    price Decimal(18, 8) CODEC(ZSTD),
+    amount Decimal(18, 8) CODEC(ZSTD),
     side LowCardinality(String),
     instrument_class Nullable(String),
-    contracts Nullable(Float64),
-    index_price Nullable(Float64),
-    mark_price Nullable(Float64),
+    contracts Nullable(Decimal(18, 8)),
+    index_price Nullable(Decimal(18, 8)),
+    mark_price Nullable(Decimal(18, 8)),
     tick_direction Nullable(Int32),
+    backfilled Bool DEFAULT false,
     date Date DEFAULT toDate(timestamp)
 ) ENGINE = MergeTree()
 PARTITION BY (venue, toYYYYMM(timestamp))
@@ -57,28 +60,29 @@ SETTINGS index_granularity = 8192;
 -- Ticker Table
 -- Stores comprehensive market state information
 CREATE TABLE IF NOT EXISTS ticker (
-    timestamp Int64 CODEC(Delta, ZSTD),
-    ingestion_timestamp Int64 CODEC(Delta, ZSTD),
+    timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    ingestion_timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    publish_timestamp Nullable(DateTime64(9)),
     venue LowCardinality(String),
     state UInt8,
     symbol String,
-    index_price Nullable(Float64) CODEC(Gorilla, ZSTD),
-    settlement_price Nullable(Float64),
-    open_interest Nullable(Float64),
-    mark_price Nullable(Float64) CODEC(Gorilla, ZSTD),
-    best_bid_price Nullable(Float64),
+    index_price Nullable(Decimal(18, 8)) CODEC(ZSTD),
+    settlement_price Nullable(Decimal(18, 8)),
+    open_interest Nullable(Decimal(18, 8)),
+    mark_price Nullable(Decimal(18, 8)) CODEC(ZSTD),
+    best_bid_price Nullable(Decimal(18, 8)),
     mark_iv Nullable(Float64),
     ask_iv Nullable(Float64),
     bid_iv Nullable(Float64),
-    underlying_price Nullable(Float64),
+    underlying_price Nullable(Decimal(18, 8)),
     underlying_index Nullable(String),
-    best_ask_price Nullable(Float64),
+    best_ask_price Nullable(Decimal(18, 8)),
     interest_rate Nullable(Float64),
-    estimated_delivery_price Nullable(Float64),
-    best_ask_amount Nullable(Float64),
-    best_bid_amount Nullable(Float64),
+    estimated_delivery_price Nullable(Decimal(18, 8)),
+    best_ask_amount Nullable(Decimal(18, 8)),
+    best_bid_amount Nullable(Decimal(18, 8)),
     current_funding Nullable(Float64),
-    delivery_price Nullable(Float64),
+    delivery_price Nullable(Decimal(18, 8)),
     funding_8h Nullable(Float64),
     interest_value Nullable(Float64),
     greeks_delta Nullable(Float64),
@@ -86,11 +90,148 @@ CREATE TABLE IF NOT EXISTS ticker (
     greeks_vega Nullable(Float64),
     greeks_theta Nullable(Float64),
     greeks_rho Nullable(Float64),
-    date Date DEFAULT toDate(toDateTime(timestamp))
+    date Date DEFAULT toDate(timestamp)
+) ENGINE = MergeTree()
+PARTITION BY (venue, toYYYYMM(timestamp))
+ORDER BY (venue, symbol, timestamp)
+TTL timestamp + INTERVAL 90 DAY
+SETTINGS index_granularity = 8192;
+
+-- Quotes Table
+-- Stores top-of-book changes from the quote channel
+CREATE TABLE IF NOT EXISTS quotes (
+    timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    ingestion_timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    publish_timestamp Nullable(DateTime64(9)),
+    venue LowCardinality(String),
+    symbol String,
+    instrument_class Nullable(String),
+    best_bid_price Nullable(Decimal(18, 8)),
+    best_bid_amount Nullable(Decimal(18, 8)),
+    best_ask_price Nullable(Decimal(18, 8)),
+    best_ask_amount Nullable(Decimal(18, 8)),
+    date Date DEFAULT toDate(timestamp)
+) ENGINE = MergeTree()
+PARTITION BY (venue, toYYYYMM(timestamp))
+ORDER BY (venue, symbol, timestamp)
+TTL timestamp + INTERVAL 30 DAY
+SETTINGS index_granularity = 8192;
+
+-- Book Deltas Table
+-- Stores incremental order book updates; replay from the last is_snapshot row per symbol
+CREATE TABLE IF NOT EXISTS book_deltas (
+    timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    ingestion_timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    publish_timestamp Nullable(DateTime64(9)),
+    venue LowCardinality(String),
+    symbol String,
+    seq_id UInt64,
+    prev_seq_id Nullable(UInt64),
+    is_snapshot Bool,
+    instrument_class Nullable(String),
+    bids Nested(
+        action LowCardinality(String),
+        price Decimal(18, 8),
+        amount Decimal(18, 8)
+    ),
+    asks Nested(
+        action LowCardinality(String),
+        price Decimal(18, 8),
+        amount Decimal(18, 8)
+    ),
+    date Date DEFAULT toDate(timestamp)
+) ENGINE = MergeTree()
+PARTITION BY (venue, toYYYYMM(timestamp))
+ORDER BY (venue, symbol, timestamp, seq_id)
+TTL timestamp + INTERVAL 7 DAY
+SETTINGS index_granularity = 8192;
+
+-- Liquidations Table
+-- Stores trades in which the maker, taker or both were liquidated
+CREATE TABLE IF NOT EXISTS liquidations (
+    timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    ingestion_timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    publish_timestamp Nullable(DateTime64(9)),
+    venue LowCardinality(String),
+    symbol String,
+    trade_id String,
+    price Decimal(18, 8),
+    amount Decimal(18, 8),
+    side LowCardinality(String),
+    liquidated LowCardinality(String),
+    instrument_class Nullable(String),
+    date Date DEFAULT toDate(timestamp)
 ) ENGINE = MergeTree()
-PARTITION BY (venue, toYYYYMM(toDateTime(timestamp)))
+PARTITION BY (venue, toYYYYMM(timestamp))
+ORDER BY (venue, symbol, timestamp)
+TTL timestamp + INTERVAL 365 DAY
+SETTINGS index_granularity = 8192;
+
+-- Funding Rates Table
+-- Stores perpetual funding as reported on each perpetual ticker
+CREATE TABLE IF NOT EXISTS funding_rates (
+    timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    ingestion_timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    publish_timestamp Nullable(DateTime64(9)),
+    venue LowCardinality(String),
+    symbol String,
+    current_funding Float64,
+    funding_8h Nullable(Float64),
+    index_price Decimal(18, 8),
+    mark_price Decimal(18, 8),
+    date Date DEFAULT toDate(timestamp)
+) ENGINE = MergeTree()
+PARTITION BY (venue, toYYYYMM(timestamp))
+ORDER BY (venue, symbol, timestamp)
+TTL timestamp + INTERVAL 90 DAY
+SETTINGS index_granularity = 8192;
+
+-- Index Prices Table
+-- Stores Deribit price index updates
+CREATE TABLE IF NOT EXISTS index_prices (
+    timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    ingestion_timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    publish_timestamp Nullable(DateTime64(9)),
+    venue LowCardinality(String),
+    index_name LowCardinality(String),
+    price Float64,
+    date Date DEFAULT toDate(timestamp)
+) ENGINE = MergeTree()
+PARTITION BY (venue, toYYYYMM(timestamp))
+ORDER BY (venue, index_name, timestamp)
+TTL timestamp + INTERVAL 90 DAY
+SETTINGS index_granularity = 8192;
+
+-- Settlements Table
+-- Stores each new settlement price, plus the delivery price once an instrument expires
+CREATE TABLE IF NOT EXISTS settlements (
+    timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    ingestion_timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    publish_timestamp Nullable(DateTime64(9)),
+    venue LowCardinality(String),
+    symbol String,
+    instrument_class Nullable(String),
+    settlement_price Decimal(18, 8),
+    delivery_price Nullable(Decimal(18, 8)),
+    date Date DEFAULT toDate(timestamp)
+) ENGINE = MergeTree()
+PARTITION BY (venue, toYYYYMM(timestamp))
+ORDER BY (venue, symbol, timestamp)
+SETTINGS index_granularity = 8192;
+
+-- Instrument Status Table
+-- Stores instrument lifecycle changes (created, started, settled, closed, terminated)
+CREATE TABLE IF NOT EXISTS instrument_status (
+    timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    ingestion_timestamp DateTime64(9) CODEC(Delta, ZSTD),
+    publish_timestamp Nullable(DateTime64(9)),
+    venue LowCardinality(String),
+    symbol String,
+    state LowCardinality(String),
+    date Date DEFAULT toDate(timestamp)
+) ENGINE = MergeTree()
+PARTITION BY (venue, toYYYYMM(timestamp))
 ORDER BY (venue, symbol, timestamp)
-TTL toDateTime(timestamp) + INTERVAL 90 DAY
 SETTINGS index_granularity = 8192;
 
 -- Materialized Views for Common Queries
@@ -0,0 +1,11 @@
+fn main() -> std::io::Result<()> {
+    println!("cargo:rerun-if-changed=proto/market_data.proto");
+
+    // Bundled protoc so the build does not depend on a system install
+    let protoc = protoc_bin_vendored::protoc_bin_path()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, e.to_string()))?;
+
+    prost_build::Config::new()
+        .protoc_executable(protoc)
+        .compile_protos(&["proto/market_data.proto"], &["proto"])
+}
@@ -0,0 +1,478 @@
+use crate::envelope::Envelope;
+use crate::errors::{CodecError, Result};
+use crate::models::MarketData;
+use crate::proto::v1;
+use crate::timestamp::{self, TimestampFormat};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Kafka header naming the codec a record was encoded with
+pub const CODEC_HEADER: &str = "codec";
+
+/// Wire encodings available to the sinks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CodecKind {
+    #[default]
+    Json,
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    Protobuf,
+    /// Confluent wire format against a Schema Registry; Kafka only
+    Avro,
+}
+
+impl CodecKind {
+    /// Value written to the `codec` header
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CodecKind::Json => "json",
+            CodecKind::MessagePack => "msgpack",
+            CodecKind::Protobuf => "protobuf",
+            CodecKind::Avro => "avro",
+        }
+    }
+
+    /// MIME type written to the `content-type` header
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            CodecKind::Json => "application/json",
+            CodecKind::MessagePack => "application/vnd.msgpack",
+            CodecKind::Protobuf => "application/x-protobuf",
+            CodecKind::Avro => "application/vnd.schemaregistry.v1+avro",
+        }
+    }
+
+    /// Codec named by a `codec` header; records without one are JSON
+    pub fn from_header(value: Option<&[u8]>) -> Result<Self> {
+        match value {
+            None | Some(b"json") => Ok(CodecKind::Json),
+            Some(b"msgpack") => Ok(CodecKind::MessagePack),
+            Some(b"protobuf") => Ok(CodecKind::Protobuf),
+            Some(b"avro") => Ok(CodecKind::Avro),
+            Some(other) => Err(CodecError::Invalid(format!(
+                "unknown codec '{}'",
+                String::from_utf8_lossy(other)
+            ))),
+        }
+    }
+
+    /// Codec that needs no setup, writing timestamps in `timestamps` where the encoding has a
+    /// choice; Avro codecs are built by the collector against its registry
+    pub fn codec(&self, timestamps: TimestampFormat) -> Result<Arc<dyn Codec>> {
+        match self {
+            CodecKind::Json => Ok(Arc::new(JsonCodec::new(timestamps))),
+            CodecKind::MessagePack => Ok(Arc::new(MsgPackCodec::new(timestamps))),
+            CodecKind::Protobuf => Ok(Arc::new(ProtobufCodec)),
+            CodecKind::Avro => Err(CodecError::Invalid(
+                "the avro codec needs a schema registry".to_string(),
+            )),
+        }
+    }
+}
+
+/// Turns market data into bytes for a sink and back
+pub trait Codec: Send + Sync {
+    fn kind(&self) -> CodecKind;
+
+    /// Encode a full envelope, as written to Kafka
+    fn encode(&self, envelope: &Envelope) -> Result<Vec<u8>>;
+
+    /// Encode a bare payload, as cached in Redis
+    fn encode_payload(&self, data: &MarketData) -> Result<Vec<u8>>;
+
+    fn decode(&self, bytes: &[u8]) -> Result<Envelope>;
+}
+
+/// Decode a Kafka record using the codec named in its header; Avro records need an `AvroCodec`
+pub fn decode(codec_header: Option<&[u8]>, bytes: &[u8]) -> Result<Envelope> {
+    // Decoding accepts either timestamp format
+    CodecKind::from_header(codec_header)?
+        .codec(TimestampFormat::default())?
+        .decode(bytes)
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec {
+    timestamps: TimestampFormat,
+}
+
+impl JsonCodec {
+    pub fn new(timestamps: TimestampFormat) -> Self {
+        Self { timestamps }
+    }
+}
+
+impl Codec for JsonCodec {
+    fn kind(&self) -> CodecKind {
+        CodecKind::Json
+    }
+
+    fn encode(&self, envelope: &Envelope) -> Result<Vec<u8>> {
+        Ok(timestamp::with_format(self.timestamps, || serde_json::to_vec(envelope))?)
+    }
+
+    fn encode_payload(&self, data: &MarketData) -> Result<Vec<u8>> {
+        Ok(timestamp::with_format(self.timestamps, || serde_json::to_vec(data))?)
+    }
+
+    /// Also accepts bare legacy payloads
+    fn decode(&self, bytes: &[u8]) -> Result<Envelope> {
+        let json = std::str::from_utf8(bytes)
+            .map_err(|e| CodecError::Invalid(e.to_string()))?;
+        Ok(Envelope::from_json(json)?)
+    }
+}
+
+/// MessagePack with named fields, so it carries the same layout as the JSON
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgPackCodec {
+    timestamps: TimestampFormat,
+}
+
+impl MsgPackCodec {
+    pub fn new(timestamps: TimestampFormat) -> Self {
+        Self { timestamps }
+    }
+}
+
+impl Codec for MsgPackCodec {
+    fn kind(&self) -> CodecKind {
+        CodecKind::MessagePack
+    }
+
+    fn encode(&self, envelope: &Envelope) -> Result<Vec<u8>> {
+        timestamp::with_format(self.timestamps, || rmp_serde::to_vec_named(envelope))
+            .map_err(|e| CodecError::Invalid(e.to_string()))
+    }
+
+    fn encode_payload(&self, data: &MarketData) -> Result<Vec<u8>> {
+        timestamp::with_format(self.timestamps, || rmp_serde::to_vec_named(data))
+            .map_err(|e| CodecError::Invalid(e.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Envelope> {
+        rmp_serde::from_slice(bytes).map_err(|e| CodecError::Invalid(e.to_string()))
+    }
+}
+
+/// `market_data.v1` messages from `proto/market_data.proto`; timestamps are always nanoseconds
+pub struct ProtobufCodec;
+
+impl Codec for ProtobufCodec {
+    fn kind(&self) -> CodecKind {
+        CodecKind::Protobuf
+    }
+
+    fn encode(&self, envelope: &Envelope) -> Result<Vec<u8>> {
+        Ok(v1::Envelope::from(envelope).encode_to_vec())
+    }
+
+    fn encode_payload(&self, data: &MarketData) -> Result<Vec<u8>> {
+        Ok(v1::MarketData::from(data).encode_to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Envelope> {
+        v1::Envelope::decode(bytes)
+            .map_err(|e| CodecError::Invalid(e.to_string()))?
+            .try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::{Price, Quantity};
+    use crate::envelope::SCHEMA_VERSION;
+    use crate::models::*;
+    use crate::Timestamp;
+    use std::collections::HashSet;
+
+    const EXCHANGE_MS: i64 = 1_700_000_000_000;
+
+    fn ts() -> Timestamp {
+        Timestamp::from_millis(EXCHANGE_MS)
+    }
+
+    fn ingested() -> Timestamp {
+        Timestamp::from_nanos(EXCHANGE_MS * 1_000_000 + 10_123_456)
+    }
+
+    fn price(text: &str) -> Price {
+        Price(text.parse().unwrap())
+    }
+
+    fn quantity(text: &str) -> Quantity {
+        Quantity(text.parse().unwrap())
+    }
+
+    fn index_price() -> MarketData {
+        MarketData::IndexPrice(IndexPrice {
+            index_name: "btc_usd".to_string(),
+            venue: "deribit".to_string(),
+            price: 43500.5,
+            timestamp: ts(),
+            ingestion_timestamp: Timestamp::from_millis(EXCHANGE_MS + 10),
+            publish_timestamp: None,
+        })
+    }
+
+    /// One message of every `MarketData` variant, with optional fields set
+    fn every_variant() -> Vec<MarketData> {
+        let symbol = "BTC-PERPETUAL".to_string();
+        let venue = "deribit".to_string();
+        let class = Some(InstrumentClass::Perpetual);
+
+        vec![
+            MarketData::Orderbook(OrderBookSnapshot {
+                symbol: symbol.clone(),
+                venue: venue.clone(),
+                bids: vec![(price("43500.5"), quantity("1200")), (price("43500"), quantity("10"))],
+                asks: vec![(price("43501.0"), quantity("0.5"))],
+                seq_id: 1001,
+                instrument_class: class,
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: Some(Timestamp::from_millis(EXCHANGE_MS + 20)),
+            }),
+            MarketData::Trade(TradeSnapshot {
+                symbol: symbol.clone(),
+                venue: venue.clone(),
+                trade_id: "BTC-123456".to_string(),
+                price: price("43500.5"),
+                amount: quantity("250"),
+                side: Side::Sell,
+                seq_id: Some(99),
+                instrument_class: class,
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+                contracts: Some(quantity("25")),
+                index_price: Some(price("43490.12")),
+                mark_price: Some(price("43499.87")),
+                tick_direction: Some(TickDirection::ZeroMinusTick),
+                backfilled: true,
+            }),
+            MarketData::Ticker(Box::new(TickerRow {
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+                venue: venue.clone(),
+                state: InstrumentState::Open,
+                symbol: symbol.clone(),
+                index_price: Some(price("43490.12")),
+                settlement_price: Some(price("43000")),
+                open_interest: Some(quantity("123456789")),
+                mark_price: Some(price("43499.87")),
+                best_bid_price: Some(price("43500.5")),
+                mark_iv: None,
+                ask_iv: None,
+                bid_iv: None,
+                underlying_price: None,
+                underlying_index: Some("btc_usd".to_string()),
+                best_ask_price: Some(price("43501")),
+                interest_rate: Some(0.0),
+                estimated_delivery_price: Some(price("43490.12")),
+                best_ask_amount: Some(quantity("0.5")),
+                best_bid_amount: Some(quantity("1200")),
+                current_funding: Some(0.00012),
+                delivery_price: None,
+                funding_8h: Some(0.0003),
+                interest_value: Some(1.5),
+                greeks_delta: Some(0.5),
+                greeks_gamma: Some(0.0001),
+                greeks_vega: Some(12.5),
+                greeks_theta: Some(-3.25),
+                greeks_rho: Some(0.01),
+            })),
+            MarketData::BookDelta(OrderBookDelta {
+                symbol: symbol.clone(),
+                venue: venue.clone(),
+                bids: vec![BookLevelChange {
+                    action: BookAction::Change,
+                    price: price("43500.5"),
+                    amount: quantity("1300"),
+                }],
+                asks: vec![BookLevelChange {
+                    action: BookAction::Delete,
+                    price: price("43501"),
+                    amount: quantity("0"),
+                }],
+                seq_id: 1002,
+                prev_seq_id: Some(1001),
+                is_snapshot: false,
+                instrument_class: class,
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+            }),
+            index_price(),
+            MarketData::VolatilityIndex(VolatilityIndex {
+                index_name: "btc_usd".to_string(),
+                venue: venue.clone(),
+                volatility: 52.1,
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+            }),
+            MarketData::MarkPrice(MarkPrice {
+                symbol: "BTC-29DEC23-45000-C".to_string(),
+                venue: venue.clone(),
+                mark_price: 0.0125,
+                iv: Some(0.48),
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+            }),
+            MarketData::EstimatedExpirationPrice(EstimatedExpirationPrice {
+                index_name: "btc_usd".to_string(),
+                venue: venue.clone(),
+                price: 43488.2,
+                is_estimated: true,
+                seconds_to_expiry: 3600,
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+            }),
+            MarketData::InstrumentStatus(InstrumentStatus {
+                symbol: "BTC-29DEC23".to_string(),
+                venue: venue.clone(),
+                state: LifecycleState::Settled,
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+            }),
+            MarketData::PlatformState(PlatformState {
+                venue: venue.clone(),
+                price_index: Some("btc_usd".to_string()),
+                locked: Some(true),
+                maintenance: None,
+                allow_unauthenticated_public_requests: Some(false),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+            }),
+            MarketData::Quote(Quote {
+                symbol: symbol.clone(),
+                venue: venue.clone(),
+                best_bid_price: Some(price("43500.5")),
+                best_bid_amount: Some(quantity("1200")),
+                best_ask_price: None,
+                best_ask_amount: None,
+                instrument_class: class,
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+            }),
+            MarketData::Liquidation(Liquidation {
+                symbol: symbol.clone(),
+                venue: venue.clone(),
+                trade_id: "BTC-123457".to_string(),
+                price: price("43400"),
+                amount: quantity("5000"),
+                side: Side::Buy,
+                liquidated: LiquidatedSide::Both,
+                instrument_class: class,
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+            }),
+            MarketData::FundingRate(FundingRate {
+                symbol: symbol.clone(),
+                venue: venue.clone(),
+                current_funding: 0.00012,
+                funding_8h: None,
+                index_price: price("43490.12"),
+                mark_price: price("43499.87"),
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+            }),
+            MarketData::Settlement(Settlement {
+                symbol: "BTC-29DEC23".to_string(),
+                venue,
+                settlement_price: price("43000.25"),
+                delivery_price: Some(price("43000.25")),
+                instrument_class: Some(InstrumentClass::Future),
+                timestamp: ts(),
+                ingestion_timestamp: ingested(),
+                publish_timestamp: None,
+            }),
+        ]
+    }
+
+    fn envelope(sequence: u64, payload: MarketData) -> Envelope {
+        Envelope {
+            schema_version: SCHEMA_VERSION,
+            instance_id: "test".to_string(),
+            connection_id: Some(3),
+            sequence,
+            channel: Some(format!("channel.{}", payload.data_type())),
+            payload,
+        }
+    }
+
+    fn assert_round_trips(codec: &dyn Codec) {
+        for (sequence, payload) in every_variant().into_iter().enumerate() {
+            let envelope = envelope(sequence as u64, payload);
+            let bytes = codec.encode(&envelope).unwrap();
+            let decoded = decode(Some(codec.kind().as_str().as_bytes()), &bytes).unwrap();
+            assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                serde_json::to_value(&envelope).unwrap(),
+                "{} through {}",
+                envelope.payload.data_type(),
+                codec.kind().as_str()
+            );
+        }
+    }
+
+    #[test]
+    fn samples_cover_every_variant() {
+        let data_types: HashSet<&str> = every_variant().iter().map(MarketData::data_type).collect();
+        assert_eq!(data_types.len(), 14);
+    }
+
+    #[test]
+    fn every_variant_round_trips_through_json() {
+        assert_round_trips(&JsonCodec::default());
+    }
+
+    #[test]
+    fn every_variant_round_trips_through_msgpack() {
+        assert_round_trips(&MsgPackCodec::default());
+    }
+
+    #[test]
+    fn every_variant_round_trips_through_protobuf() {
+        assert_round_trips(&ProtobufCodec);
+    }
+
+    #[test]
+    fn decode_follows_the_codec_header() {
+        let envelope = envelope(1, index_price());
+        let json = JsonCodec::default().encode(&envelope).unwrap();
+        let protobuf = ProtobufCodec.encode(&envelope).unwrap();
+
+        // Records without a header are JSON
+        assert_eq!(decode(None, &json).unwrap().sequence, 1);
+        assert_eq!(decode(Some(b"protobuf"), &protobuf).unwrap().sequence, 1);
+        assert!(decode(Some(b"msgpack"), &json).is_err());
+        assert!(decode(Some(b"bogus"), &json).is_err());
+        assert!(decode(Some(b"avro"), &json).is_err());
+    }
+
+    #[test]
+    fn each_codec_writes_its_own_timestamp_format() {
+        let data = index_price();
+        let legacy = JsonCodec::new(TimestampFormat::Legacy).encode_payload(&data).unwrap();
+        let nanos = JsonCodec::new(TimestampFormat::Nanos).encode_payload(&data).unwrap();
+
+        let legacy: serde_json::Value = serde_json::from_slice(&legacy).unwrap();
+        let nanos: serde_json::Value = serde_json::from_slice(&nanos).unwrap();
+        assert_eq!(legacy["timestamp"], "2023-11-14T22:13:20Z");
+        assert_eq!(nanos["timestamp"], 1_700_000_000_000_000_000i64);
+        // Serializing outside a codec is unaffected by either
+        assert_eq!(serde_json::to_value(&data).unwrap()["timestamp"], nanos["timestamp"]);
+    }
+}
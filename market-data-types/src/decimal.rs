@@ -0,0 +1,373 @@
+use crate::errors::DecimalError;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// Largest scale kept; 18 decimals still fit an `i64` mantissa for values below 9.2
+pub const MAX_SCALE: u8 = 18;
+
+/// Exact decimal `mantissa * 10^-scale`.
+///
+/// Serialized as a JSON string (`"0.0005"`) so no float rounding happens on the wire;
+/// `mantissa()` and `scale()` give the scaled-integer form for sinks that prefer it.
+/// Equality and ordering compare the numeric value, so `1.50` equals `1.5`.
+#[derive(Clone, Copy, Default)]
+pub struct FixedPoint {
+    mantissa: i64,
+    scale: u8,
+}
+
+impl FixedPoint {
+    pub fn new(mantissa: i64, scale: u8) -> Self {
+        Self {
+            mantissa,
+            scale: scale.min(MAX_SCALE),
+        }
+    }
+
+    /// Round `value` to `scale` decimals; fails for NaN, infinities and mantissas beyond `i64`
+    pub fn from_f64(value: f64, scale: u8) -> Result<Self, DecimalError> {
+        if !value.is_finite() {
+            return Err(DecimalError::NonFinite(value));
+        }
+        let scale = scale.min(MAX_SCALE);
+        let scaled = (value * 10f64.powi(scale as i32)).round();
+        // `i64::MAX as f64` rounds up to 2^63, which is itself out of range
+        if scaled < i64::MIN as f64 || scaled >= i64::MAX as f64 {
+            return Err(DecimalError::OutOfRange(value, scale));
+        }
+        Ok(Self::new(scaled as i64, scale))
+    }
+
+    /// Shortest decimal that round-trips `value`, i.e. the number as the exchange wrote it
+    pub fn from_f64_exact(value: f64) -> Result<Self, DecimalError> {
+        if !value.is_finite() {
+            return Err(DecimalError::NonFinite(value));
+        }
+        // `Display` for f64 prints the shortest round-trip form and never uses an exponent;
+        // parsing only fails when the integer part alone overflows the mantissa
+        value.to_string().parse().map_err(|_| DecimalError::OutOfRange(value, 0))
+    }
+
+    pub fn mantissa(&self) -> i64 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    /// Same value with trailing zero decimals removed
+    fn normalized(&self) -> Self {
+        let mut value = *self;
+        while value.scale > 0 && value.mantissa % 10 == 0 {
+            value.mantissa /= 10;
+            value.scale -= 1;
+        }
+        value
+    }
+
+    /// Mantissa at a larger scale, widened so it cannot overflow
+    fn widened(&self, scale: u8) -> i128 {
+        self.mantissa as i128 * 10i128.pow((scale - self.scale) as u32)
+    }
+}
+
+impl PartialEq for FixedPoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FixedPoint {}
+
+impl PartialOrd for FixedPoint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FixedPoint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        self.widened(scale).cmp(&other.widened(scale))
+    }
+}
+
+impl Hash for FixedPoint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let normalized = self.normalized();
+        normalized.mantissa.hash(state);
+        normalized.scale.hash(state);
+    }
+}
+
+impl fmt::Display for FixedPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, int, frac)
+    }
+}
+
+impl fmt::Debug for FixedPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl FromStr for FixedPoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid decimal '{}'", s);
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (int, frac) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if int.is_empty() && frac.is_empty() {
+            return Err(invalid());
+        }
+        if !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        // Drop insignificant trailing zeros, then anything beyond MAX_SCALE
+        let frac = frac.trim_end_matches('0');
+        let frac = &frac[..frac.len().min(MAX_SCALE as usize)];
+        let mantissa: i64 = format!("{}{}", int, frac).parse().map_err(|_| invalid())?;
+
+        Ok(Self::new(
+            if negative { -mantissa } else { mantissa },
+            frac.len() as u8,
+        ))
+    }
+}
+
+impl Serialize for FixedPoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for FixedPoint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FixedPointVisitor;
+
+        impl Visitor<'_> for FixedPointVisitor {
+            type Value = FixedPoint;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a decimal string or number")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<FixedPoint, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            // Numbers are accepted so payloads written before the switch still decode
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<FixedPoint, E> {
+                FixedPoint::from_f64_exact(v).map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<FixedPoint, E> {
+                Ok(FixedPoint::new(v, 0))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<FixedPoint, E> {
+                i64::try_from(v)
+                    .map(|v| FixedPoint::new(v, 0))
+                    .map_err(|_| E::custom(format!("decimal {} out of range", v)))
+            }
+        }
+
+        deserializer.deserialize_any(FixedPointVisitor)
+    }
+}
+
+/// Price on an instrument's tick grid
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Price(pub FixedPoint);
+
+/// Amount in an instrument's trade-size units
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Quantity(pub FixedPoint);
+
+impl Price {
+    /// Round to `scale` decimals, the tick size's precision
+    pub fn from_f64(value: f64, scale: u8) -> Result<Self, DecimalError> {
+        FixedPoint::from_f64(value, scale).map(Self)
+    }
+
+    /// Keep every decimal of `value`, for prices not on a tick grid (index, mark)
+    pub fn exact(value: f64) -> Result<Self, DecimalError> {
+        FixedPoint::from_f64_exact(value).map(Self)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.0.to_f64()
+    }
+}
+
+impl Quantity {
+    /// Round to `scale` decimals, the minimum trade amount's precision
+    pub fn from_f64(value: f64, scale: u8) -> Result<Self, DecimalError> {
+        FixedPoint::from_f64(value, scale).map(Self)
+    }
+
+    pub fn exact(value: f64) -> Result<Self, DecimalError> {
+        FixedPoint::from_f64_exact(value).map(Self)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.0.to_f64()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.mantissa() == 0
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Number of decimals needed to write `step` (a tick size or minimum amount) exactly
+pub fn scale_of(step: f64) -> Result<u8, DecimalError> {
+    FixedPoint::from_f64_exact(step).map(|step| step.normalized().scale())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> FixedPoint {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_plain_decimals() {
+        let value = parse("43500.25");
+        assert_eq!((value.mantissa(), value.scale()), (4350025, 2));
+
+        let value = parse("-0.0005");
+        assert_eq!((value.mantissa(), value.scale()), (-5, 4));
+
+        assert_eq!(parse(".5"), parse("0.5"));
+        assert_eq!(parse("7."), FixedPoint::new(7, 0));
+        assert_eq!(parse("1.500").scale(), 1);
+    }
+
+    #[test]
+    fn rejects_malformed_decimals() {
+        for input in ["", ".", "-", "1e5", "1.2.3", "abc", "+1", " 1", "1_000", "99999999999999999999"] {
+            assert!(input.parse::<FixedPoint>().is_err(), "{:?} should not parse", input);
+        }
+    }
+
+    #[test]
+    fn caps_scale_at_max() {
+        assert_eq!(FixedPoint::new(1, 30).scale(), MAX_SCALE);
+
+        let value = parse("0.1234567890123456789999");
+        assert_eq!(value.scale(), MAX_SCALE);
+        assert_eq!(value.to_string(), "0.123456789012345678");
+
+        assert_eq!(FixedPoint::from_f64(1.0, 25).unwrap(), FixedPoint::new(1, 0));
+    }
+
+    #[test]
+    fn rounds_half_away_from_zero() {
+        assert_eq!(FixedPoint::from_f64(0.125, 2).unwrap().to_string(), "0.13");
+        assert_eq!(FixedPoint::from_f64(-0.125, 2).unwrap().to_string(), "-0.13");
+        assert_eq!(FixedPoint::from_f64(43500.46, 1).unwrap().to_string(), "43500.5");
+        assert_eq!(FixedPoint::from_f64(0.1 + 0.2, 1).unwrap().to_string(), "0.3");
+    }
+
+    #[test]
+    fn exact_keeps_the_shortest_round_trip_form() {
+        assert_eq!(FixedPoint::from_f64_exact(0.0005).unwrap().to_string(), "0.0005");
+        assert_eq!(FixedPoint::from_f64_exact(2500.05).unwrap().to_string(), "2500.05");
+        assert_eq!(FixedPoint::from_f64_exact(-3.0).unwrap().to_string(), "-3");
+        assert_eq!(scale_of(0.0001).unwrap(), 4);
+        assert_eq!(scale_of(2.5).unwrap(), 1);
+        assert_eq!(scale_of(10.0).unwrap(), 0);
+    }
+
+    #[test]
+    fn rejects_non_finite_floats() {
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(FixedPoint::from_f64(value, 2), Err(DecimalError::NonFinite(_))));
+            assert!(matches!(FixedPoint::from_f64_exact(value), Err(DecimalError::NonFinite(_))));
+            assert!(Price::exact(value).is_err());
+            assert!(Quantity::from_f64(value, 0).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_floats_beyond_the_mantissa() {
+        assert_eq!(FixedPoint::from_f64(10.0, 18), Err(DecimalError::OutOfRange(10.0, 18)));
+        assert_eq!(FixedPoint::from_f64(-1e19, 0), Err(DecimalError::OutOfRange(-1e19, 0)));
+        assert_eq!(FixedPoint::from_f64_exact(1e300), Err(DecimalError::OutOfRange(1e300, 0)));
+        assert!(FixedPoint::from_f64(9.0, 18).is_ok());
+    }
+
+    #[test]
+    fn compares_by_value() {
+        assert_eq!(parse("1.50"), parse("1.5"));
+        assert!(parse("0.1") < parse("0.10001"));
+        assert!(parse("-2") < parse("-1.999"));
+
+        let mut seen = std::collections::HashSet::new();
+        seen.insert(FixedPoint::new(150, 2));
+        assert!(seen.contains(&FixedPoint::new(15, 1)));
+    }
+
+    #[test]
+    fn serializes_as_a_string() {
+        let price = Price::from_f64(43500.5, 1).unwrap();
+        assert_eq!(serde_json::to_string(&price).unwrap(), "\"43500.5\"");
+        assert_eq!(serde_json::to_string(&FixedPoint::new(-5, 4)).unwrap(), "\"-0.0005\"");
+        assert_eq!(serde_json::to_string(&FixedPoint::new(0, 0)).unwrap(), "\"0\"");
+    }
+
+    #[test]
+    fn deserializes_strings_and_legacy_numbers() {
+        let from_str: Quantity = serde_json::from_str("\"0.0001\"").unwrap();
+        assert_eq!(from_str, Quantity::exact(0.0001).unwrap());
+
+        let from_float: Price = serde_json::from_str("2500.05").unwrap();
+        assert_eq!(from_float.to_string(), "2500.05");
+
+        let from_int: Price = serde_json::from_str("42").unwrap();
+        assert_eq!(from_int, Price(FixedPoint::new(42, 0)));
+
+        assert!(serde_json::from_str::<Price>("\"1.2.3\"").is_err());
+        assert!(serde_json::from_str::<Price>("18446744073709551615").is_err());
+    }
+}
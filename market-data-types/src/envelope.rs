@@ -0,0 +1,123 @@
+use crate::models::MarketData;
+use serde::{Deserialize, Serialize};
+
+/// Version of the envelope and payload layout written by the collector
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `schema_version` reported for payloads written before envelopes existed
+pub const LEGACY_SCHEMA_VERSION: u32 = 0;
+
+/// A `MarketData` message plus where it came from and its position in its stream.
+///
+/// `sequence` counts up by one per (venue, symbol, data_type) for the lifetime of the
+/// producing instance, so a consumer that sees a jump knows messages were lost between the
+/// exchange connection and the topic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub schema_version: u32,
+    /// Collector instance that produced the message
+    pub instance_id: String,
+    /// Pool connection the frame arrived on; None for legacy payloads
+    pub connection_id: Option<usize>,
+    pub sequence: u64,
+    /// Exchange channel the data was received on, e.g. `trades.BTC-PERPETUAL.100ms`
+    pub channel: Option<String>,
+    pub payload: MarketData,
+}
+
+/// Either form found on the wire
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WireMessage {
+    Enveloped(Envelope),
+    Legacy(MarketData),
+}
+
+impl Envelope {
+    /// Decode an enveloped payload or a bare legacy `MarketData`
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        Ok(match serde_json::from_str(json)? {
+            WireMessage::Enveloped(envelope) => envelope,
+            WireMessage::Legacy(payload) => Envelope {
+                schema_version: LEGACY_SCHEMA_VERSION,
+                instance_id: String::new(),
+                connection_id: None,
+                sequence: 0,
+                channel: None,
+                payload,
+            },
+        })
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.schema_version == LEGACY_SCHEMA_VERSION
+    }
+
+    /// Copy with the payload stamped with the current publish time
+    pub fn published(&self) -> Envelope {
+        Envelope {
+            payload: self.payload.published(),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::IndexPrice;
+    use crate::Timestamp;
+
+    fn index_price() -> MarketData {
+        MarketData::IndexPrice(IndexPrice {
+            index_name: "btc_usd".to_string(),
+            venue: "deribit".to_string(),
+            price: 43500.5,
+            timestamp: Timestamp::from_millis(1_700_000_000_000),
+            ingestion_timestamp: Timestamp::from_millis(1_700_000_000_010),
+            publish_timestamp: None,
+        })
+    }
+
+    #[test]
+    fn bare_payloads_decode_as_legacy() {
+        let json = serde_json::to_string(&index_price()).unwrap();
+        let envelope = Envelope::from_json(&json).unwrap();
+
+        assert!(envelope.is_legacy());
+        assert_eq!(envelope.schema_version, LEGACY_SCHEMA_VERSION);
+        assert_eq!(envelope.instance_id, "");
+        assert_eq!(envelope.connection_id, None);
+        assert_eq!(envelope.sequence, 0);
+        assert_eq!(envelope.channel, None);
+        assert_eq!(
+            serde_json::to_value(&envelope.payload).unwrap(),
+            serde_json::to_value(index_price()).unwrap()
+        );
+    }
+
+    #[test]
+    fn enveloped_payloads_keep_their_metadata() {
+        let envelope = Envelope {
+            schema_version: SCHEMA_VERSION,
+            instance_id: "collector-1".to_string(),
+            connection_id: Some(2),
+            sequence: 7,
+            channel: Some("deribit_price_index.btc_usd".to_string()),
+            payload: index_price(),
+        };
+        let decoded = Envelope::from_json(&serde_json::to_string(&envelope).unwrap()).unwrap();
+
+        assert!(!decoded.is_legacy());
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&envelope).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_json_that_is_neither_form() {
+        assert!(Envelope::from_json(r#"{"data_type":"unknown"}"#).is_err());
+        assert!(Envelope::from_json("not json").is_err());
+    }
+}
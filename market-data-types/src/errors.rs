@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CodecError {
+    #[error("JSON parse error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid message: {0}")]
+    Invalid(String),
+}
+
+/// A float that has no exact decimal form
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+pub enum DecimalError {
+    #[error("Non-finite decimal: {0}")]
+    NonFinite(f64),
+
+    #[error("Decimal {0} does not fit at scale {1}")]
+    OutOfRange(f64, u8),
+}
+
+pub type Result<T> = std::result::Result<T, CodecError>;
@@ -0,0 +1,20 @@
+//! Names of the metadata headers the collector adds to Kafka records, so consumers can
+//! filter and route without decoding the payload. Every value is UTF-8 text.
+//!
+//! Only `codec` (`CODEC_HEADER`) is always present; the rest can be switched off per
+//! deployment and are absent on records written before they existed.
+
+/// `MarketData::data_type`, e.g. `trade`
+pub const DATA_TYPE_HEADER: &str = "data_type";
+pub const VENUE_HEADER: &str = "venue";
+/// `MarketData::stream_key`: instrument or index name, `platform` for platform state
+pub const SYMBOL_HEADER: &str = "symbol";
+/// `Envelope::schema_version` as a decimal string
+pub const SCHEMA_VERSION_HEADER: &str = "schema_version";
+/// MIME type of the payload, see `CodecKind::content_type`
+pub const CONTENT_TYPE_HEADER: &str = "content-type";
+/// Exchange time in nanoseconds since the epoch, as a decimal string; absent for messages
+/// that carry no exchange time
+pub const EXCHANGE_TIMESTAMP_HEADER: &str = "exchange_timestamp";
+/// W3C trace context (`00-{trace_id}-{span_id}-{flags}`) starting a trace for the record
+pub const TRACEPARENT_HEADER: &str = "traceparent";
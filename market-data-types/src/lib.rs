@@ -0,0 +1,19 @@
+//! Venue-neutral market data model, wire codecs and topic/key naming, shared by the
+//! collector and the services that consume its topics.
+
+pub mod codec;
+pub mod decimal;
+pub mod envelope;
+pub mod errors;
+pub mod headers;
+pub mod models;
+pub mod naming;
+pub mod proto;
+pub mod timestamp;
+
+pub use codec::{Codec, CodecKind, CODEC_HEADER};
+pub use decimal::{FixedPoint, Price, Quantity};
+pub use envelope::Envelope;
+pub use errors::{CodecError, DecimalError, Result};
+pub use models::MarketData;
+pub use timestamp::{Timestamp, TimestampFormat};
@@ -0,0 +1,641 @@
+use crate::decimal::{Price, Quantity};
+use crate::errors::CodecError;
+use crate::timestamp::{self, Timestamp};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Unified Kafka schema for market data
+// All data types include common metadata fields for efficient querying and partitioning
+// Timestamps are nanoseconds since the epoch: `timestamp` is exchange time, `ingestion_timestamp`
+// the moment the frame arrived, `publish_timestamp` the moment a sink wrote the message
+
+/// Taker side of a trade, serialized as `"buy"` / `"sell"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// Whether an instrument is trading, serialized as `1` (open) / `0` (closed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstrumentState {
+    Closed,
+    Open,
+}
+
+impl InstrumentState {
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            InstrumentState::Closed => 0,
+            InstrumentState::Open => 1,
+        }
+    }
+}
+
+impl Serialize for InstrumentState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.as_u8())
+    }
+}
+
+impl<'de> Deserialize<'de> for InstrumentState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        match u8::deserialize(deserializer)? {
+            0 => Ok(InstrumentState::Closed),
+            1 => Ok(InstrumentState::Open),
+            other => Err(serde::de::Error::custom(format!("invalid instrument state {}", other))),
+        }
+    }
+}
+
+/// Instrument lifecycle stage from `instrument.state.{kind}.{currency}`, serialized in lowercase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LifecycleState {
+    Created,
+    Started,
+    Settled,
+    Closed,
+    Terminated,
+}
+
+impl LifecycleState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LifecycleState::Created => "created",
+            LifecycleState::Started => "started",
+            LifecycleState::Settled => "settled",
+            LifecycleState::Closed => "closed",
+            LifecycleState::Terminated => "terminated",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "created" => Some(LifecycleState::Created),
+            "started" => Some(LifecycleState::Started),
+            "settled" => Some(LifecycleState::Settled),
+            "closed" => Some(LifecycleState::Closed),
+            "terminated" => Some(LifecycleState::Terminated),
+            _ => None,
+        }
+    }
+}
+
+// Written as a plain string so every codec, Avro included, keeps the pre-enum wire format
+impl Serialize for LifecycleState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for LifecycleState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        LifecycleState::from_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid instrument lifecycle state {}", name)))
+    }
+}
+
+/// Direction of the tick relative to the previous trade, serialized as Deribit's code (0-3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TickDirection {
+    PlusTick,
+    ZeroPlusTick,
+    MinusTick,
+    ZeroMinusTick,
+}
+
+impl TickDirection {
+    pub fn code(&self) -> i32 {
+        match self {
+            TickDirection::PlusTick => 0,
+            TickDirection::ZeroPlusTick => 1,
+            TickDirection::MinusTick => 2,
+            TickDirection::ZeroMinusTick => 3,
+        }
+    }
+}
+
+/// Deribit's `tick_direction` code; anything outside 0-3 is an error
+impl TryFrom<i64> for TickDirection {
+    type Error = CodecError;
+
+    fn try_from(code: i64) -> std::result::Result<Self, CodecError> {
+        match code {
+            0 => Ok(TickDirection::PlusTick),
+            1 => Ok(TickDirection::ZeroPlusTick),
+            2 => Ok(TickDirection::MinusTick),
+            3 => Ok(TickDirection::ZeroMinusTick),
+            _ => Err(CodecError::Invalid(format!("invalid tick direction {}", code))),
+        }
+    }
+}
+
+impl Serialize for TickDirection {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_i32(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for TickDirection {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let code = i64::deserialize(deserializer)?;
+        TickDirection::try_from(code).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Instrument family, serialized in lowercase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstrumentClass {
+    Perpetual,
+    Future,
+    Option,
+    Spot,
+    Combo,
+}
+
+impl InstrumentClass {
+    /// Classify a Deribit instrument name.
+    ///
+    /// `BTC_USDC` is spot, `BTC-PERPETUAL` perpetual, `BTC-27DEC24` a future,
+    /// `BTC-27DEC24-100000-C` an option and `BTC-FS-27DEC24_PERP` (any alphabetic second
+    /// segment) a combo. None when the name fits none of these shapes.
+    pub fn from_instrument_name(name: &str) -> Option<Self> {
+        fn starts_with_digit(segment: &str) -> bool {
+            segment.starts_with(|c: char| c.is_ascii_digit())
+        }
+
+        let parts: Vec<&str> = name.split('-').collect();
+
+        match parts.as_slice() {
+            [pair] if pair.contains('_') => Some(InstrumentClass::Spot),
+            [_, "PERPETUAL"] => Some(InstrumentClass::Perpetual),
+            [_, expiry] if starts_with_digit(expiry) => Some(InstrumentClass::Future),
+            [_, expiry, _, "C" | "P"] if starts_with_digit(expiry) => Some(InstrumentClass::Option),
+            [_, strategy, ..] if strategy.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                Some(InstrumentClass::Combo)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Order book snapshot data
+/// Represents the current state of the order book for an instrument
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookSnapshot {
+    pub symbol: String,
+    pub venue: String,
+    pub bids: Vec<(Price, Quantity)>,
+    pub asks: Vec<(Price, Quantity)>,
+    pub seq_id: u64,
+    pub instrument_class: Option<InstrumentClass>,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Action applied to a single price level by an incremental book update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BookAction {
+    New,
+    Change,
+    Delete,
+}
+
+/// One price level change within an order book delta
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookLevelChange {
+    pub action: BookAction,
+    pub price: Price,
+    pub amount: Quantity,
+}
+
+/// Incremental order book update
+/// `is_snapshot` marks a full book that replaces any state built so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookDelta {
+    pub symbol: String,
+    pub venue: String,
+    pub bids: Vec<BookLevelChange>,
+    pub asks: Vec<BookLevelChange>,
+    pub seq_id: u64,
+    pub prev_seq_id: Option<u64>,
+    pub is_snapshot: bool,
+    pub instrument_class: Option<InstrumentClass>,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Trade execution data
+/// Represents an individual trade that occurred on the exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeSnapshot {
+    pub symbol: String,
+    pub venue: String,
+    pub trade_id: String,
+    pub price: Price,
+    pub amount: Quantity,
+    pub side: Side,
+    pub seq_id: Option<u64>,
+    pub instrument_class: Option<InstrumentClass>,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+
+    // Extended Deribit fields
+    pub contracts: Option<Quantity>,  // Trade size in contract units
+    pub index_price: Option<Price>,   // Index Price at the moment of trade
+    pub mark_price: Option<Price>,    // Mark Price at the moment of trade
+    pub tick_direction: Option<TickDirection>,
+    /// Fetched over REST after a reconnect rather than received live
+    #[serde(default)]
+    pub backfilled: bool,
+}
+
+/// Ticker data with comprehensive market information (flattened structure)
+/// Represents the current market state for an instrument
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerRow {
+    #[serde(with = "timestamp::legacy_millis")]
+    pub timestamp: Timestamp,
+    #[serde(with = "timestamp::legacy_secs")]
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+    pub venue: String,
+    pub state: InstrumentState,
+    pub symbol: String,
+    pub index_price: Option<Price>,
+    pub settlement_price: Option<Price>,
+    pub open_interest: Option<Quantity>,
+    pub mark_price: Option<Price>,
+    pub best_bid_price: Option<Price>,
+    pub mark_iv: Option<f64>,
+    pub ask_iv: Option<f64>,
+    pub bid_iv: Option<f64>,
+    pub underlying_price: Option<Price>,
+    pub underlying_index: Option<String>,
+    pub best_ask_price: Option<Price>,
+    pub interest_rate: Option<f64>,
+    pub estimated_delivery_price: Option<Price>,
+    pub best_ask_amount: Option<Quantity>,
+    pub best_bid_amount: Option<Quantity>,
+    pub current_funding: Option<f64>,
+    pub delivery_price: Option<Price>,
+    pub funding_8h: Option<f64>,
+    pub interest_value: Option<f64>,
+    pub greeks_delta: Option<f64>,
+    pub greeks_gamma: Option<f64>,
+    pub greeks_vega: Option<f64>,
+    pub greeks_theta: Option<f64>,
+    pub greeks_rho: Option<f64>,
+}
+
+/// Deribit price index value (`deribit_price_index.{index_name}`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexPrice {
+    pub index_name: String,
+    pub venue: String,
+    pub price: f64,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Volatility index value (`deribit_volatility_index.{index_name}`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolatilityIndex {
+    pub index_name: String,
+    pub venue: String,
+    pub volatility: f64,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Option mark price and implied volatility (`markprice.options.{index_name}`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkPrice {
+    pub symbol: String,
+    pub venue: String,
+    pub mark_price: f64,
+    pub iv: Option<f64>,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Expected delivery price ahead of expiry (`estimated_expiration_price.{index_name}`)
+/// The channel carries no exchange time, so `timestamp` is the receive time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimatedExpirationPrice {
+    pub index_name: String,
+    pub venue: String,
+    pub price: f64,
+    pub is_estimated: bool,
+    pub seconds_to_expiry: i64,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Instrument lifecycle change (`instrument.state.{kind}.{currency}`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstrumentStatus {
+    pub symbol: String,
+    pub venue: String,
+    pub state: LifecycleState,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Platform-wide notification (`platform_state`)
+/// Only the fields present in the notification are set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformState {
+    pub venue: String,
+    pub price_index: Option<String>,
+    pub locked: Option<bool>,
+    pub maintenance: Option<bool>,
+    pub allow_unauthenticated_public_requests: Option<bool>,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Top-of-book quote from the `quote` channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quote {
+    pub symbol: String,
+    pub venue: String,
+    pub best_bid_price: Option<Price>,
+    pub best_bid_amount: Option<Quantity>,
+    pub best_ask_price: Option<Price>,
+    pub best_ask_amount: Option<Quantity>,
+    pub instrument_class: Option<InstrumentClass>,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Which side of a trade was a forced liquidation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiquidatedSide {
+    Maker,
+    Taker,
+    Both,
+}
+
+impl LiquidatedSide {
+    /// Deribit's `liquidation` trade flag: `M`, `T` or `MT`
+    pub fn from_flag(flag: &str) -> Option<Self> {
+        match flag {
+            "M" => Some(LiquidatedSide::Maker),
+            "T" => Some(LiquidatedSide::Taker),
+            "MT" => Some(LiquidatedSide::Both),
+            _ => None,
+        }
+    }
+}
+
+/// A trade in which at least one side was liquidated; also published as a regular trade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Liquidation {
+    pub symbol: String,
+    pub venue: String,
+    pub trade_id: String,
+    pub price: Price,
+    pub amount: Quantity,
+    /// Taker side of the liquidation trade
+    pub side: Side,
+    pub liquidated: LiquidatedSide,
+    pub instrument_class: Option<InstrumentClass>,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Perpetual funding, taken from the perpetual's ticker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub symbol: String,
+    pub venue: String,
+    pub current_funding: f64,
+    pub funding_8h: Option<f64>,
+    pub index_price: Price,
+    pub mark_price: Price,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// A new settlement price seen on an instrument's ticker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settlement {
+    pub symbol: String,
+    pub venue: String,
+    pub settlement_price: Price,
+    /// Set once the instrument has expired
+    pub delivery_price: Option<Price>,
+    pub instrument_class: Option<InstrumentClass>,
+    pub timestamp: Timestamp,
+    pub ingestion_timestamp: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_timestamp: Option<Timestamp>,
+}
+
+/// Unified market data enum for streaming
+/// Tagged with type for easy deserialization and routing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "data_type", rename_all = "lowercase")]
+pub enum MarketData {
+    Orderbook(OrderBookSnapshot),
+    Trade(TradeSnapshot),
+    /// Boxed: a ticker is more than twice the size of any other variant
+    Ticker(Box<TickerRow>),
+    #[serde(rename = "book_delta")]
+    BookDelta(OrderBookDelta),
+    #[serde(rename = "index_price")]
+    IndexPrice(IndexPrice),
+    #[serde(rename = "volatility_index")]
+    VolatilityIndex(VolatilityIndex),
+    #[serde(rename = "mark_price")]
+    MarkPrice(MarkPrice),
+    #[serde(rename = "estimated_expiration_price")]
+    EstimatedExpirationPrice(EstimatedExpirationPrice),
+    #[serde(rename = "instrument_status")]
+    InstrumentStatus(InstrumentStatus),
+    #[serde(rename = "platform_state")]
+    PlatformState(PlatformState),
+    Quote(Quote),
+    Liquidation(Liquidation),
+    #[serde(rename = "funding_rate")]
+    FundingRate(FundingRate),
+    Settlement(Settlement),
+}
+
+impl MarketData {
+    /// The `data_type` tag this variant is serialized with
+    pub fn data_type(&self) -> &'static str {
+        match self {
+            MarketData::Orderbook(_) => "orderbook",
+            MarketData::Trade(_) => "trade",
+            MarketData::Ticker(_) => "ticker",
+            MarketData::BookDelta(_) => "book_delta",
+            MarketData::IndexPrice(_) => "index_price",
+            MarketData::VolatilityIndex(_) => "volatility_index",
+            MarketData::MarkPrice(_) => "mark_price",
+            MarketData::EstimatedExpirationPrice(_) => "estimated_expiration_price",
+            MarketData::InstrumentStatus(_) => "instrument_status",
+            MarketData::PlatformState(_) => "platform_state",
+            MarketData::Quote(_) => "quote",
+            MarketData::Liquidation(_) => "liquidation",
+            MarketData::FundingRate(_) => "funding_rate",
+            MarketData::Settlement(_) => "settlement",
+        }
+    }
+
+    pub fn venue(&self) -> &str {
+        match self {
+            MarketData::Orderbook(d) => &d.venue,
+            MarketData::Trade(d) => &d.venue,
+            MarketData::Ticker(d) => &d.venue,
+            MarketData::BookDelta(d) => &d.venue,
+            MarketData::IndexPrice(d) => &d.venue,
+            MarketData::VolatilityIndex(d) => &d.venue,
+            MarketData::MarkPrice(d) => &d.venue,
+            MarketData::EstimatedExpirationPrice(d) => &d.venue,
+            MarketData::InstrumentStatus(d) => &d.venue,
+            MarketData::PlatformState(d) => &d.venue,
+            MarketData::Quote(d) => &d.venue,
+            MarketData::Liquidation(d) => &d.venue,
+            MarketData::FundingRate(d) => &d.venue,
+            MarketData::Settlement(d) => &d.venue,
+        }
+    }
+
+    /// Instrument or index the message is about; `platform` for platform notifications
+    pub fn stream_key(&self) -> &str {
+        match self {
+            MarketData::Orderbook(d) => &d.symbol,
+            MarketData::Trade(d) => &d.symbol,
+            MarketData::Ticker(d) => &d.symbol,
+            MarketData::BookDelta(d) => &d.symbol,
+            MarketData::IndexPrice(d) => &d.index_name,
+            MarketData::VolatilityIndex(d) => &d.index_name,
+            MarketData::MarkPrice(d) => &d.symbol,
+            MarketData::EstimatedExpirationPrice(d) => &d.index_name,
+            MarketData::InstrumentStatus(d) => &d.symbol,
+            MarketData::PlatformState(_) => "platform",
+            MarketData::Quote(d) => &d.symbol,
+            MarketData::Liquidation(d) => &d.symbol,
+            MarketData::FundingRate(d) => &d.symbol,
+            MarketData::Settlement(d) => &d.symbol,
+        }
+    }
+
+    /// Exchange time of the message; None for platform notifications, which carry none
+    pub fn exchange_timestamp(&self) -> Option<Timestamp> {
+        match self {
+            MarketData::Orderbook(d) => Some(d.timestamp),
+            MarketData::Trade(d) => Some(d.timestamp),
+            MarketData::Ticker(d) => Some(d.timestamp),
+            MarketData::BookDelta(d) => Some(d.timestamp),
+            MarketData::IndexPrice(d) => Some(d.timestamp),
+            MarketData::VolatilityIndex(d) => Some(d.timestamp),
+            MarketData::MarkPrice(d) => Some(d.timestamp),
+            MarketData::EstimatedExpirationPrice(d) => Some(d.timestamp),
+            MarketData::InstrumentStatus(d) => Some(d.timestamp),
+            MarketData::PlatformState(_) => None,
+            MarketData::Quote(d) => Some(d.timestamp),
+            MarketData::Liquidation(d) => Some(d.timestamp),
+            MarketData::FundingRate(d) => Some(d.timestamp),
+            MarketData::Settlement(d) => Some(d.timestamp),
+        }
+    }
+
+    /// Copy stamped with the current time as `publish_timestamp`
+    pub fn published(&self) -> MarketData {
+        let mut data = self.clone();
+        *data.publish_timestamp_mut() = Some(Timestamp::now());
+        data
+    }
+
+    fn publish_timestamp_mut(&mut self) -> &mut Option<Timestamp> {
+        match self {
+            MarketData::Orderbook(d) => &mut d.publish_timestamp,
+            MarketData::Trade(d) => &mut d.publish_timestamp,
+            MarketData::Ticker(d) => &mut d.publish_timestamp,
+            MarketData::BookDelta(d) => &mut d.publish_timestamp,
+            MarketData::IndexPrice(d) => &mut d.publish_timestamp,
+            MarketData::VolatilityIndex(d) => &mut d.publish_timestamp,
+            MarketData::MarkPrice(d) => &mut d.publish_timestamp,
+            MarketData::EstimatedExpirationPrice(d) => &mut d.publish_timestamp,
+            MarketData::InstrumentStatus(d) => &mut d.publish_timestamp,
+            MarketData::PlatformState(d) => &mut d.publish_timestamp,
+            MarketData::Quote(d) => &mut d.publish_timestamp,
+            MarketData::Liquidation(d) => &mut d.publish_timestamp,
+            MarketData::FundingRate(d) => &mut d.publish_timestamp,
+            MarketData::Settlement(d) => &mut d.publish_timestamp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifecycle_state_is_written_as_its_lowercase_name() {
+        for state in [
+            LifecycleState::Created,
+            LifecycleState::Started,
+            LifecycleState::Settled,
+            LifecycleState::Closed,
+            LifecycleState::Terminated,
+        ] {
+            let json = serde_json::to_string(&state).unwrap();
+            assert_eq!(json, format!("\"{}\"", state.as_str()));
+            assert_eq!(serde_json::from_str::<LifecycleState>(&json).unwrap(), state);
+        }
+        assert_eq!(serde_json::to_string(&LifecycleState::Settled).unwrap(), "\"settled\"");
+    }
+
+    #[test]
+    fn tick_direction_round_trips_through_its_code() {
+        for code in 0..=3 {
+            let direction = TickDirection::try_from(code).unwrap();
+            assert_eq!(direction.code() as i64, code);
+            assert_eq!(serde_json::to_string(&direction).unwrap(), code.to_string());
+        }
+    }
+
+    #[test]
+    fn unknown_tick_direction_is_rejected() {
+        assert!(TickDirection::try_from(4).is_err());
+        assert!(TickDirection::try_from(-1).is_err());
+        assert!(serde_json::from_str::<TickDirection>("7").is_err());
+    }
+
+    #[test]
+    fn unknown_lifecycle_state_is_rejected() {
+        assert!(serde_json::from_str::<LifecycleState>("\"deactivated\"").is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use crate::models::MarketData;
+
+// Default topic names; deployments may override any of them in the collector's `[kafka]` config
+pub const ORDERBOOK_TOPIC: &str = "market-data-orderbook";
+pub const TRADE_TOPIC: &str = "market-data-trades";
+pub const TICKER_TOPIC: &str = "market-data-ticker";
+pub const INDEX_PRICE_TOPIC: &str = "market-data-index-price";
+pub const VOLATILITY_INDEX_TOPIC: &str = "market-data-volatility-index";
+pub const MARK_PRICE_TOPIC: &str = "market-data-mark-price";
+pub const ESTIMATED_EXPIRATION_PRICE_TOPIC: &str = "market-data-estimated-expiration-price";
+pub const INSTRUMENT_STATE_TOPIC: &str = "market-data-instrument-state";
+pub const PLATFORM_STATE_TOPIC: &str = "market-data-platform-state";
+pub const QUOTE_TOPIC: &str = "market-data-quotes";
+pub const LIQUIDATION_TOPIC: &str = "market-data-liquidations";
+pub const FUNDING_RATE_TOPIC: &str = "market-data-funding-rate";
+pub const SETTLEMENT_TOPIC: &str = "market-data-settlements";
+/// Records that failed permanently, with the failure in `dlq.*` headers
+pub const DEAD_LETTER_TOPIC: &str = "market-data-dead-letter";
+
+/// Topic a message is published to under the default names
+pub fn default_topic(data: &MarketData) -> &'static str {
+    match data {
+        // Deltas share the book topic so they stay ordered with the snapshots
+        MarketData::Orderbook(_) | MarketData::BookDelta(_) => ORDERBOOK_TOPIC,
+        MarketData::Trade(_) => TRADE_TOPIC,
+        MarketData::Ticker(_) => TICKER_TOPIC,
+        MarketData::IndexPrice(_) => INDEX_PRICE_TOPIC,
+        MarketData::VolatilityIndex(_) => VOLATILITY_INDEX_TOPIC,
+        MarketData::MarkPrice(_) => MARK_PRICE_TOPIC,
+        MarketData::EstimatedExpirationPrice(_) => ESTIMATED_EXPIRATION_PRICE_TOPIC,
+        MarketData::InstrumentStatus(_) => INSTRUMENT_STATE_TOPIC,
+        MarketData::PlatformState(_) => PLATFORM_STATE_TOPIC,
+        MarketData::Quote(_) => QUOTE_TOPIC,
+        MarketData::Liquidation(_) => LIQUIDATION_TOPIC,
+        MarketData::FundingRate(_) => FUNDING_RATE_TOPIC,
+        MarketData::Settlement(_) => SETTLEMENT_TOPIC,
+    }
+}
+
+/// Kafka record key, `<venue>.<symbol or index>`, so each stream stays on one partition
+pub fn kafka_key(data: &MarketData) -> String {
+    format!("{}.{}", data.venue(), data.stream_key())
+}
+
+/// Redis key holding the latest value, `<venue>:<symbol or index>:<kind>`.
+/// None for book deltas, which are not a state on their own.
+pub fn redis_key(data: &MarketData) -> Option<String> {
+    let kind = match data {
+        MarketData::BookDelta(_) => return None,
+        MarketData::PlatformState(d) => return Some(format!("{}:platform_state", d.venue)),
+        MarketData::Orderbook(_) => "orderbook",
+        MarketData::Trade(_) => "last_trade",
+        MarketData::Ticker(_) => "ticker",
+        MarketData::IndexPrice(_) => "index_price",
+        MarketData::VolatilityIndex(_) => "volatility_index",
+        MarketData::MarkPrice(_) => "mark_price",
+        MarketData::EstimatedExpirationPrice(_) => "estimated_expiration_price",
+        MarketData::InstrumentStatus(_) => "instrument_state",
+        MarketData::Quote(_) => "quote",
+        MarketData::Liquidation(_) => "last_liquidation",
+        MarketData::FundingRate(_) => "funding_rate",
+        MarketData::Settlement(_) => "settlement",
+    };
+    Some(format!("{}:{}:{}", data.venue(), data.stream_key(), kind))
+}
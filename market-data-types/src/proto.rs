@@ -0,0 +1,575 @@
+use crate::decimal::{FixedPoint, Price, Quantity};
+use crate::envelope::Envelope;
+use crate::errors::{CodecError, Result};
+use crate::models::{
+    BookAction, BookLevelChange, EstimatedExpirationPrice, FundingRate, IndexPrice,
+    InstrumentClass, InstrumentState, InstrumentStatus, LifecycleState, LiquidatedSide,
+    Liquidation, MarkPrice, MarketData, OrderBookDelta, OrderBookSnapshot, PlatformState, Quote,
+    Settlement, Side, TickDirection, TickerRow, TradeSnapshot, VolatilityIndex,
+};
+use crate::timestamp::Timestamp;
+
+/// Types generated from `proto/market_data.proto`
+#[allow(clippy::all)]
+pub mod v1 {
+    include!(concat!(env!("OUT_DIR"), "/market_data.v1.rs"));
+}
+
+use v1::market_data::Data;
+
+fn decode_error(message: impl Into<String>) -> CodecError {
+    CodecError::Invalid(message.into())
+}
+
+fn decimal(text: &str) -> Result<FixedPoint> {
+    text.parse().map_err(decode_error)
+}
+
+fn price(text: &str) -> Result<Price> {
+    decimal(text).map(Price)
+}
+
+fn quantity(text: &str) -> Result<Quantity> {
+    decimal(text).map(Quantity)
+}
+
+fn optional_price(text: Option<String>) -> Result<Option<Price>> {
+    text.as_deref().map(price).transpose()
+}
+
+fn optional_quantity(text: Option<String>) -> Result<Option<Quantity>> {
+    text.as_deref().map(quantity).transpose()
+}
+
+fn nanos(ts: Option<Timestamp>) -> Option<i64> {
+    ts.map(|ts| ts.as_nanos())
+}
+
+fn timestamp(nanos: Option<i64>) -> Option<Timestamp> {
+    nanos.map(Timestamp::from_nanos)
+}
+
+fn class_to_proto(class: Option<InstrumentClass>) -> i32 {
+    let class = match class {
+        None => v1::InstrumentClass::Unspecified,
+        Some(InstrumentClass::Perpetual) => v1::InstrumentClass::Perpetual,
+        Some(InstrumentClass::Future) => v1::InstrumentClass::Future,
+        Some(InstrumentClass::Option) => v1::InstrumentClass::Option,
+        Some(InstrumentClass::Spot) => v1::InstrumentClass::Spot,
+        Some(InstrumentClass::Combo) => v1::InstrumentClass::Combo,
+    };
+    class as i32
+}
+
+fn class_from_proto(value: i32) -> Option<InstrumentClass> {
+    match v1::InstrumentClass::try_from(value).ok()? {
+        v1::InstrumentClass::Unspecified => None,
+        v1::InstrumentClass::Perpetual => Some(InstrumentClass::Perpetual),
+        v1::InstrumentClass::Future => Some(InstrumentClass::Future),
+        v1::InstrumentClass::Option => Some(InstrumentClass::Option),
+        v1::InstrumentClass::Spot => Some(InstrumentClass::Spot),
+        v1::InstrumentClass::Combo => Some(InstrumentClass::Combo),
+    }
+}
+
+fn action_to_proto(action: BookAction) -> i32 {
+    let action = match action {
+        BookAction::New => v1::BookAction::New,
+        BookAction::Change => v1::BookAction::Change,
+        BookAction::Delete => v1::BookAction::Delete,
+    };
+    action as i32
+}
+
+fn action_from_proto(value: i32) -> Result<BookAction> {
+    match v1::BookAction::try_from(value) {
+        Ok(v1::BookAction::New) => Ok(BookAction::New),
+        Ok(v1::BookAction::Change) => Ok(BookAction::Change),
+        Ok(v1::BookAction::Delete) => Ok(BookAction::Delete),
+        _ => Err(decode_error(format!("invalid book action {}", value))),
+    }
+}
+
+fn side_to_proto(side: Side) -> i32 {
+    let side = match side {
+        Side::Buy => v1::Side::Buy,
+        Side::Sell => v1::Side::Sell,
+    };
+    side as i32
+}
+
+fn side_from_proto(value: i32) -> Result<Side> {
+    match v1::Side::try_from(value) {
+        Ok(v1::Side::Buy) => Ok(Side::Buy),
+        Ok(v1::Side::Sell) => Ok(Side::Sell),
+        _ => Err(decode_error(format!("invalid side {}", value))),
+    }
+}
+
+fn liquidated_to_proto(liquidated: LiquidatedSide) -> i32 {
+    let liquidated = match liquidated {
+        LiquidatedSide::Maker => v1::LiquidatedSide::Maker,
+        LiquidatedSide::Taker => v1::LiquidatedSide::Taker,
+        LiquidatedSide::Both => v1::LiquidatedSide::Both,
+    };
+    liquidated as i32
+}
+
+fn liquidated_from_proto(value: i32) -> Result<LiquidatedSide> {
+    match v1::LiquidatedSide::try_from(value) {
+        Ok(v1::LiquidatedSide::Maker) => Ok(LiquidatedSide::Maker),
+        Ok(v1::LiquidatedSide::Taker) => Ok(LiquidatedSide::Taker),
+        Ok(v1::LiquidatedSide::Both) => Ok(LiquidatedSide::Both),
+        _ => Err(decode_error(format!("invalid liquidated side {}", value))),
+    }
+}
+
+fn levels_to_proto(levels: &[(Price, Quantity)]) -> Vec<v1::PriceLevel> {
+    levels
+        .iter()
+        .map(|(price, amount)| v1::PriceLevel {
+            price: price.to_string(),
+            amount: amount.to_string(),
+        })
+        .collect()
+}
+
+fn levels_from_proto(levels: Vec<v1::PriceLevel>) -> Result<Vec<(Price, Quantity)>> {
+    levels
+        .into_iter()
+        .map(|level| Ok((price(&level.price)?, quantity(&level.amount)?)))
+        .collect()
+}
+
+fn changes_to_proto(changes: &[BookLevelChange]) -> Vec<v1::BookLevelChange> {
+    changes
+        .iter()
+        .map(|change| v1::BookLevelChange {
+            action: action_to_proto(change.action),
+            price: change.price.to_string(),
+            amount: change.amount.to_string(),
+        })
+        .collect()
+}
+
+fn changes_from_proto(changes: Vec<v1::BookLevelChange>) -> Result<Vec<BookLevelChange>> {
+    changes
+        .into_iter()
+        .map(|change| {
+            Ok(BookLevelChange {
+                action: action_from_proto(change.action)?,
+                price: price(&change.price)?,
+                amount: quantity(&change.amount)?,
+            })
+        })
+        .collect()
+}
+
+impl From<&Envelope> for v1::Envelope {
+    fn from(envelope: &Envelope) -> Self {
+        v1::Envelope {
+            schema_version: envelope.schema_version,
+            instance_id: envelope.instance_id.clone(),
+            connection_id: envelope.connection_id.map(|id| id as u64),
+            sequence: envelope.sequence,
+            channel: envelope.channel.clone(),
+            payload: Some((&envelope.payload).into()),
+        }
+    }
+}
+
+impl TryFrom<v1::Envelope> for Envelope {
+    type Error = CodecError;
+
+    fn try_from(envelope: v1::Envelope) -> Result<Self> {
+        let payload = envelope
+            .payload
+            .ok_or_else(|| decode_error("envelope without payload"))?;
+
+        Ok(Envelope {
+            schema_version: envelope.schema_version,
+            instance_id: envelope.instance_id,
+            connection_id: envelope.connection_id.map(|id| id as usize),
+            sequence: envelope.sequence,
+            channel: envelope.channel,
+            payload: payload.try_into()?,
+        })
+    }
+}
+
+impl From<&MarketData> for v1::MarketData {
+    fn from(data: &MarketData) -> Self {
+        let data = match data {
+            MarketData::Orderbook(ob) => Data::Orderbook(v1::OrderBookSnapshot {
+                symbol: ob.symbol.clone(),
+                venue: ob.venue.clone(),
+                bids: levels_to_proto(&ob.bids),
+                asks: levels_to_proto(&ob.asks),
+                seq_id: ob.seq_id,
+                instrument_class: class_to_proto(ob.instrument_class),
+                timestamp: ob.timestamp.as_nanos(),
+                ingestion_timestamp: ob.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(ob.publish_timestamp),
+            }),
+            MarketData::BookDelta(delta) => Data::BookDelta(v1::OrderBookDelta {
+                symbol: delta.symbol.clone(),
+                venue: delta.venue.clone(),
+                bids: changes_to_proto(&delta.bids),
+                asks: changes_to_proto(&delta.asks),
+                seq_id: delta.seq_id,
+                prev_seq_id: delta.prev_seq_id,
+                is_snapshot: delta.is_snapshot,
+                instrument_class: class_to_proto(delta.instrument_class),
+                timestamp: delta.timestamp.as_nanos(),
+                ingestion_timestamp: delta.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(delta.publish_timestamp),
+            }),
+            MarketData::Trade(trade) => Data::Trade(v1::TradeSnapshot {
+                symbol: trade.symbol.clone(),
+                venue: trade.venue.clone(),
+                trade_id: trade.trade_id.clone(),
+                price: trade.price.to_string(),
+                amount: trade.amount.to_string(),
+                side: side_to_proto(trade.side),
+                seq_id: trade.seq_id,
+                instrument_class: class_to_proto(trade.instrument_class),
+                timestamp: trade.timestamp.as_nanos(),
+                ingestion_timestamp: trade.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(trade.publish_timestamp),
+                contracts: trade.contracts.map(|q| q.to_string()),
+                index_price: trade.index_price.map(|p| p.to_string()),
+                mark_price: trade.mark_price.map(|p| p.to_string()),
+                tick_direction: trade.tick_direction.map(|t| t.code()),
+                backfilled: trade.backfilled,
+            }),
+            MarketData::Ticker(ticker) => Data::Ticker(v1::TickerRow {
+                timestamp: ticker.timestamp.as_nanos(),
+                ingestion_timestamp: ticker.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(ticker.publish_timestamp),
+                venue: ticker.venue.clone(),
+                state: ticker.state.as_u8() as u32,
+                symbol: ticker.symbol.clone(),
+                index_price: ticker.index_price.map(|p| p.to_string()),
+                settlement_price: ticker.settlement_price.map(|p| p.to_string()),
+                open_interest: ticker.open_interest.map(|q| q.to_string()),
+                mark_price: ticker.mark_price.map(|p| p.to_string()),
+                best_bid_price: ticker.best_bid_price.map(|p| p.to_string()),
+                mark_iv: ticker.mark_iv,
+                ask_iv: ticker.ask_iv,
+                bid_iv: ticker.bid_iv,
+                underlying_price: ticker.underlying_price.map(|p| p.to_string()),
+                underlying_index: ticker.underlying_index.clone(),
+                best_ask_price: ticker.best_ask_price.map(|p| p.to_string()),
+                interest_rate: ticker.interest_rate,
+                estimated_delivery_price: ticker.estimated_delivery_price.map(|p| p.to_string()),
+                best_ask_amount: ticker.best_ask_amount.map(|q| q.to_string()),
+                best_bid_amount: ticker.best_bid_amount.map(|q| q.to_string()),
+                current_funding: ticker.current_funding,
+                delivery_price: ticker.delivery_price.map(|p| p.to_string()),
+                funding_8h: ticker.funding_8h,
+                interest_value: ticker.interest_value,
+                greeks_delta: ticker.greeks_delta,
+                greeks_gamma: ticker.greeks_gamma,
+                greeks_vega: ticker.greeks_vega,
+                greeks_theta: ticker.greeks_theta,
+                greeks_rho: ticker.greeks_rho,
+            }),
+            MarketData::IndexPrice(index) => Data::IndexPrice(v1::IndexPrice {
+                index_name: index.index_name.clone(),
+                venue: index.venue.clone(),
+                price: index.price,
+                timestamp: index.timestamp.as_nanos(),
+                ingestion_timestamp: index.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(index.publish_timestamp),
+            }),
+            MarketData::VolatilityIndex(index) => Data::VolatilityIndex(v1::VolatilityIndex {
+                index_name: index.index_name.clone(),
+                venue: index.venue.clone(),
+                volatility: index.volatility,
+                timestamp: index.timestamp.as_nanos(),
+                ingestion_timestamp: index.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(index.publish_timestamp),
+            }),
+            MarketData::MarkPrice(mark) => Data::MarkPrice(v1::MarkPrice {
+                symbol: mark.symbol.clone(),
+                venue: mark.venue.clone(),
+                mark_price: mark.mark_price,
+                iv: mark.iv,
+                timestamp: mark.timestamp.as_nanos(),
+                ingestion_timestamp: mark.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(mark.publish_timestamp),
+            }),
+            MarketData::EstimatedExpirationPrice(price) => {
+                Data::EstimatedExpirationPrice(v1::EstimatedExpirationPrice {
+                    index_name: price.index_name.clone(),
+                    venue: price.venue.clone(),
+                    price: price.price,
+                    is_estimated: price.is_estimated,
+                    seconds_to_expiry: price.seconds_to_expiry,
+                    timestamp: price.timestamp.as_nanos(),
+                    ingestion_timestamp: price.ingestion_timestamp.as_nanos(),
+                    publish_timestamp: nanos(price.publish_timestamp),
+                })
+            }
+            MarketData::InstrumentStatus(status) => Data::InstrumentStatus(v1::InstrumentStatus {
+                symbol: status.symbol.clone(),
+                venue: status.venue.clone(),
+                state: status.state.as_str().to_string(),
+                timestamp: status.timestamp.as_nanos(),
+                ingestion_timestamp: status.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(status.publish_timestamp),
+            }),
+            MarketData::PlatformState(state) => Data::PlatformState(v1::PlatformState {
+                venue: state.venue.clone(),
+                price_index: state.price_index.clone(),
+                locked: state.locked,
+                maintenance: state.maintenance,
+                allow_unauthenticated_public_requests: state.allow_unauthenticated_public_requests,
+                ingestion_timestamp: state.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(state.publish_timestamp),
+            }),
+            MarketData::Quote(quote) => Data::Quote(v1::Quote {
+                symbol: quote.symbol.clone(),
+                venue: quote.venue.clone(),
+                best_bid_price: quote.best_bid_price.map(|p| p.to_string()),
+                best_bid_amount: quote.best_bid_amount.map(|q| q.to_string()),
+                best_ask_price: quote.best_ask_price.map(|p| p.to_string()),
+                best_ask_amount: quote.best_ask_amount.map(|q| q.to_string()),
+                instrument_class: class_to_proto(quote.instrument_class),
+                timestamp: quote.timestamp.as_nanos(),
+                ingestion_timestamp: quote.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(quote.publish_timestamp),
+            }),
+            MarketData::Liquidation(liquidation) => Data::Liquidation(v1::Liquidation {
+                symbol: liquidation.symbol.clone(),
+                venue: liquidation.venue.clone(),
+                trade_id: liquidation.trade_id.clone(),
+                price: liquidation.price.to_string(),
+                amount: liquidation.amount.to_string(),
+                side: side_to_proto(liquidation.side),
+                liquidated: liquidated_to_proto(liquidation.liquidated),
+                instrument_class: class_to_proto(liquidation.instrument_class),
+                timestamp: liquidation.timestamp.as_nanos(),
+                ingestion_timestamp: liquidation.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(liquidation.publish_timestamp),
+            }),
+            MarketData::FundingRate(funding) => Data::FundingRate(v1::FundingRate {
+                symbol: funding.symbol.clone(),
+                venue: funding.venue.clone(),
+                current_funding: funding.current_funding,
+                funding_8h: funding.funding_8h,
+                index_price: funding.index_price.to_string(),
+                mark_price: funding.mark_price.to_string(),
+                timestamp: funding.timestamp.as_nanos(),
+                ingestion_timestamp: funding.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(funding.publish_timestamp),
+            }),
+            MarketData::Settlement(settlement) => Data::Settlement(v1::Settlement {
+                symbol: settlement.symbol.clone(),
+                venue: settlement.venue.clone(),
+                settlement_price: settlement.settlement_price.to_string(),
+                delivery_price: settlement.delivery_price.map(|p| p.to_string()),
+                instrument_class: class_to_proto(settlement.instrument_class),
+                timestamp: settlement.timestamp.as_nanos(),
+                ingestion_timestamp: settlement.ingestion_timestamp.as_nanos(),
+                publish_timestamp: nanos(settlement.publish_timestamp),
+            }),
+        };
+
+        v1::MarketData { data: Some(data) }
+    }
+}
+
+impl TryFrom<v1::MarketData> for MarketData {
+    type Error = CodecError;
+
+    fn try_from(data: v1::MarketData) -> Result<Self> {
+        let data = data.data.ok_or_else(|| decode_error("market data without a variant"))?;
+
+        Ok(match data {
+            Data::Orderbook(ob) => MarketData::Orderbook(OrderBookSnapshot {
+                symbol: ob.symbol,
+                venue: ob.venue,
+                bids: levels_from_proto(ob.bids)?,
+                asks: levels_from_proto(ob.asks)?,
+                seq_id: ob.seq_id,
+                instrument_class: class_from_proto(ob.instrument_class),
+                timestamp: Timestamp::from_nanos(ob.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(ob.ingestion_timestamp),
+                publish_timestamp: timestamp(ob.publish_timestamp),
+            }),
+            Data::BookDelta(delta) => MarketData::BookDelta(OrderBookDelta {
+                symbol: delta.symbol,
+                venue: delta.venue,
+                bids: changes_from_proto(delta.bids)?,
+                asks: changes_from_proto(delta.asks)?,
+                seq_id: delta.seq_id,
+                prev_seq_id: delta.prev_seq_id,
+                is_snapshot: delta.is_snapshot,
+                instrument_class: class_from_proto(delta.instrument_class),
+                timestamp: Timestamp::from_nanos(delta.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(delta.ingestion_timestamp),
+                publish_timestamp: timestamp(delta.publish_timestamp),
+            }),
+            Data::Trade(trade) => MarketData::Trade(TradeSnapshot {
+                side: side_from_proto(trade.side)?,
+                symbol: trade.symbol,
+                venue: trade.venue,
+                trade_id: trade.trade_id,
+                price: price(&trade.price)?,
+                amount: quantity(&trade.amount)?,
+                seq_id: trade.seq_id,
+                instrument_class: class_from_proto(trade.instrument_class),
+                timestamp: Timestamp::from_nanos(trade.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(trade.ingestion_timestamp),
+                publish_timestamp: timestamp(trade.publish_timestamp),
+                contracts: optional_quantity(trade.contracts)?,
+                index_price: optional_price(trade.index_price)?,
+                mark_price: optional_price(trade.mark_price)?,
+                tick_direction: trade
+                    .tick_direction
+                    .map(|code| TickDirection::try_from(code as i64))
+                    .transpose()?,
+                backfilled: trade.backfilled,
+            }),
+            Data::Ticker(ticker) => MarketData::Ticker(Box::new(TickerRow {
+                timestamp: Timestamp::from_nanos(ticker.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(ticker.ingestion_timestamp),
+                publish_timestamp: timestamp(ticker.publish_timestamp),
+                venue: ticker.venue,
+                state: if ticker.state == 1 {
+                    InstrumentState::Open
+                } else {
+                    InstrumentState::Closed
+                },
+                symbol: ticker.symbol,
+                index_price: optional_price(ticker.index_price)?,
+                settlement_price: optional_price(ticker.settlement_price)?,
+                open_interest: optional_quantity(ticker.open_interest)?,
+                mark_price: optional_price(ticker.mark_price)?,
+                best_bid_price: optional_price(ticker.best_bid_price)?,
+                mark_iv: ticker.mark_iv,
+                ask_iv: ticker.ask_iv,
+                bid_iv: ticker.bid_iv,
+                underlying_price: optional_price(ticker.underlying_price)?,
+                underlying_index: ticker.underlying_index,
+                best_ask_price: optional_price(ticker.best_ask_price)?,
+                interest_rate: ticker.interest_rate,
+                estimated_delivery_price: optional_price(ticker.estimated_delivery_price)?,
+                best_ask_amount: optional_quantity(ticker.best_ask_amount)?,
+                best_bid_amount: optional_quantity(ticker.best_bid_amount)?,
+                current_funding: ticker.current_funding,
+                delivery_price: optional_price(ticker.delivery_price)?,
+                funding_8h: ticker.funding_8h,
+                interest_value: ticker.interest_value,
+                greeks_delta: ticker.greeks_delta,
+                greeks_gamma: ticker.greeks_gamma,
+                greeks_vega: ticker.greeks_vega,
+                greeks_theta: ticker.greeks_theta,
+                greeks_rho: ticker.greeks_rho,
+            })),
+            Data::IndexPrice(index) => MarketData::IndexPrice(IndexPrice {
+                index_name: index.index_name,
+                venue: index.venue,
+                price: index.price,
+                timestamp: Timestamp::from_nanos(index.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(index.ingestion_timestamp),
+                publish_timestamp: timestamp(index.publish_timestamp),
+            }),
+            Data::VolatilityIndex(index) => MarketData::VolatilityIndex(VolatilityIndex {
+                index_name: index.index_name,
+                venue: index.venue,
+                volatility: index.volatility,
+                timestamp: Timestamp::from_nanos(index.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(index.ingestion_timestamp),
+                publish_timestamp: timestamp(index.publish_timestamp),
+            }),
+            Data::MarkPrice(mark) => MarketData::MarkPrice(MarkPrice {
+                symbol: mark.symbol,
+                venue: mark.venue,
+                mark_price: mark.mark_price,
+                iv: mark.iv,
+                timestamp: Timestamp::from_nanos(mark.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(mark.ingestion_timestamp),
+                publish_timestamp: timestamp(mark.publish_timestamp),
+            }),
+            Data::EstimatedExpirationPrice(price) => {
+                MarketData::EstimatedExpirationPrice(EstimatedExpirationPrice {
+                    index_name: price.index_name,
+                    venue: price.venue,
+                    price: price.price,
+                    is_estimated: price.is_estimated,
+                    seconds_to_expiry: price.seconds_to_expiry,
+                    timestamp: Timestamp::from_nanos(price.timestamp),
+                    ingestion_timestamp: Timestamp::from_nanos(price.ingestion_timestamp),
+                    publish_timestamp: timestamp(price.publish_timestamp),
+                })
+            }
+            Data::InstrumentStatus(status) => MarketData::InstrumentStatus(InstrumentStatus {
+                state: LifecycleState::from_name(&status.state).ok_or_else(|| {
+                    decode_error(format!("invalid instrument lifecycle state {}", status.state))
+                })?,
+                symbol: status.symbol,
+                venue: status.venue,
+                timestamp: Timestamp::from_nanos(status.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(status.ingestion_timestamp),
+                publish_timestamp: timestamp(status.publish_timestamp),
+            }),
+            Data::PlatformState(state) => MarketData::PlatformState(PlatformState {
+                venue: state.venue,
+                price_index: state.price_index,
+                locked: state.locked,
+                maintenance: state.maintenance,
+                allow_unauthenticated_public_requests: state.allow_unauthenticated_public_requests,
+                ingestion_timestamp: Timestamp::from_nanos(state.ingestion_timestamp),
+                publish_timestamp: timestamp(state.publish_timestamp),
+            }),
+            Data::Quote(quote) => MarketData::Quote(Quote {
+                symbol: quote.symbol,
+                venue: quote.venue,
+                best_bid_price: optional_price(quote.best_bid_price)?,
+                best_bid_amount: optional_quantity(quote.best_bid_amount)?,
+                best_ask_price: optional_price(quote.best_ask_price)?,
+                best_ask_amount: optional_quantity(quote.best_ask_amount)?,
+                instrument_class: class_from_proto(quote.instrument_class),
+                timestamp: Timestamp::from_nanos(quote.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(quote.ingestion_timestamp),
+                publish_timestamp: timestamp(quote.publish_timestamp),
+            }),
+            Data::Liquidation(liquidation) => MarketData::Liquidation(Liquidation {
+                side: side_from_proto(liquidation.side)?,
+                liquidated: liquidated_from_proto(liquidation.liquidated)?,
+                symbol: liquidation.symbol,
+                venue: liquidation.venue,
+                trade_id: liquidation.trade_id,
+                price: price(&liquidation.price)?,
+                amount: quantity(&liquidation.amount)?,
+                instrument_class: class_from_proto(liquidation.instrument_class),
+                timestamp: Timestamp::from_nanos(liquidation.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(liquidation.ingestion_timestamp),
+                publish_timestamp: timestamp(liquidation.publish_timestamp),
+            }),
+            Data::FundingRate(funding) => MarketData::FundingRate(FundingRate {
+                symbol: funding.symbol,
+                venue: funding.venue,
+                current_funding: funding.current_funding,
+                funding_8h: funding.funding_8h,
+                index_price: price(&funding.index_price)?,
+                mark_price: price(&funding.mark_price)?,
+                timestamp: Timestamp::from_nanos(funding.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(funding.ingestion_timestamp),
+                publish_timestamp: timestamp(funding.publish_timestamp),
+            }),
+            Data::Settlement(settlement) => MarketData::Settlement(Settlement {
+                symbol: settlement.symbol,
+                venue: settlement.venue,
+                settlement_price: price(&settlement.settlement_price)?,
+                delivery_price: optional_price(settlement.delivery_price)?,
+                instrument_class: class_from_proto(settlement.instrument_class),
+                timestamp: Timestamp::from_nanos(settlement.timestamp),
+                ingestion_timestamp: Timestamp::from_nanos(settlement.ingestion_timestamp),
+                publish_timestamp: timestamp(settlement.publish_timestamp),
+            }),
+        })
+    }
+}
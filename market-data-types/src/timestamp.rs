@@ -0,0 +1,292 @@
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::Cell;
+use std::fmt;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// How timestamps are written to sinks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampFormat {
+    /// Integer nanoseconds since the Unix epoch for every field
+    #[default]
+    Nanos,
+    /// The pre-nanosecond wire format: RFC3339 strings, except ticker timestamps which are
+    /// exchange milliseconds and ingestion seconds
+    Legacy,
+}
+
+thread_local! {
+    static FORMAT: Cell<TimestampFormat> = const { Cell::new(TimestampFormat::Nanos) };
+}
+
+/// Run `serialize` with timestamps written in `format`; reading accepts either format regardless.
+///
+/// Codecs call this around their own encode, so each sink picks its format and anything
+/// serialized outside a codec is written as nanoseconds.
+pub(crate) fn with_format<T>(format: TimestampFormat, serialize: impl FnOnce() -> T) -> T {
+    struct Restore(TimestampFormat);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            FORMAT.with(|current| current.set(self.0));
+        }
+    }
+
+    let _restore = Restore(FORMAT.with(|current| current.replace(format)));
+    serialize()
+}
+
+fn format() -> TimestampFormat {
+    FORMAT.with(Cell::get)
+}
+
+/// Nanoseconds since the Unix epoch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    pub fn from_nanos(nanos: i64) -> Self {
+        Self(nanos)
+    }
+
+    pub fn from_micros(micros: i64) -> Self {
+        Self(micros.saturating_mul(1_000))
+    }
+
+    /// Exchange time as sent by Deribit
+    pub fn from_millis(millis: i64) -> Self {
+        Self(millis.saturating_mul(1_000_000))
+    }
+
+    pub fn from_secs(secs: i64) -> Self {
+        Self(secs.saturating_mul(1_000_000_000))
+    }
+
+    pub fn now() -> Self {
+        Self(OffsetDateTime::now_utc().unix_timestamp_nanos() as i64)
+    }
+
+    pub fn as_nanos(&self) -> i64 {
+        self.0
+    }
+
+    pub fn as_millis(&self) -> i64 {
+        self.0 / 1_000_000
+    }
+
+    pub fn as_secs(&self) -> i64 {
+        self.0 / 1_000_000_000
+    }
+
+    pub fn to_offset_datetime(&self) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp_nanos(self.0 as i128)
+            .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+    }
+
+    /// Integer in an unknown unit, told apart by magnitude.
+    ///
+    /// Any date between 1973 and 2286 is below 1e11 in seconds, below 1e14 in
+    /// milliseconds and below 1e17 in microseconds, so the ranges do not overlap.
+    fn from_integer(value: i64) -> Self {
+        match value.unsigned_abs() {
+            0..=99_999_999_999 => Self::from_secs(value),
+            100_000_000_000..=99_999_999_999_999 => Self::from_millis(value),
+            100_000_000_000_000..=99_999_999_999_999_999 => Self::from_micros(value),
+            _ => Self::from_nanos(value),
+        }
+    }
+
+    fn to_rfc3339(self) -> String {
+        self.to_offset_datetime()
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| self.0.to_string())
+    }
+}
+
+impl From<OffsetDateTime> for Timestamp {
+    fn from(datetime: OffsetDateTime) -> Self {
+        Self(datetime.unix_timestamp_nanos() as i64)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match format() {
+            TimestampFormat::Nanos => serializer.serialize_i64(self.0),
+            TimestampFormat::Legacy => serializer.serialize_str(&self.to_rfc3339()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TimestampVisitor;
+
+        impl Visitor<'_> for TimestampVisitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an integer timestamp or an RFC3339 string")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Timestamp, E> {
+                Ok(Timestamp::from_integer(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Timestamp, E> {
+                i64::try_from(v)
+                    .map(Timestamp::from_integer)
+                    .map_err(|_| E::custom(format!("timestamp {} out of range", v)))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Timestamp, E> {
+                OffsetDateTime::parse(v, &Rfc3339)
+                    .map(Timestamp::from)
+                    .map_err(|e| E::custom(format!("invalid timestamp '{}': {}", v, e)))
+            }
+        }
+
+        deserializer.deserialize_any(TimestampVisitor)
+    }
+}
+
+/// `#[serde(with)]` for fields that were exchange milliseconds before nanosecond timestamps
+pub mod legacy_millis {
+    use super::{format, Timestamp, TimestampFormat};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(ts: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+        match format() {
+            TimestampFormat::Nanos => ts.serialize(serializer),
+            TimestampFormat::Legacy => serializer.serialize_i64(ts.as_millis()),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+        Timestamp::deserialize(deserializer)
+    }
+}
+
+/// `#[serde(with)]` for fields that were Unix seconds before nanosecond timestamps
+pub mod legacy_secs {
+    use super::{format, Timestamp, TimestampFormat};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(ts: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+        match format() {
+            TimestampFormat::Nanos => ts.serialize(serializer),
+            TimestampFormat::Legacy => serializer.serialize_i64(ts.as_secs()),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+        Timestamp::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    /// 2023-11-14T22:13:20Z
+    const SECS: i64 = 1_700_000_000;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct LegacyFields {
+        #[serde(with = "legacy_millis")]
+        exchange: Timestamp,
+        #[serde(with = "legacy_secs")]
+        ingestion: Timestamp,
+    }
+
+    #[test]
+    fn integer_unit_is_told_apart_by_magnitude() {
+        let expected = Timestamp::from_secs(SECS);
+        assert_eq!(Timestamp::from_integer(SECS), expected);
+        assert_eq!(Timestamp::from_integer(SECS * 1_000), expected);
+        assert_eq!(Timestamp::from_integer(SECS * 1_000_000), expected);
+        assert_eq!(Timestamp::from_integer(SECS * 1_000_000_000), expected);
+        assert_eq!(Timestamp::from_integer(-SECS), Timestamp::from_secs(-SECS));
+    }
+
+    #[test]
+    fn integer_unit_boundaries() {
+        let cases = [
+            (99_999_999_999, Timestamp::from_secs(99_999_999_999)),
+            (100_000_000_000, Timestamp::from_millis(100_000_000_000)),
+            (99_999_999_999_999, Timestamp::from_millis(99_999_999_999_999)),
+            (100_000_000_000_000, Timestamp::from_micros(100_000_000_000_000)),
+            (99_999_999_999_999_999, Timestamp::from_micros(99_999_999_999_999_999)),
+            (100_000_000_000_000_000, Timestamp::from_nanos(100_000_000_000_000_000)),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(Timestamp::from_integer(value), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn reads_integers_and_rfc3339_strings() {
+        let expected = Timestamp::from_secs(SECS);
+        for json in ["1700000000000", "1700000000000000000", "\"2023-11-14T22:13:20Z\""] {
+            assert_eq!(serde_json::from_str::<Timestamp>(json).unwrap(), expected, "{}", json);
+        }
+        assert!(serde_json::from_str::<Timestamp>("18446744073709551615").is_err());
+        assert!(serde_json::from_str::<Timestamp>("\"yesterday\"").is_err());
+    }
+
+    #[test]
+    fn format_switch_changes_the_written_form() {
+        let ts = Timestamp::from_millis(SECS * 1_000 + 250);
+
+        let nanos = with_format(TimestampFormat::Nanos, || serde_json::to_string(&ts).unwrap());
+        assert_eq!(nanos, "1700000000250000000");
+
+        let legacy = with_format(TimestampFormat::Legacy, || serde_json::to_string(&ts).unwrap());
+        assert_eq!(legacy, "\"2023-11-14T22:13:20.25Z\"");
+
+        // Either form reads back regardless of the selected format
+        assert_eq!(serde_json::from_str::<Timestamp>(&nanos).unwrap(), ts);
+        assert_eq!(serde_json::from_str::<Timestamp>(&legacy).unwrap(), ts);
+    }
+
+    #[test]
+    fn format_only_applies_inside_its_scope() {
+        let ts = Timestamp::from_secs(SECS);
+
+        let (inner, outer) = with_format(TimestampFormat::Legacy, || {
+            let inner = with_format(TimestampFormat::Nanos, || serde_json::to_string(&ts).unwrap());
+            (inner, serde_json::to_string(&ts).unwrap())
+        });
+        assert_eq!(inner, "1700000000000000000");
+        assert_eq!(outer, "\"2023-11-14T22:13:20Z\"");
+        assert_eq!(serde_json::to_string(&ts).unwrap(), "1700000000000000000");
+    }
+
+    #[test]
+    fn legacy_fields_round_trip() {
+        let fields = LegacyFields {
+            exchange: Timestamp::from_millis(SECS * 1_000 + 250),
+            ingestion: Timestamp::from_secs(SECS),
+        };
+
+        let legacy = with_format(TimestampFormat::Legacy, || serde_json::to_string(&fields).unwrap());
+        assert_eq!(legacy, r#"{"exchange":1700000000250,"ingestion":1700000000}"#);
+        assert_eq!(serde_json::from_str::<LegacyFields>(&legacy).unwrap(), fields);
+
+        let nanos = with_format(TimestampFormat::Nanos, || serde_json::to_string(&fields).unwrap());
+        assert_eq!(
+            nanos,
+            r#"{"exchange":1700000000250000000,"ingestion":1700000000000000000}"#
+        );
+        assert_eq!(serde_json::from_str::<LegacyFields>(&nanos).unwrap(), fields);
+    }
+}
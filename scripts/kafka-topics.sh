@@ -10,7 +10,11 @@ docker exec market-data-kafka kafka-topics --bootstrap-server localhost:9092 --l
 
 echo ""
 echo "Topic details:"
-for topic in market-data-orderbook market-data-trades market-data-ticker; do
+for topic in market-data-orderbook market-data-trades market-data-ticker \
+    market-data-index-price market-data-volatility-index market-data-mark-price \
+    market-data-estimated-expiration-price market-data-instrument-state market-data-platform-state \
+    market-data-quotes market-data-liquidations market-data-funding-rate market-data-settlements \
+    market-data-dead-letter; do
     echo ""
     echo "📊 $topic:"
     docker exec market-data-kafka kafka-topics --bootstrap-server localhost:9092 --describe --topic "$topic" 2>/dev/null || echo "  (not created yet)"
@@ -33,8 +33,9 @@ async fn main() -> Result<()> {
         }
     };
 
-    let kafka_producer = Arc::new(KafkaProducer::new(config.kafka.clone())?);
-    let redis_storage = Arc::new(RedisStorage::new(&config.redis).await?);
+    let timestamps = config.serialization.timestamp_format;
+    let kafka_producer = Arc::new(KafkaProducer::new(config.kafka.clone(), timestamps).await?);
+    let redis_storage = Arc::new(RedisStorage::new(&config.redis, timestamps).await?);
 
     // Create cancellation token for graceful shutdown
     let shutdown_token = CancellationToken::new();
@@ -42,9 +43,11 @@ async fn main() -> Result<()> {
     // Spawn health check server with graceful shutdown
     let health_port = config.health_check.port;
     let health_shutdown = shutdown_token.clone();
+    let health_kafka = kafka_producer.clone();
     let health_handle: JoinHandle<()> = tokio::spawn(async move {
         if let Err(e) = health_check::start_server(
             health_port,
+            health_kafka,
             async move { health_shutdown.cancelled().await },
         )
         .await
@@ -88,19 +91,19 @@ async fn main() -> Result<()> {
                     }
                     result = orderbook_stream.next() => {
                         match result {
-                            Some(Ok(market_data)) => {
-                                info!("Received market data: {:?}", market_data);
+                            Some(Ok(envelope)) => {
+                                info!("Received market data: {:?}", envelope);
 
                                 let (redis_result, kafka_result) = tokio::join!(
-                                    redis_storage.update_latest_data(&market_data),
-                                    kafka_producer.send_market_data(&market_data)
+                                    redis_storage.update_latest_data(&envelope.payload),
+                                    kafka_producer.enqueue(&envelope)
                                 );
 
                                 if let Err(e) = redis_result {
                                     error!("Redis update failed after retries: {}", e);
                                 }
                                 if let Err(e) = kafka_result {
-                                    error!("Kafka send failed after retries: {}", e);
+                                    error!("Kafka enqueue failed: {}", e);
                                 }
                             }
                             Some(Err(e)) => {
@@ -22,10 +22,11 @@ async fn main() -> Result<()> {
 
     let (symbol_tx, _symbol_rx) = mpsc::channel(100);
 
-    let kafka_producer = Arc::new(KafkaProducer::new(config.kafka.clone())?);
+    let timestamps = config.serialization.timestamp_format;
+    let kafka_producer = Arc::new(KafkaProducer::new(config.kafka.clone(), timestamps).await?);
     let _kafka_consumer = KafkaConsumer::new(config.kafka.clone(), symbol_tx)?;
 
-    let redis_storage = Arc::new(RedisStorage::new(&config.redis).await?);
+    let redis_storage = Arc::new(RedisStorage::new(&config.redis, timestamps).await?);
 
     // Create cancellation token for graceful shutdown
     let shutdown_token = CancellationToken::new();
@@ -33,9 +34,11 @@ async fn main() -> Result<()> {
     // Spawn health check server with graceful shutdown
     let health_port = config.health_check.port;
     let health_shutdown = shutdown_token.clone();
+    let health_kafka = kafka_producer.clone();
     let health_handle: JoinHandle<()> = tokio::spawn(async move {
         if let Err(e) = health_check::start_server(
             health_port,
+            health_kafka,
             async move { health_shutdown.cancelled().await },
         )
         .await
@@ -73,13 +76,13 @@ async fn main() -> Result<()> {
                     }
                     result = ticker_stream.next() => {
                         match result {
-                            Some(Ok(market_data)) => {
-                                if let Err(e) = redis_storage.update_latest_data(&market_data).await {
+                            Some(Ok(envelope)) => {
+                                if let Err(e) = redis_storage.update_latest_data(&envelope.payload).await {
                                     error!("Failed to update Redis: {}", e);
                                 }
 
-                                if let Err(e) = kafka_producer.send_market_data(&market_data).await {
-                                    error!("Failed to send to kafka: {}", e);
+                                if let Err(e) = kafka_producer.enqueue(&envelope).await {
+                                    error!("Failed to enqueue to kafka: {}", e);
                                 }
                             }
                             Some(Err(e)) => {
@@ -1,5 +1,12 @@
 use crate::errors::{MarketDataError, Result};
+use crate::exchanges::deribit::{
+    BookFeed, ChannelSettings, DeribitCredentials, ReferenceChannels, SymbolSelector,
+};
+use crate::infra::schema_registry::Compatibility;
+use crate::infra::spool::OverflowPolicy;
 use config::{Config as ConfigLoader, File};
+use market_data_types::timestamp::TimestampFormat;
+use market_data_types::{naming, CodecKind};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -12,6 +19,10 @@ pub struct Config {
     pub health_check: HealthCheckConfig,
     #[serde(default)]
     pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub serialization: SerializationConfig,
+    #[serde(default)]
+    pub collector: CollectorConfig,
     pub exchanges: HashMap<String, ExchangeConfig>,
 }
 
@@ -21,13 +32,188 @@ pub struct KafkaConfig {
     pub orderbook_topic: String,
     pub trade_topic: String,
     pub ticker_topic: String,
+    #[serde(default = "default_index_price_topic")]
+    pub index_price_topic: String,
+    #[serde(default = "default_volatility_index_topic")]
+    pub volatility_index_topic: String,
+    #[serde(default = "default_mark_price_topic")]
+    pub mark_price_topic: String,
+    #[serde(default = "default_estimated_expiration_price_topic")]
+    pub estimated_expiration_price_topic: String,
+    #[serde(default = "default_instrument_state_topic")]
+    pub instrument_state_topic: String,
+    #[serde(default = "default_platform_state_topic")]
+    pub platform_state_topic: String,
+    #[serde(default = "default_quote_topic")]
+    pub quote_topic: String,
+    #[serde(default = "default_liquidation_topic")]
+    pub liquidation_topic: String,
+    #[serde(default = "default_funding_rate_topic")]
+    pub funding_rate_topic: String,
+    #[serde(default = "default_settlement_topic")]
+    pub settlement_topic: String,
+    /// Where records go that can never be delivered or encoded
+    #[serde(default = "default_dead_letter_topic")]
+    pub dead_letter_topic: String,
     #[serde(default)]
     pub producer: KafkaProducerConfig,
     #[serde(default)]
     pub consumer: KafkaConsumerConfig,
+    /// Encoding for every topic not listed in `topic_codecs`
+    #[serde(default)]
+    pub codec: CodecKind,
+    /// Per-topic encoding overrides, keyed by topic name
+    #[serde(default)]
+    pub topic_codecs: HashMap<String, CodecKind>,
+    /// Required when any topic uses the `avro` codec
+    #[serde(default)]
+    pub schema_registry: Option<SchemaRegistryConfig>,
+    /// Where records go while the brokers are unreachable
+    #[serde(default)]
+    pub spool: SpoolConfig,
+    #[serde(default)]
+    pub headers: KafkaHeadersConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+impl KafkaConfig {
+    pub fn topics(&self) -> [&str; 13] {
+        [
+            &self.orderbook_topic,
+            &self.trade_topic,
+            &self.ticker_topic,
+            &self.index_price_topic,
+            &self.volatility_index_topic,
+            &self.mark_price_topic,
+            &self.estimated_expiration_price_topic,
+            &self.instrument_state_topic,
+            &self.platform_state_topic,
+            &self.quote_topic,
+            &self.liquidation_topic,
+            &self.funding_rate_topic,
+            &self.settlement_topic,
+        ]
+    }
+
+    /// Encoding used for `topic`
+    pub fn codec_for(&self, topic: &str) -> CodecKind {
+        self.topic_codecs.get(topic).copied().unwrap_or(self.codec)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaRegistryConfig {
+    /// Registry base URL, or `mock://` for the in-process registry
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Level set on every subject before registering; `BACKWARD` by default
+    #[serde(default)]
+    pub compatibility: Compatibility,
+}
+
+/// Metadata headers written on each record next to `codec`, which is always written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KafkaHeadersConfig {
+    #[serde(default = "default_header_enabled")]
+    pub data_type: bool,
+    #[serde(default = "default_header_enabled")]
+    pub venue: bool,
+    #[serde(default = "default_header_enabled")]
+    pub symbol: bool,
+    #[serde(default = "default_header_enabled")]
+    pub schema_version: bool,
+    #[serde(default = "default_header_enabled")]
+    pub content_type: bool,
+    #[serde(default = "default_header_enabled")]
+    pub exchange_timestamp: bool,
+    /// W3C `traceparent`, a new trace per record
+    #[serde(default = "default_header_enabled")]
+    pub trace_context: bool,
+}
+
+impl Default for KafkaHeadersConfig {
+    fn default() -> Self {
+        Self {
+            data_type: default_header_enabled(),
+            venue: default_header_enabled(),
+            symbol: default_header_enabled(),
+            schema_version: default_header_enabled(),
+            content_type: default_header_enabled(),
+            exchange_timestamp: default_header_enabled(),
+            trace_context: default_header_enabled(),
+        }
+    }
+}
+
+fn default_header_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpoolConfig {
+    /// Directory of the segment files; each collector process needs its own
+    #[serde(default = "default_spool_dir")]
+    pub dir: String,
+    /// Size at which a new segment file is started
+    #[serde(default = "default_spool_segment_bytes")]
+    pub segment_bytes: u64,
+    /// Undelivered bytes kept before `overflow` applies
+    #[serde(default = "default_spool_max_bytes")]
+    pub max_bytes: u64,
+    /// Records older than this are discarded instead of replayed
+    #[serde(default = "default_spool_max_age_secs")]
+    pub max_age_secs: u64,
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+    /// fsync every append; off trades durability on power loss for throughput
+    #[serde(default = "default_spool_fsync")]
+    pub fsync: bool,
+    /// Spooled records sent to Kafka at once during replay
+    #[serde(default = "default_spool_replay_batch")]
+    pub replay_batch: usize,
+}
+
+impl Default for SpoolConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_spool_dir(),
+            segment_bytes: default_spool_segment_bytes(),
+            max_bytes: default_spool_max_bytes(),
+            max_age_secs: default_spool_max_age_secs(),
+            overflow: OverflowPolicy::default(),
+            fsync: default_spool_fsync(),
+            replay_batch: default_spool_replay_batch(),
+        }
+    }
+}
+
+fn default_spool_dir() -> String {
+    "spool".to_string()
+}
+
+fn default_spool_segment_bytes() -> u64 {
+    16 * 1024 * 1024
+}
+
+fn default_spool_max_bytes() -> u64 {
+    1024 * 1024 * 1024
+}
+
+fn default_spool_max_age_secs() -> u64 {
+    86400
+}
+
+fn default_spool_fsync() -> bool {
+    true
+}
+
+fn default_spool_replay_batch() -> usize {
+    500
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KafkaProducerConfig {
     #[serde(default = "default_timeout")]
     pub timeout_ms: u64,
@@ -37,6 +223,25 @@ pub struct KafkaProducerConfig {
     pub initial_backoff_ms: u64,
     #[serde(default = "default_send_timeout_ms")]
     pub send_timeout_ms: u64,
+    /// Enqueued messages awaiting a delivery report before `enqueue` waits
+    #[serde(default = "default_max_in_flight")]
+    pub max_in_flight: usize,
+    /// How often librdkafka reports statistics to the health server; 0 turns them off
+    #[serde(default = "default_statistics_interval_ms")]
+    pub statistics_interval_ms: u64,
+}
+
+impl Default for KafkaProducerConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_timeout(),
+            max_reconnect_attempts: default_max_reconnect_attempts(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            send_timeout_ms: default_send_timeout_ms(),
+            max_in_flight: default_max_in_flight(),
+            statistics_interval_ms: default_statistics_interval_ms(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -45,6 +250,50 @@ pub struct KafkaConsumerConfig {
     pub group_id: String,
 }
 
+fn default_index_price_topic() -> String {
+    naming::INDEX_PRICE_TOPIC.to_string()
+}
+
+fn default_volatility_index_topic() -> String {
+    naming::VOLATILITY_INDEX_TOPIC.to_string()
+}
+
+fn default_mark_price_topic() -> String {
+    naming::MARK_PRICE_TOPIC.to_string()
+}
+
+fn default_estimated_expiration_price_topic() -> String {
+    naming::ESTIMATED_EXPIRATION_PRICE_TOPIC.to_string()
+}
+
+fn default_instrument_state_topic() -> String {
+    naming::INSTRUMENT_STATE_TOPIC.to_string()
+}
+
+fn default_platform_state_topic() -> String {
+    naming::PLATFORM_STATE_TOPIC.to_string()
+}
+
+fn default_quote_topic() -> String {
+    naming::QUOTE_TOPIC.to_string()
+}
+
+fn default_liquidation_topic() -> String {
+    naming::LIQUIDATION_TOPIC.to_string()
+}
+
+fn default_funding_rate_topic() -> String {
+    naming::FUNDING_RATE_TOPIC.to_string()
+}
+
+fn default_settlement_topic() -> String {
+    naming::SETTLEMENT_TOPIC.to_string()
+}
+
+fn default_dead_letter_topic() -> String {
+    naming::DEAD_LETTER_TOPIC.to_string()
+}
+
 fn default_timeout() -> u64 {
     5000
 }
@@ -65,6 +314,14 @@ fn default_send_timeout_ms() -> u64 {
     100
 }
 
+fn default_max_in_flight() -> usize {
+    10_000
+}
+
+fn default_statistics_interval_ms() -> u64 {
+    5000
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RedisConfig {
     pub url: String,
@@ -72,6 +329,8 @@ pub struct RedisConfig {
     pub max_reconnect_attempts: u32,
     #[serde(default = "default_initial_backoff_ms")]
     pub initial_backoff_ms: u64,
+    #[serde(default)]
+    pub codec: CodecKind,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -133,11 +392,127 @@ fn default_exchange_unsubscribe_timeout_ms() -> u64 {
     1000
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SerializationConfig {
+    /// `nanos` (integer nanoseconds) or `legacy` for consumers not yet migrated
+    #[serde(default)]
+    pub timestamp_format: TimestampFormat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectorConfig {
+    /// Identifies this process in message envelopes; defaults to `$HOSTNAME` (the pod name)
+    #[serde(default = "default_instance_id")]
+    pub instance_id: String,
+}
+
+impl Default for CollectorConfig {
+    fn default() -> Self {
+        Self {
+            instance_id: default_instance_id(),
+        }
+    }
+}
+
+fn default_instance_id() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| format!("market-data-{}", std::process::id()))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExchangeConfig {
     pub enabled: bool,
     #[serde(default)]
     pub symbols: Vec<String>,
+    #[serde(default)]
+    pub testnet: bool,
+    /// WebSocket endpoint override, e.g. "wss://test.deribit.com/ws/api/v2"
+    #[serde(default)]
+    pub ws_url: Option<String>,
+    /// API key for an authenticated session; falls back to `DERIBIT_KEY` / `DERIBIT_SECRET`
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub client_secret: Option<String>,
+    /// Seconds between exchange heartbeats (Deribit minimum is 10)
+    #[serde(default = "default_heartbeat_interval")]
+    pub heartbeat_interval: u64,
+    /// Seconds without any frame before the connection is considered dead and reopened
+    #[serde(default = "default_silence_timeout_secs")]
+    pub silence_timeout_secs: u64,
+    #[serde(default)]
+    pub book_feed: BookFeed,
+    #[serde(default)]
+    pub channels: ChannelSettings,
+    /// Index, mark price, instrument state and platform channels (not tied to `symbols`)
+    #[serde(default)]
+    pub reference_channels: ReferenceChannels,
+    /// How often symbol selectors are re-resolved against `public/get_instruments`
+    #[serde(default = "default_instrument_refresh_secs")]
+    pub instrument_refresh_secs: u64,
+    /// Fetch trades missed during a disconnect over REST after reconnecting
+    #[serde(default = "default_trade_backfill")]
+    pub trade_backfill: bool,
+    /// Channels per WebSocket connection before another connection is opened
+    #[serde(default = "default_max_channels_per_connection")]
+    pub max_channels_per_connection: usize,
+    /// Upper bound on concurrent WebSocket connections
+    #[serde(default = "default_max_connections")]
+    pub max_connections: usize,
+    /// Maximum channels per subscribe request
+    #[serde(default = "default_subscribe_chunk_size")]
+    pub subscribe_chunk_size: usize,
+    /// Minimum delay between subscribe/unsubscribe requests
+    #[serde(default = "default_subscribe_interval_ms")]
+    pub subscribe_interval_ms: u64,
+}
+
+impl ExchangeConfig {
+    /// Configured credentials, or the environment when neither field is set
+    pub fn credentials(&self) -> Option<DeribitCredentials> {
+        match (&self.client_id, &self.client_secret) {
+            (Some(client_id), Some(client_secret)) => Some(DeribitCredentials {
+                client_id: client_id.clone(),
+                client_secret: client_secret.clone(),
+            }),
+            (None, None) => DeribitCredentials::from_env(),
+            _ => None,
+        }
+    }
+}
+
+fn default_heartbeat_interval() -> u64 {
+    10
+}
+
+fn default_silence_timeout_secs() -> u64 {
+    30
+}
+
+fn default_instrument_refresh_secs() -> u64 {
+    300
+}
+
+fn default_trade_backfill() -> bool {
+    true
+}
+
+fn default_max_channels_per_connection() -> usize {
+    500
+}
+
+fn default_max_connections() -> usize {
+    4
+}
+
+fn default_subscribe_chunk_size() -> usize {
+    100
+}
+
+fn default_subscribe_interval_ms() -> u64 {
+    100
 }
 
 impl Config {
@@ -147,8 +522,244 @@ impl Config {
             .build()
             .map_err(|e| MarketDataError::ConfigError(format!("Failed to load config: {}", e)))?;
 
-        config
+        let config: Self = config
             .try_deserialize()
-            .map_err(|e| MarketDataError::ConfigError(format!("Failed to parse config: {}", e)))
+            .map_err(|e| MarketDataError::ConfigError(format!("Failed to parse config: {}", e)))?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate_codecs(&self) -> Result<()> {
+        if self.redis.codec == CodecKind::Avro {
+            return Err(MarketDataError::ConfigError(
+                "redis: the avro codec is only supported for Kafka".to_string(),
+            ));
+        }
+
+        let uses_avro = self
+            .kafka
+            .topics()
+            .iter()
+            .any(|topic| self.kafka.codec_for(topic) == CodecKind::Avro);
+        if uses_avro {
+            if self.kafka.schema_registry.is_none() {
+                return Err(MarketDataError::ConfigError(
+                    "kafka: the avro codec requires [kafka.schema_registry]".to_string(),
+                ));
+            }
+            // The Avro schema types timestamps as long nanoseconds
+            if self.serialization.timestamp_format != TimestampFormat::Nanos {
+                return Err(MarketDataError::ConfigError(
+                    "kafka: the avro codec requires serialization.timestamp_format = \"nanos\""
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_producer(&self) -> Result<()> {
+        let spool = &self.kafka.spool;
+        if spool.segment_bytes == 0 || spool.max_bytes < spool.segment_bytes {
+            return Err(MarketDataError::ConfigError(
+                "kafka.spool: segment_bytes must be greater than 0 and at most max_bytes".to_string(),
+            ));
+        }
+        if spool.max_age_secs == 0 {
+            return Err(MarketDataError::ConfigError(
+                "kafka.spool: max_age_secs must be greater than 0".to_string(),
+            ));
+        }
+        if spool.replay_batch == 0 {
+            return Err(MarketDataError::ConfigError(
+                "kafka.spool: replay_batch must be greater than 0".to_string(),
+            ));
+        }
+        if self.kafka.producer.max_in_flight == 0 {
+            return Err(MarketDataError::ConfigError(
+                "kafka.producer: max_in_flight must be greater than 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.validate_codecs()?;
+        self.validate_producer()?;
+        for (name, exchange) in &self.exchanges {
+            for symbol in &exchange.symbols {
+                SymbolSelector::parse(symbol).map_err(|e| Self::scoped(name, e))?;
+            }
+            if exchange.instrument_refresh_secs == 0 {
+                return Err(MarketDataError::ConfigError(format!(
+                    "exchanges.{}: instrument_refresh_secs must be greater than 0",
+                    name
+                )));
+            }
+            if exchange.max_channels_per_connection == 0 || exchange.max_connections == 0 {
+                return Err(MarketDataError::ConfigError(format!(
+                    "exchanges.{}: max_channels_per_connection and max_connections must be greater than 0",
+                    name
+                )));
+            }
+            if exchange.subscribe_chunk_size == 0 {
+                return Err(MarketDataError::ConfigError(format!(
+                    "exchanges.{}: subscribe_chunk_size must be greater than 0",
+                    name
+                )));
+            }
+            if exchange.heartbeat_interval < 10 {
+                return Err(MarketDataError::ConfigError(format!(
+                    "exchanges.{}: heartbeat_interval must be at least 10 seconds",
+                    name
+                )));
+            }
+            if exchange.silence_timeout_secs <= exchange.heartbeat_interval {
+                return Err(MarketDataError::ConfigError(format!(
+                    "exchanges.{}: silence_timeout_secs must be greater than heartbeat_interval",
+                    name
+                )));
+            }
+            if let Some(url) = &exchange.ws_url {
+                if !url.starts_with("wss://") && !url.starts_with("ws://") {
+                    return Err(MarketDataError::ConfigError(format!(
+                        "exchanges.{}: ws_url must be a ws:// or wss:// URL, got '{}'",
+                        name, url
+                    )));
+                }
+            }
+            if exchange.client_id.is_some() != exchange.client_secret.is_some() {
+                return Err(MarketDataError::ConfigError(format!(
+                    "exchanges.{}: client_id and client_secret must be set together",
+                    name
+                )));
+            }
+            exchange
+                .channels
+                .validate(exchange.book_feed, exchange.credentials().is_some())
+                .map_err(|e| Self::scoped(name, e))?;
+            exchange
+                .reference_channels
+                .validate()
+                .map_err(|e| Self::scoped(name, e))?;
+        }
+        Ok(())
+    }
+
+    /// Prefix a validation error with the exchange it belongs to
+    fn scoped(exchange: &str, error: MarketDataError) -> MarketDataError {
+        match error {
+            MarketDataError::ConfigError(msg) => {
+                MarketDataError::ConfigError(format!("exchanges.{}: {}", exchange, msg))
+            }
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::deribit::{ChannelOverride, UpdateInterval};
+    use config::FileFormat;
+
+    /// `config/default.toml` with `edit` applied to its Deribit section
+    fn config(edit: impl FnOnce(&mut ExchangeConfig)) -> Config {
+        let mut config: Config = ConfigLoader::builder()
+            .add_source(File::from_str(include_str!("../config/default.toml"), FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        edit(config.exchanges.get_mut("deribit").unwrap());
+        config
+    }
+
+    fn rejection(config: Config) -> String {
+        match config.validate() {
+            Err(MarketDataError::ConfigError(msg)) => msg,
+            other => panic!("expected a config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        config(|_| {}).validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_silence_timeout_within_a_heartbeat() {
+        let msg = rejection(config(|deribit| deribit.silence_timeout_secs = deribit.heartbeat_interval));
+        assert!(msg.starts_with("exchanges.deribit: silence_timeout_secs"), "{}", msg);
+    }
+
+    #[test]
+    fn rejects_non_websocket_url() {
+        let msg = rejection(config(|deribit| {
+            deribit.ws_url = Some("https://www.deribit.com/ws/api/v2".to_string())
+        }));
+        assert!(msg.starts_with("exchanges.deribit: ws_url"), "{}", msg);
+    }
+
+    #[test]
+    fn rejects_half_set_credentials() {
+        let msg = rejection(config(|deribit| deribit.client_id = Some("id".to_string())));
+        assert!(msg.contains("client_id and client_secret"), "{}", msg);
+
+        let msg = rejection(config(|deribit| deribit.client_secret = Some("secret".to_string())));
+        assert!(msg.contains("client_id and client_secret"), "{}", msg);
+    }
+
+    #[test]
+    fn channel_errors_name_the_exchange() {
+        let msg = rejection(config(|deribit| deribit.channels.book_depth = 5));
+        assert!(msg.starts_with("exchanges.deribit: channels for '*'"), "{}", msg);
+    }
+
+    #[test]
+    fn rejects_unsupported_book_parameters() {
+        let settings = |edit: fn(&mut ChannelSettings)| {
+            let mut settings = ChannelSettings::default();
+            edit(&mut settings);
+            settings
+        };
+
+        let depth = settings(|s| s.book_depth = 5);
+        assert!(depth.validate(BookFeed::Grouped, true).is_err());
+
+        let group = settings(|s| s.book_group = "0".to_string());
+        assert!(group.validate(BookFeed::Grouped, true).is_err());
+        let group = settings(|s| s.book_group = "fine".to_string());
+        assert!(group.validate(BookFeed::Grouped, true).is_err());
+        let group = settings(|s| s.book_group = "5".to_string());
+        group.validate(BookFeed::Grouped, true).unwrap();
+    }
+
+    #[test]
+    fn raw_interval_needs_the_incremental_feed_and_a_session() {
+        let raw = ChannelSettings {
+            interval: UpdateInterval::Raw,
+            ..ChannelSettings::default()
+        };
+
+        assert!(raw.validate(BookFeed::Grouped, true).is_err());
+        assert!(raw.validate(BookFeed::IncrementalDeltas, false).is_err());
+        raw.validate(BookFeed::IncrementalDeltas, true).unwrap();
+    }
+
+    #[test]
+    fn overrides_are_validated_too() {
+        let mut settings = ChannelSettings::default();
+        settings.overrides.insert(
+            "BTC-PERPETUAL".to_string(),
+            ChannelOverride {
+                book_depth: Some(50),
+                ..ChannelOverride::default()
+            },
+        );
+
+        let err = settings.validate(BookFeed::Grouped, true).unwrap_err().to_string();
+        assert!(err.contains("'BTC-PERPETUAL'"), "{}", err);
     }
 }
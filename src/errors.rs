@@ -8,6 +8,12 @@ pub enum MarketDataError{
     #[error("Websocket error: {0}")]
     WebSocketError(String),
 
+    #[error("Stream lagged: {0}")]
+    StreamLagged(String),
+
+    #[error("Authentication error: {0}")]
+    AuthenticationError(String),
+
     #[error("Kafka error:{0}")]
     KafkaError(#[from] rdkafka::error::KafkaError),
 
@@ -19,6 +25,18 @@ pub enum MarketDataError{
 
     #[error("JSON parse error: {0}")]
     JsonError(#[from] serde_json::Error),
+
+    #[error("Codec error: {0}")]
+    CodecError(#[from] market_data_types::CodecError),
+
+    #[error("Decimal error: {0}")]
+    DecimalError(#[from] market_data_types::DecimalError),
+
+    #[error("Schema registry error: {0}")]
+    SchemaRegistryError(String),
+
+    #[error("Spool error: {0}")]
+    SpoolError(String),
     
     #[error("HTTP error: {0}")]
     HttpError(#[from] reqwest::Error),
@@ -1,5 +1,29 @@
+pub mod auth;
+pub mod backfill;
+pub mod book;
+pub mod channels;
+pub mod connection;
+pub mod envelope;
 pub mod exchange;
+pub mod instruments;
 pub mod models;
+pub mod pool;
 
+pub use auth::DeribitCredentials;
+pub use book::BookFeed;
+pub use channels::{ChannelKind, ChannelOverride, ChannelSettings, ReferenceChannels, UpdateInterval};
+pub use connection::DeribitConnection;
+pub use envelope::Sequencer;
 pub use exchange::{Deribit, DeribitConfig};
-pub use models::{Exchange, MarketData, OrderBookSnapshot, TradeSnapshot, TickerRow};
+pub use instruments::{InstrumentResolver, InstrumentScale, InstrumentScales, SymbolSelector};
+pub use pool::ConnectionPool;
+pub use market_data_types::decimal::{FixedPoint, Price, Quantity};
+pub use market_data_types::timestamp::{Timestamp, TimestampFormat};
+pub use market_data_types::Envelope;
+pub use models::{
+    BookAction, BookLevelChange, EstimatedExpirationPrice, Exchange, FundingRate, IndexPrice,
+    InstrumentClass, InstrumentState, InstrumentStatus, LifecycleState, LiquidatedSide, Liquidation, MarkPrice,
+    MarketData, OrderBookDelta, OrderBookSnapshot, PlatformState, Quote, Settlement, Side,
+    SubscriptionStatus, SymbolCommand, SymbolUpdate, SymbolUpdateReport, TickDirection, TickerRow,
+    TradeSnapshot, VolatilityIndex,
+};
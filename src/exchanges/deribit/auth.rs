@@ -0,0 +1,117 @@
+use crate::errors::{MarketDataError, Result};
+use deribit::models::authentication::{AuthRequest, AuthResponse};
+use deribit::DeribitAPIClient;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Environment variables read when no credentials are configured
+pub const CLIENT_ID_ENV: &str = "DERIBIT_KEY";
+pub const CLIENT_SECRET_ENV: &str = "DERIBIT_SECRET";
+
+/// Refresh once this fraction of the token lifetime has passed
+const REFRESH_AT_FRACTION: f64 = 0.8;
+
+/// API key used for `public/auth` with the `client_credentials` grant
+#[derive(Clone, PartialEq, Eq)]
+pub struct DeribitCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl DeribitCredentials {
+    /// Credentials from `DERIBIT_KEY` / `DERIBIT_SECRET`, when both are set
+    pub fn from_env() -> Option<Self> {
+        let client_id = std::env::var(CLIENT_ID_ENV).ok().filter(|v| !v.is_empty())?;
+        let client_secret = std::env::var(CLIENT_SECRET_ENV).ok().filter(|v| !v.is_empty())?;
+        Some(Self {
+            client_id,
+            client_secret,
+        })
+    }
+}
+
+// Keep the secret out of logs and panics
+impl fmt::Debug for DeribitCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeribitCredentials")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Tokens issued for the current session
+#[derive(Clone)]
+pub struct AuthToken {
+    refresh_token: String,
+    scope: String,
+    issued_at: Instant,
+    expires_in: Duration,
+}
+
+impl AuthToken {
+    fn from_response(response: AuthResponse) -> Self {
+        Self {
+            refresh_token: response.refresh_token,
+            scope: response.scope,
+            issued_at: Instant::now(),
+            expires_in: Duration::from_secs(response.expires_in.max(0) as u64),
+        }
+    }
+
+    /// When the token should be rotated, comfortably before it expires
+    pub fn refresh_at(&self) -> Instant {
+        self.issued_at + self.expires_in.mul_f64(REFRESH_AT_FRACTION)
+    }
+}
+
+impl fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthToken")
+            .field("scope", &self.scope)
+            .field("expires_in", &self.expires_in)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Authenticate the session with the `client_credentials` grant
+pub async fn login(
+    api_client: &mut DeribitAPIClient,
+    credentials: &DeribitCredentials,
+) -> Result<AuthToken> {
+    let request = AuthRequest::credential_auth(&credentials.client_id, &credentials.client_secret);
+    let token = call(api_client, request).await?;
+
+    info!(
+        component = "deribit",
+        client_id = %credentials.client_id,
+        scope = %token.scope,
+        expires_in_secs = token.expires_in.as_secs(),
+        "Authenticated session"
+    );
+    Ok(token)
+}
+
+/// Rotate the session's tokens with the `refresh_token` grant
+pub async fn refresh(api_client: &mut DeribitAPIClient, token: &AuthToken) -> Result<AuthToken> {
+    let token = call(api_client, AuthRequest::refresh_token(&token.refresh_token)).await?;
+
+    info!(
+        component = "deribit",
+        expires_in_secs = token.expires_in.as_secs(),
+        "Refreshed access token"
+    );
+    Ok(token)
+}
+
+async fn call(api_client: &mut DeribitAPIClient, request: AuthRequest) -> Result<AuthToken> {
+    let response = api_client
+        .call(request)
+        .await
+        .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?
+        .await
+        .map_err(|e| MarketDataError::AuthenticationError(e.to_string()))?;
+
+    Ok(AuthToken::from_response(response))
+}
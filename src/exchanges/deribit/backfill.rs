@@ -0,0 +1,403 @@
+use market_data_types::decimal::Price;
+use super::instruments::InstrumentScales;
+use super::models::{InstrumentClass, MarketData, Side, TickDirection, TradeSnapshot};
+use market_data_types::timestamp::Timestamp;
+use crate::errors::Result;
+use serde::{Deserialize, Deserializer};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use tracing::{debug, warn};
+
+/// Trade ids remembered for deduplication between live and backfilled trades
+const SEEN_TRADE_CAPACITY: usize = 100_000;
+
+/// Trades requested per page (API maximum)
+const PAGE_SIZE: u32 = 1000;
+
+/// Trade as returned by `public/get_last_trades_by_instrument[_and_time]`
+#[derive(Debug, Clone, Deserialize)]
+struct RestTrade {
+    trade_id: String,
+    trade_seq: u64,
+    timestamp: u64,
+    instrument_name: String,
+    price: f64,
+    amount: f64,
+    direction: Side,
+    #[serde(default)]
+    index_price: Option<f64>,
+    #[serde(default)]
+    mark_price: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_tick_direction")]
+    tick_direction: Option<TickDirection>,
+}
+
+/// Unknown codes are logged and left out rather than failing the whole page
+fn lenient_tick_direction<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<TickDirection>, D::Error> {
+    let Some(code) = Option::<i64>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    match TickDirection::try_from(code) {
+        Ok(direction) => Ok(Some(direction)),
+        Err(e) => {
+            warn!(component = "deribit", error = %e, "Unexpected tick direction in backfilled trade");
+            Ok(None)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TradesPage {
+    trades: Vec<RestTrade>,
+    #[serde(default)]
+    has_more: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetTradesResponse {
+    result: TradesPage,
+}
+
+/// Last trade seen per instrument plus a bounded set of recent trade ids.
+///
+/// Shared by the live trades feed and the backfill so a trade is emitted once whichever
+/// path delivers it first.
+#[derive(Debug, Default)]
+pub struct TradeTracker {
+    last: HashMap<String, (u64, u64)>,
+    seen: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl TradeTracker {
+    /// Record a trade; false when its `trade_id` was already emitted
+    pub fn record(&mut self, instrument: &str, trade_id: &str, trade_seq: u64, timestamp: u64) -> bool {
+        if !self.seen.insert(trade_id.to_string()) {
+            return false;
+        }
+        self.order.push_back(trade_id.to_string());
+        if self.order.len() > SEEN_TRADE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+
+        let last = self.last.entry(instrument.to_string()).or_insert((trade_seq, timestamp));
+        if trade_seq >= last.0 {
+            *last = (trade_seq, timestamp);
+        }
+        true
+    }
+
+    /// `(trade_seq, timestamp_ms)` of the newest trade seen for an instrument
+    pub fn last(&self, instrument: &str) -> Option<(u64, u64)> {
+        self.last.get(instrument).copied()
+    }
+
+    /// `(trade_seq, timestamp_ms)` of the newest trade seen for every instrument
+    pub fn snapshot(&self) -> HashMap<String, (u64, u64)> {
+        self.last.clone()
+    }
+}
+
+/// Fetches trades missed while the WebSocket was down
+#[derive(Debug, Clone)]
+pub struct TradeBackfill {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl TradeBackfill {
+    pub fn new(rest_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: rest_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Every trade on `instrument` after `after_seq` up to `end_ms`, oldest first.
+    ///
+    /// Without a known `trade_seq` the first page starts at `start_ms`; every later page
+    /// continues from the last `trade_seq` received, so trades sharing a millisecond across
+    /// a page boundary are not lost.
+    pub async fn fetch(
+        &self,
+        instrument: &str,
+        after_seq: Option<u64>,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<BackfilledTrade>> {
+        let trades = collect_pages(
+            |start| self.fetch_page(instrument, start, end_ms),
+            after_seq,
+            start_ms,
+            end_ms,
+        )
+        .await?;
+
+        debug!(
+            component = "deribit",
+            instrument = %instrument,
+            trades = trades.len(),
+            "Fetched trades for backfill"
+        );
+        Ok(trades)
+    }
+
+    async fn fetch_page(&self, instrument: &str, start: PageStart, end_ms: u64) -> Result<TradesPage> {
+        let (method, mut query) = match start {
+            PageStart::Time(start_ms) => (
+                "get_last_trades_by_instrument_and_time",
+                vec![
+                    ("start_timestamp", start_ms.to_string()),
+                    ("end_timestamp", end_ms.to_string()),
+                ],
+            ),
+            PageStart::Seq(start_seq) => (
+                "get_last_trades_by_instrument",
+                vec![("start_seq", start_seq.to_string())],
+            ),
+        };
+        query.extend([
+            ("instrument_name", instrument.to_string()),
+            ("count", PAGE_SIZE.to_string()),
+            ("sorting", "asc".to_string()),
+        ]);
+
+        let response: GetTradesResponse = self
+            .http
+            .get(format!("{}/public/{}", self.base_url, method))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response.result)
+    }
+}
+
+/// Where a page of trades begins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PageStart {
+    Time(u64),
+    Seq(u64),
+}
+
+/// Follow pages from `fetch_page` until the exchange has no more or they pass `end_ms`
+async fn collect_pages<F, Fut>(
+    mut fetch_page: F,
+    after_seq: Option<u64>,
+    start_ms: u64,
+    end_ms: u64,
+) -> Result<Vec<BackfilledTrade>>
+where
+    F: FnMut(PageStart) -> Fut,
+    Fut: Future<Output = Result<TradesPage>>,
+{
+    let mut trades = Vec::new();
+    let mut start = match after_seq {
+        Some(seq) => PageStart::Seq(seq + 1),
+        None => PageStart::Time(start_ms),
+    };
+
+    loop {
+        let page = fetch_page(start).await?;
+        let received_at = Timestamp::now();
+        let last_seq = page.trades.last().map(|t| t.trade_seq);
+        let past_end = page.trades.iter().any(|t| t.timestamp > end_ms);
+
+        trades.extend(
+            page.trades
+                .into_iter()
+                .take_while(|t| t.timestamp <= end_ms)
+                .filter(|t| after_seq.is_none_or(|seq| t.trade_seq > seq))
+                .map(|trade| BackfilledTrade { trade, received_at }),
+        );
+
+        match last_seq {
+            Some(seq) if page.has_more && !past_end => start = PageStart::Seq(seq + 1),
+            _ => break,
+        }
+    }
+    Ok(trades)
+}
+
+/// A trade fetched over REST, not yet checked against the tracker
+#[derive(Debug, Clone)]
+pub struct BackfilledTrade {
+    trade: RestTrade,
+    /// When the page holding the trade arrived
+    received_at: Timestamp,
+}
+
+impl BackfilledTrade {
+    /// Record in the tracker and convert, or None when the trade was already emitted
+    pub fn into_market_data(
+        self,
+        tracker: &mut TradeTracker,
+        scales: &InstrumentScales,
+    ) -> Result<Option<MarketData>> {
+        let BackfilledTrade { trade, received_at } = self;
+        if !tracker.record(&trade.instrument_name, &trade.trade_id, trade.trade_seq, trade.timestamp) {
+            return Ok(None);
+        }
+
+        let price = scales.price(&trade.instrument_name, trade.price)?;
+        let amount = scales.quantity(&trade.instrument_name, trade.amount)?;
+        let instrument_class = InstrumentClass::from_instrument_name(&trade.instrument_name);
+
+        Ok(Some(MarketData::Trade(TradeSnapshot {
+            symbol: trade.instrument_name,
+            venue: "deribit".to_string(),
+            trade_id: trade.trade_id,
+            price,
+            amount,
+            side: trade.direction,
+            seq_id: Some(trade.trade_seq),
+            instrument_class,
+            timestamp: Timestamp::from_millis(trade.timestamp as i64),
+            ingestion_timestamp: received_at,
+            publish_timestamp: None,
+            contracts: Some(amount),
+            index_price: trade.index_price.map(Price::exact).transpose()?,
+            mark_price: trade.mark_price.map(Price::exact).transpose()?,
+            tick_direction: trade.tick_direction,
+            backfilled: true,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(trade_seq: u64, timestamp: u64) -> RestTrade {
+        RestTrade {
+            trade_id: format!("ETH-{}", trade_seq),
+            trade_seq,
+            timestamp,
+            instrument_name: "ETH-PERPETUAL".to_string(),
+            price: 2500.0,
+            amount: 1.0,
+            direction: Side::Buy,
+            index_price: None,
+            mark_price: None,
+            tick_direction: None,
+        }
+    }
+
+    /// Answer a page request the way the exchange would from `history`
+    fn page(history: &[RestTrade], start: PageStart, end_ms: u64) -> TradesPage {
+        let matching: Vec<RestTrade> = history
+            .iter()
+            .filter(|t| match start {
+                PageStart::Time(start_ms) => t.timestamp >= start_ms && t.timestamp <= end_ms,
+                PageStart::Seq(start_seq) => t.trade_seq >= start_seq,
+            })
+            .cloned()
+            .collect();
+        TradesPage {
+            has_more: matching.len() > PAGE_SIZE as usize,
+            trades: matching.into_iter().take(PAGE_SIZE as usize).collect(),
+        }
+    }
+
+    async fn backfill(
+        history: &[RestTrade],
+        after_seq: Option<u64>,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> (Vec<u64>, Vec<PageStart>) {
+        let mut requests = Vec::new();
+        let trades = collect_pages(
+            |start| {
+                requests.push(start);
+                std::future::ready(Ok(page(history, start, end_ms)))
+            },
+            after_seq,
+            start_ms,
+            end_ms,
+        )
+        .await
+        .unwrap();
+        (trades.iter().map(|t| t.trade.trade_seq).collect(), requests)
+    }
+
+    #[tokio::test]
+    async fn pages_through_a_millisecond_shared_by_more_than_a_page() {
+        let history: Vec<RestTrade> = (1..=2500).map(|seq| trade(seq, 1_000)).collect();
+
+        let (seqs, requests) = backfill(&history, None, 1_000, 2_000).await;
+
+        assert_eq!(seqs, (1..=2500).collect::<Vec<_>>());
+        assert_eq!(
+            requests,
+            [PageStart::Time(1_000), PageStart::Seq(1_001), PageStart::Seq(2_001)]
+        );
+    }
+
+    #[tokio::test]
+    async fn resumes_after_the_last_seen_trade_and_stops_at_the_end() {
+        let history: Vec<RestTrade> = (1..=10).map(|seq| trade(seq, seq * 100)).collect();
+
+        let (seqs, requests) = backfill(&history, Some(3), 300, 700).await;
+
+        assert_eq!(seqs, [4, 5, 6, 7]);
+        assert_eq!(requests, [PageStart::Seq(4)]);
+    }
+
+    #[tokio::test]
+    async fn stops_when_a_page_passes_the_end() {
+        let history: Vec<RestTrade> = (1..=1500).map(|seq| trade(seq, seq)).collect();
+
+        let (seqs, requests) = backfill(&history, Some(0), 0, 1_200).await;
+
+        assert_eq!(seqs.len(), 1_200);
+        assert_eq!(requests, [PageStart::Seq(1), PageStart::Seq(1_001)]);
+    }
+
+    #[test]
+    fn unknown_tick_directions_do_not_fail_the_page() {
+        let page: TradesPage = serde_json::from_str(
+            r#"{"trades": [
+                {"trade_id": "1", "trade_seq": 1, "timestamp": 1, "instrument_name": "ETH-PERPETUAL",
+                 "price": 2500.0, "amount": 1.0, "direction": "buy", "tick_direction": 9},
+                {"trade_id": "2", "trade_seq": 2, "timestamp": 2, "instrument_name": "ETH-PERPETUAL",
+                 "price": 2500.5, "amount": 1.0, "direction": "sell", "tick_direction": 0}
+            ], "has_more": false}"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.trades[0].tick_direction, None);
+        assert_eq!(page.trades[1].tick_direction, Some(TickDirection::PlusTick));
+    }
+
+    #[test]
+    fn tracker_emits_each_trade_id_once() {
+        let mut tracker = TradeTracker::default();
+
+        assert!(tracker.record("ETH-PERPETUAL", "ETH-2", 2, 200));
+        assert!(!tracker.record("ETH-PERPETUAL", "ETH-2", 2, 200));
+        // An older trade arriving late is emitted but does not move the last trade back
+        assert!(tracker.record("ETH-PERPETUAL", "ETH-1", 1, 100));
+
+        assert_eq!(tracker.last("ETH-PERPETUAL"), Some((2, 200)));
+        assert_eq!(tracker.last("BTC-PERPETUAL"), None);
+        assert_eq!(tracker.snapshot(), HashMap::from([("ETH-PERPETUAL".to_string(), (2, 200))]));
+    }
+
+    #[test]
+    fn tracker_forgets_the_oldest_ids_past_capacity() {
+        let mut tracker = TradeTracker::default();
+        for seq in 0..=SEEN_TRADE_CAPACITY as u64 {
+            tracker.record("ETH-PERPETUAL", &seq.to_string(), seq, seq);
+        }
+
+        assert!(!tracker.record("ETH-PERPETUAL", "1", 1, 1));
+        assert!(tracker.record("ETH-PERPETUAL", "0", 0, 0));
+    }
+}
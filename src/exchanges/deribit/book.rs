@@ -0,0 +1,378 @@
+use market_data_types::decimal::{Price, Quantity};
+use super::instruments::InstrumentScales;
+use market_data_types::timestamp::Timestamp;
+use super::models::{
+    BookAction, BookLevelChange, InstrumentClass, MarketData, OrderBookDelta, OrderBookSnapshot,
+};
+use crate::errors::Result;
+use deribit::models::subscription::{BookData, Delta};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tracing::{debug, warn};
+
+/// How the incremental book feed is surfaced to the collector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BookFeed {
+    /// Grouped `book.{instrument}.none.10.100ms` snapshots straight from the exchange
+    #[default]
+    Grouped,
+    /// Incremental feed, emitted as full-depth snapshots of the local book
+    IncrementalSnapshots,
+    /// Incremental feed, emitted as the deltas received from the exchange
+    IncrementalDeltas,
+}
+
+impl BookFeed {
+    pub fn is_incremental(&self) -> bool {
+        !matches!(self, BookFeed::Grouped)
+    }
+}
+
+/// Full-depth L2 book for one instrument, maintained from incremental updates
+#[derive(Debug, Default)]
+struct LocalOrderBook {
+    bids: BTreeMap<Price, Quantity>,
+    asks: BTreeMap<Price, Quantity>,
+    change_id: i64,
+}
+
+impl LocalOrderBook {
+    fn apply_side(side: &mut BTreeMap<Price, Quantity>, action: BookAction, price: Price, amount: Quantity) {
+        match action {
+            BookAction::New | BookAction::Change => {
+                side.insert(price, amount);
+            }
+            BookAction::Delete => {
+                side.remove(&price);
+            }
+        }
+    }
+
+    fn apply(&mut self, bids: &[BookLevelChange], asks: &[BookLevelChange], change_id: i64) {
+        for level in bids {
+            Self::apply_side(&mut self.bids, level.action, level.price, level.amount);
+        }
+        for level in asks {
+            Self::apply_side(&mut self.asks, level.action, level.price, level.amount);
+        }
+        self.change_id = change_id;
+    }
+
+    /// Bids best (highest) first
+    fn bids(&self) -> Vec<(Price, Quantity)> {
+        self.bids.iter().rev().map(|(p, a)| (*p, *a)).collect()
+    }
+
+    /// Asks best (lowest) first
+    fn asks(&self) -> Vec<(Price, Quantity)> {
+        self.asks.iter().map(|(p, a)| (*p, *a)).collect()
+    }
+}
+
+/// Result of feeding one book notification into the builder
+#[derive(Debug)]
+// Returned and matched right away; boxing would cost an allocation per book update
+#[allow(clippy::large_enum_variant)]
+pub enum BookUpdate {
+    /// Market data to forward downstream
+    Emit(MarketData),
+    /// Update was dropped while waiting for a fresh snapshot
+    Skipped,
+    /// `prev_change_id` did not match; the channel must be resubscribed to get a new snapshot
+    Resync(String),
+}
+
+/// Per-instrument local books reconstructed from the `book.{instrument}.{interval}` feed.
+///
+/// Deribit sends a full snapshot (no `prev_change_id`) right after subscribing, then deltas
+/// whose `prev_change_id` must equal the `change_id` of the previous message. Any mismatch
+/// means a message was lost, so the book is discarded until the next snapshot arrives.
+#[derive(Debug)]
+pub struct OrderBookBuilder {
+    feed: BookFeed,
+    scales: InstrumentScales,
+    books: HashMap<String, LocalOrderBook>,
+}
+
+impl OrderBookBuilder {
+    pub fn new(feed: BookFeed, scales: InstrumentScales) -> Self {
+        Self {
+            feed,
+            scales,
+            books: HashMap::new(),
+        }
+    }
+
+    /// Drop all books, e.g. after a reconnect; each instrument waits for its next snapshot
+    pub fn reset(&mut self) {
+        self.books.clear();
+    }
+
+    /// `received_at` is when the frame carrying `data` arrived
+    pub fn apply(&mut self, data: BookData, received_at: Timestamp) -> BookUpdate {
+        let instrument = data.instrument_name;
+        let levels = (
+            self.convert_levels(&instrument, &data.bids),
+            self.convert_levels(&instrument, &data.asks),
+        );
+        let (bids, asks) = match levels {
+            (Ok(bids), Ok(asks)) => (bids, asks),
+            (Err(e), _) | (_, Err(e)) => {
+                // Dropping the update would leave the local book wrong, so start over
+                warn!(
+                    component = "deribit",
+                    instrument = %instrument,
+                    error = %e,
+                    "Invalid book level, resyncing"
+                );
+                self.books.remove(&instrument);
+                return BookUpdate::Resync(instrument);
+            }
+        };
+
+        let is_snapshot = match data.prev_change_id {
+            None => {
+                let mut book = LocalOrderBook::default();
+                book.apply(&bids, &asks, data.change_id);
+                self.books.insert(instrument.clone(), book);
+                debug!(instrument = %instrument, change_id = data.change_id, "Book snapshot received");
+                true
+            }
+            Some(prev_change_id) => {
+                let Some(book) = self.books.get_mut(&instrument) else {
+                    return BookUpdate::Skipped;
+                };
+                if book.change_id != prev_change_id {
+                    warn!(
+                        component = "deribit",
+                        instrument = %instrument,
+                        expected = book.change_id,
+                        prev_change_id,
+                        "Order book sequence gap, resyncing"
+                    );
+                    self.books.remove(&instrument);
+                    return BookUpdate::Resync(instrument);
+                }
+                book.apply(&bids, &asks, data.change_id);
+                false
+            }
+        };
+
+        let timestamp = Timestamp::from_millis(data.timestamp as i64);
+        let instrument_class = InstrumentClass::from_instrument_name(&instrument);
+
+        let market_data = match self.feed {
+            BookFeed::IncrementalDeltas => MarketData::BookDelta(OrderBookDelta {
+                symbol: instrument,
+                venue: "deribit".to_string(),
+                bids,
+                asks,
+                seq_id: data.change_id as u64,
+                prev_seq_id: data.prev_change_id.map(|id| id as u64),
+                is_snapshot,
+                instrument_class,
+                timestamp,
+                ingestion_timestamp: received_at,
+                publish_timestamp: None,
+            }),
+            BookFeed::IncrementalSnapshots | BookFeed::Grouped => {
+                let book = &self.books[&instrument];
+                MarketData::Orderbook(OrderBookSnapshot {
+                    symbol: instrument.clone(),
+                    venue: "deribit".to_string(),
+                    bids: book.bids(),
+                    asks: book.asks(),
+                    seq_id: data.change_id as u64,
+                    instrument_class,
+                    timestamp,
+                    ingestion_timestamp: received_at,
+                    publish_timestamp: None,
+                })
+            }
+        };
+
+        BookUpdate::Emit(market_data)
+    }
+
+    /// Map the `deribit` crate's `(Delta, price, amount)` levels into our model
+    fn convert_levels(
+        &self,
+        instrument: &str,
+        levels: &[(Delta, f64, f64)],
+    ) -> Result<Vec<BookLevelChange>> {
+        levels
+            .iter()
+            .map(|(delta, price, amount)| {
+                Ok(BookLevelChange {
+                    action: match delta {
+                        Delta::New => BookAction::New,
+                        Delta::Change => BookAction::Change,
+                        Delta::Delete => BookAction::Delete,
+                    },
+                    price: self.scales.price(instrument, *price)?,
+                    amount: self.scales.quantity(instrument, *amount)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A `book.{instrument}.100ms` notification as Deribit sends it
+    fn book(change_id: i64, prev_change_id: Option<i64>, bids: serde_json::Value, asks: serde_json::Value) -> BookData {
+        let mut data = json!({
+            "type": if prev_change_id.is_some() { "change" } else { "snapshot" },
+            "instrument_name": "BTC-PERPETUAL",
+            "timestamp": 1_700_000_000_000u64,
+            "change_id": change_id,
+            "bids": bids,
+            "asks": asks,
+        });
+        if let Some(prev_change_id) = prev_change_id {
+            data["prev_change_id"] = json!(prev_change_id);
+        }
+        serde_json::from_value(data).unwrap()
+    }
+
+    fn snapshot() -> BookData {
+        book(
+            10,
+            None,
+            json!([["new", 100.0, 1.0], ["new", 101.0, 2.0], ["new", 99.5, 3.0]]),
+            json!([["new", 102.0, 4.0], ["new", 103.5, 5.0]]),
+        )
+    }
+
+    fn builder(feed: BookFeed) -> OrderBookBuilder {
+        OrderBookBuilder::new(feed, InstrumentScales::default())
+    }
+
+    fn received_at() -> Timestamp {
+        Timestamp::from_millis(1_700_000_000_005)
+    }
+
+    fn levels(levels: &[(f64, f64)]) -> Vec<(Price, Quantity)> {
+        levels
+            .iter()
+            .map(|(p, a)| (Price::exact(*p).unwrap(), Quantity::exact(*a).unwrap()))
+            .collect()
+    }
+
+    fn emitted_snapshot(update: BookUpdate) -> OrderBookSnapshot {
+        match update {
+            BookUpdate::Emit(MarketData::Orderbook(snapshot)) => snapshot,
+            other => panic!("expected an order book snapshot, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn snapshot_is_emitted_best_levels_first() {
+        let mut builder = builder(BookFeed::IncrementalSnapshots);
+        let snapshot = emitted_snapshot(builder.apply(snapshot(), received_at()));
+
+        assert_eq!(snapshot.symbol, "BTC-PERPETUAL");
+        assert_eq!(snapshot.seq_id, 10);
+        assert_eq!(snapshot.bids, levels(&[(101.0, 2.0), (100.0, 1.0), (99.5, 3.0)]));
+        assert_eq!(snapshot.asks, levels(&[(102.0, 4.0), (103.5, 5.0)]));
+        assert_eq!(snapshot.timestamp, Timestamp::from_millis(1_700_000_000_000));
+        assert_eq!(snapshot.ingestion_timestamp, received_at());
+    }
+
+    #[test]
+    fn deltas_change_insert_and_delete_levels() {
+        let mut builder = builder(BookFeed::IncrementalSnapshots);
+        builder.apply(snapshot(), received_at());
+
+        let delta = book(
+            11,
+            Some(10),
+            json!([["change", 100.0, 7.0], ["delete", 101.0, 0.0], ["new", 100.5, 1.5]]),
+            json!([["delete", 102.0, 0.0]]),
+        );
+        let snapshot = emitted_snapshot(builder.apply(delta, received_at()));
+
+        assert_eq!(snapshot.seq_id, 11);
+        assert_eq!(snapshot.bids, levels(&[(100.5, 1.5), (100.0, 7.0), (99.5, 3.0)]));
+        assert_eq!(snapshot.asks, levels(&[(103.5, 5.0)]));
+    }
+
+    #[test]
+    fn deltas_before_a_snapshot_are_skipped() {
+        let mut builder = builder(BookFeed::IncrementalSnapshots);
+        let delta = book(11, Some(10), json!([["change", 100.0, 7.0]]), json!([]));
+
+        assert!(matches!(builder.apply(delta, received_at()), BookUpdate::Skipped));
+    }
+
+    #[test]
+    fn change_id_gap_resyncs_and_waits_for_the_next_snapshot() {
+        let mut builder = builder(BookFeed::IncrementalSnapshots);
+        builder.apply(snapshot(), received_at());
+
+        // Change 11 was lost
+        let gap = book(12, Some(11), json!([["change", 100.0, 7.0]]), json!([]));
+        match builder.apply(gap, received_at()) {
+            BookUpdate::Resync(instrument) => assert_eq!(instrument, "BTC-PERPETUAL"),
+            other => panic!("expected a resync, got {:?}", other),
+        }
+
+        // The book was discarded, so deltas are dropped until the resubscribe snapshot
+        let next = book(13, Some(12), json!([["change", 100.0, 8.0]]), json!([]));
+        assert!(matches!(builder.apply(next, received_at()), BookUpdate::Skipped));
+
+        let snapshot = emitted_snapshot(builder.apply(snapshot(), received_at()));
+        assert_eq!(snapshot.bids.len(), 3);
+    }
+
+    #[test]
+    fn unrepresentable_level_resyncs() {
+        let mut builder = builder(BookFeed::IncrementalSnapshots);
+        builder.apply(snapshot(), received_at());
+
+        // Far beyond the decimal mantissa
+        let delta = book(11, Some(10), json!([]), json!([["new", 1e300, 1.0]]));
+        assert!(matches!(builder.apply(delta, received_at()), BookUpdate::Resync(_)));
+
+        let next = book(12, Some(11), json!([["change", 100.0, 8.0]]), json!([]));
+        assert!(matches!(builder.apply(next, received_at()), BookUpdate::Skipped));
+    }
+
+    #[test]
+    fn reset_drops_every_book() {
+        let mut builder = builder(BookFeed::IncrementalSnapshots);
+        builder.apply(snapshot(), received_at());
+        builder.reset();
+
+        let delta = book(11, Some(10), json!([["change", 100.0, 7.0]]), json!([]));
+        assert!(matches!(builder.apply(delta, received_at()), BookUpdate::Skipped));
+    }
+
+    #[test]
+    fn delta_feed_forwards_changes_as_received() {
+        let mut builder = builder(BookFeed::IncrementalDeltas);
+
+        let first = match builder.apply(snapshot(), received_at()) {
+            BookUpdate::Emit(MarketData::BookDelta(delta)) => delta,
+            other => panic!("expected a book delta, got {:?}", other),
+        };
+        assert!(first.is_snapshot);
+        assert_eq!(first.prev_seq_id, None);
+        assert_eq!(first.bids.len(), 3);
+
+        let delta = book(11, Some(10), json!([["delete", 101.0, 0.0]]), json!([]));
+        let delta = match builder.apply(delta, received_at()) {
+            BookUpdate::Emit(MarketData::BookDelta(delta)) => delta,
+            other => panic!("expected a book delta, got {:?}", other),
+        };
+        assert!(!delta.is_snapshot);
+        assert_eq!((delta.seq_id, delta.prev_seq_id), (11, Some(10)));
+        assert_eq!(delta.bids.len(), 1);
+        assert!(matches!(delta.bids[0].action, BookAction::Delete));
+        assert_eq!(delta.bids[0].price, Price::exact(101.0).unwrap());
+    }
+}
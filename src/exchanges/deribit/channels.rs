@@ -0,0 +1,670 @@
+use super::book::BookFeed;
+use super::exchange::DeribitConfig;
+use super::instruments::InstrumentScales;
+use market_data_types::timestamp::Timestamp;
+use market_data_types::decimal::{Price, Quantity};
+use super::models::{
+    EstimatedExpirationPrice, FundingRate, IndexPrice, InstrumentClass, InstrumentState,
+    InstrumentStatus, LifecycleState, LiquidatedSide, Liquidation, MarkPrice, MarketData,
+    OrderBookSnapshot, PlatformState, Quote, Settlement, Side, TickDirection, TradeSnapshot,
+    TickerRow, VolatilityIndex,
+};
+use crate::errors::{MarketDataError, Result};
+use deribit::models::Direction;
+use deribit::models::subscription::{
+    DeribitPriceIndexData, DeribitVolatilityIndexData, EstimatedExpirationPriceData,
+    GroupedBookData, InstrumentState as DeribitInstrumentState, InstrumentStateData,
+    MarkPriceOptionData, PlatformStateData, QuoteData as DeribitQuoteData, TickerData as DeribitTickerData, TickerState,
+    TradesData as DeribitTradesData,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Notification interval for book, trades and ticker channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpdateInterval {
+    /// Every change; requires an authenticated session
+    #[serde(rename = "raw")]
+    Raw,
+    #[serde(rename = "100ms")]
+    Ms100,
+    #[serde(rename = "agg2")]
+    Agg2,
+}
+
+impl UpdateInterval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpdateInterval::Raw => "raw",
+            UpdateInterval::Ms100 => "100ms",
+            UpdateInterval::Agg2 => "agg2",
+        }
+    }
+}
+
+/// Channel parameters applied to one instrument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelParams {
+    pub interval: UpdateInterval,
+    /// Price grouping for the grouped book: `none` or a grouping step such as `1`, `2`, `5`
+    pub book_group: String,
+    /// Levels per side for the grouped book: 1, 10 or 20
+    pub book_depth: u32,
+}
+
+fn default_interval() -> UpdateInterval {
+    UpdateInterval::Ms100
+}
+
+fn default_book_group() -> String {
+    "none".to_string()
+}
+
+fn default_book_depth() -> u32 {
+    10
+}
+
+/// Per-instrument override; unset fields fall back to the exchange-wide values
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelOverride {
+    pub interval: Option<UpdateInterval>,
+    pub book_group: Option<String>,
+    pub book_depth: Option<u32>,
+}
+
+/// `[exchanges.<name>.channels]`: exchange-wide channel parameters plus per-symbol overrides
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelSettings {
+    #[serde(default = "default_interval")]
+    pub interval: UpdateInterval,
+    #[serde(default = "default_book_group")]
+    pub book_group: String,
+    #[serde(default = "default_book_depth")]
+    pub book_depth: u32,
+    #[serde(default)]
+    pub overrides: HashMap<String, ChannelOverride>,
+}
+
+impl Default for ChannelSettings {
+    fn default() -> Self {
+        Self {
+            interval: default_interval(),
+            book_group: default_book_group(),
+            book_depth: default_book_depth(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl ChannelSettings {
+    /// Effective parameters for an instrument
+    pub fn for_symbol(&self, symbol: &str) -> ChannelParams {
+        let over = self.overrides.get(symbol);
+        ChannelParams {
+            interval: over.and_then(|o| o.interval).unwrap_or(self.interval),
+            book_group: over
+                .and_then(|o| o.book_group.clone())
+                .unwrap_or_else(|| self.book_group.clone()),
+            book_depth: over.and_then(|o| o.book_depth).unwrap_or(self.book_depth),
+        }
+    }
+
+    /// Reject combinations Deribit would refuse at subscribe time
+    pub fn validate(&self, book_feed: BookFeed, authenticated: bool) -> Result<()> {
+        let scopes = std::iter::once(("*", self.for_symbol("")))
+            .chain(self.overrides.keys().map(|s| (s.as_str(), self.for_symbol(s))));
+
+        for (scope, params) in scopes {
+            let invalid = |reason: String| {
+                MarketDataError::ConfigError(format!("channels for '{}': {}", scope, reason))
+            };
+
+            if !matches!(params.book_depth, 1 | 10 | 20) {
+                return Err(invalid(format!(
+                    "book_depth must be 1, 10 or 20, got {}",
+                    params.book_depth
+                )));
+            }
+            if params.book_group != "none" && !params.book_group.parse::<u32>().is_ok_and(|g| g > 0) {
+                return Err(invalid(format!(
+                    "book_group must be 'none' or a positive integer, got '{}'",
+                    params.book_group
+                )));
+            }
+            if !book_feed.is_incremental() && params.interval == UpdateInterval::Raw {
+                return Err(invalid(
+                    "the grouped book does not support the 'raw' interval".to_string(),
+                ));
+            }
+            if params.interval == UpdateInterval::Raw && !authenticated {
+                return Err(invalid(
+                    "the 'raw' interval requires an authenticated session; set client_id and client_secret".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `[exchanges.<name>.reference_channels]`: channels keyed by index or currency, not instrument
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReferenceChannels {
+    /// Index names such as `btc_usd`
+    #[serde(default)]
+    pub price_index: Vec<String>,
+    #[serde(default)]
+    pub volatility_index: Vec<String>,
+    #[serde(default)]
+    pub mark_price_options: Vec<String>,
+    #[serde(default)]
+    pub estimated_expiration_price: Vec<String>,
+    /// `{kind}.{currency}` pairs such as `option.BTC` or `any.any`
+    #[serde(default)]
+    pub instrument_state: Vec<String>,
+    #[serde(default)]
+    pub platform_state: bool,
+}
+
+impl ReferenceChannels {
+    pub fn is_empty(&self) -> bool {
+        self.channels().is_empty()
+    }
+
+    /// Every configured channel name
+    pub fn channels(&self) -> Vec<String> {
+        let keyed = [
+            (ChannelKind::PriceIndex, &self.price_index),
+            (ChannelKind::VolatilityIndex, &self.volatility_index),
+            (ChannelKind::MarkPriceOptions, &self.mark_price_options),
+            (ChannelKind::EstimatedExpirationPrice, &self.estimated_expiration_price),
+            (ChannelKind::InstrumentState, &self.instrument_state),
+        ];
+
+        let mut channels: Vec<String> = keyed
+            .into_iter()
+            .flat_map(|(kind, keys)| keys.iter().map(move |key| kind.reference_channel(key)))
+            .collect();
+
+        if self.platform_state {
+            channels.push(ChannelKind::PlatformState.reference_channel(""));
+        }
+        channels
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for pair in &self.instrument_state {
+            let valid = matches!(
+                pair.split_once('.'),
+                Some((kind, currency)) if !kind.is_empty() && !currency.is_empty() && !currency.contains('.')
+            );
+            if !valid {
+                return Err(MarketDataError::ConfigError(format!(
+                    "reference_channels.instrument_state entries must be '{{kind}}.{{currency}}', got '{}'",
+                    pair
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Channel families the adapter can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelKind {
+    Orderbook,
+    IncrementalBook,
+    Trades,
+    Ticker,
+    Quote,
+    PriceIndex,
+    VolatilityIndex,
+    MarkPriceOptions,
+    EstimatedExpirationPrice,
+    InstrumentState,
+    PlatformState,
+}
+
+impl ChannelKind {
+    pub const ALL: [ChannelKind; 11] = [
+        ChannelKind::Orderbook,
+        ChannelKind::IncrementalBook,
+        ChannelKind::Trades,
+        ChannelKind::Ticker,
+        ChannelKind::Quote,
+        ChannelKind::PriceIndex,
+        ChannelKind::VolatilityIndex,
+        ChannelKind::MarkPriceOptions,
+        ChannelKind::EstimatedExpirationPrice,
+        ChannelKind::InstrumentState,
+        ChannelKind::PlatformState,
+    ];
+
+    /// Families that are not subscribed per instrument
+    pub const REFERENCE: [ChannelKind; 6] = [
+        ChannelKind::PriceIndex,
+        ChannelKind::VolatilityIndex,
+        ChannelKind::MarkPriceOptions,
+        ChannelKind::EstimatedExpirationPrice,
+        ChannelKind::InstrumentState,
+        ChannelKind::PlatformState,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChannelKind::Orderbook => "orderbook",
+            ChannelKind::IncrementalBook => "incremental_book",
+            ChannelKind::Trades => "trades",
+            ChannelKind::Ticker => "ticker",
+            ChannelKind::Quote => "quote",
+            ChannelKind::PriceIndex => "price_index",
+            ChannelKind::VolatilityIndex => "volatility_index",
+            ChannelKind::MarkPriceOptions => "mark_price_options",
+            ChannelKind::EstimatedExpirationPrice => "estimated_expiration_price",
+            ChannelKind::InstrumentState => "instrument_state",
+            ChannelKind::PlatformState => "platform_state",
+        }
+    }
+
+    /// Build the Deribit channel name for an instrument
+    pub fn channel(&self, settings: &ChannelSettings, symbol: &str) -> String {
+        let params = settings.for_symbol(symbol);
+        let interval = params.interval.as_str();
+        match self {
+            // Grouped book: book.{instrument}.{group}.{depth}.{interval}
+            ChannelKind::Orderbook => format!(
+                "book.{}.{}.{}.{}",
+                symbol, params.book_group, params.book_depth, interval
+            ),
+            // Ungrouped book with new/change/delete deltas
+            ChannelKind::IncrementalBook => format!("book.{}.{}", symbol, interval),
+            ChannelKind::Trades => format!("trades.{}.{}", symbol, interval),
+            ChannelKind::Ticker => format!("ticker.{}.{}", symbol, interval),
+            // Sent on every top-of-book change; takes no interval
+            ChannelKind::Quote => format!("quote.{}", symbol),
+            _ => self.reference_channel(symbol),
+        }
+    }
+
+    /// Instrument of a `trades.{instrument}.{interval}` channel
+    pub fn trades_instrument(channel: &str) -> Option<&str> {
+        channel
+            .strip_prefix("trades.")
+            .and_then(|rest| rest.rsplit_once('.'))
+            .map(|(instrument, _)| instrument)
+    }
+
+    /// Build the channel name for an index name, `{kind}.{currency}` pair or nothing
+    fn reference_channel(&self, key: &str) -> String {
+        match self {
+            ChannelKind::PriceIndex => format!("deribit_price_index.{}", key),
+            ChannelKind::VolatilityIndex => format!("deribit_volatility_index.{}", key),
+            ChannelKind::MarkPriceOptions => format!("markprice.options.{}", key),
+            ChannelKind::EstimatedExpirationPrice => format!("estimated_expiration_price.{}", key),
+            ChannelKind::InstrumentState => format!("instrument.state.{}", key),
+            ChannelKind::PlatformState => "platform_state".to_string(),
+            _ => unreachable!("{} channels are built per instrument", self.as_str()),
+        }
+    }
+
+    pub fn channels<'a>(
+        &self,
+        settings: &ChannelSettings,
+        symbols: impl IntoIterator<Item = &'a String>,
+    ) -> Vec<String> {
+        symbols.into_iter().map(|s| self.channel(settings, s)).collect()
+    }
+
+    /// Book channel family for the configured feed
+    pub(super) fn orderbook(config: &DeribitConfig) -> Self {
+        if config.book_feed.is_incremental() {
+            ChannelKind::IncrementalBook
+        } else {
+            ChannelKind::Orderbook
+        }
+    }
+}
+
+pub(super) fn convert_grouped_book_to_market_data(
+    data: GroupedBookData,
+    scales: &InstrumentScales,
+    received_at: Timestamp,
+) -> Result<MarketData> {
+    let levels = |levels: Vec<(f64, f64)>| -> Result<Vec<(Price, Quantity)>> {
+        levels
+            .into_iter()
+            .map(|(price, amount)| {
+                Ok((
+                    scales.price(&data.instrument_name, price)?,
+                    scales.quantity(&data.instrument_name, amount)?,
+                ))
+            })
+            .collect()
+    };
+
+    Ok(MarketData::Orderbook(OrderBookSnapshot {
+        symbol: data.instrument_name.clone(),
+        venue: "deribit".to_string(),
+        bids: levels(data.bids)?,
+        asks: levels(data.asks)?,
+        seq_id: data.change_id as u64,
+        instrument_class: InstrumentClass::from_instrument_name(&data.instrument_name),
+        timestamp: exchange_time(data.timestamp),
+        ingestion_timestamp: received_at,
+        publish_timestamp: None,
+    }))
+}
+
+/// Liquidation trades are emitted twice: as a trade and as a `Liquidation`
+pub(super) fn convert_trades_to_market_data(
+    trades: Vec<DeribitTradesData>,
+    scales: &InstrumentScales,
+    received_at: Timestamp,
+) -> Result<Vec<MarketData>> {
+    let mut converted = Vec::with_capacity(trades.len());
+
+    for trade in trades {
+        let price = scales.price(&trade.instrument_name, trade.price)?;
+        let amount = scales.quantity(&trade.instrument_name, trade.amount)?;
+        let instrument_class = InstrumentClass::from_instrument_name(&trade.instrument_name);
+        let timestamp = exchange_time(trade.timestamp);
+        let liquidated = trade.liquidation.as_deref().and_then(LiquidatedSide::from_flag);
+        let tick_direction = tick_direction(&trade);
+
+        let snapshot = TradeSnapshot {
+            symbol: trade.instrument_name,
+            venue: "deribit".to_string(),
+            trade_id: trade.trade_id,
+            price,
+            amount,
+            side: side(trade.direction),
+            seq_id: Some(trade.trade_seq),
+            instrument_class,
+            timestamp,
+            ingestion_timestamp: received_at,
+            publish_timestamp: None,
+            // Use amount as contracts
+            contracts: Some(amount),
+            index_price: Some(Price::exact(trade.index_price)?),
+            mark_price: None, // Not provided in trades subscription
+            tick_direction,
+            backfilled: false,
+        };
+
+        let liquidation = liquidated.map(|liquidated| Liquidation {
+            symbol: snapshot.symbol.clone(),
+            venue: snapshot.venue.clone(),
+            trade_id: snapshot.trade_id.clone(),
+            price,
+            amount,
+            side: snapshot.side,
+            liquidated,
+            instrument_class,
+            timestamp,
+            ingestion_timestamp: received_at,
+            publish_timestamp: None,
+        });
+
+        converted.push(MarketData::Trade(snapshot));
+        converted.extend(liquidation.map(MarketData::Liquidation));
+    }
+    Ok(converted)
+}
+
+/// Quotes and amounts follow the tick grid; index, mark and settlement prices keep full precision
+pub(super) fn convert_ticker_to_market_data(
+    ticker: DeribitTickerData,
+    scales: &InstrumentScales,
+    received_at: Timestamp,
+) -> Result<MarketData> {
+    let symbol = ticker.instrument_name.as_str();
+    let price = |p: f64| scales.price(symbol, p);
+
+    Ok(MarketData::Ticker(Box::new(TickerRow {
+        timestamp: exchange_time(ticker.timestamp),
+        ingestion_timestamp: received_at,
+        publish_timestamp: None,
+        venue: "deribit".to_string(),
+        state: instrument_state(ticker.state),
+        symbol: ticker.instrument_name.clone(),
+        index_price: Some(Price::exact(ticker.index_price)?),
+        settlement_price: ticker.settlement_price.map(Price::exact).transpose()?,
+        open_interest: Some(Quantity::exact(ticker.open_interest)?),
+        mark_price: Some(Price::exact(ticker.mark_price)?),
+        best_bid_price: ticker.best_bid_price.map(price).transpose()?,
+        mark_iv: ticker.mark_iv,
+        ask_iv: ticker.ask_iv,
+        bid_iv: ticker.bid_iv,
+        underlying_price: ticker.underlying_price.map(Price::exact).transpose()?,
+        underlying_index: ticker.underlying_index.clone(),
+        best_ask_price: ticker.best_ask_price.map(price).transpose()?,
+        interest_rate: ticker.interest_rate,
+        estimated_delivery_price: Some(Price::exact(ticker.estimated_delivery_price)?),
+        best_ask_amount: Some(scales.quantity(symbol, ticker.best_ask_amount)?),
+        best_bid_amount: Some(scales.quantity(symbol, ticker.best_bid_amount)?),
+        current_funding: ticker.current_funding,
+        delivery_price: ticker.delivery_price.map(Price::exact).transpose()?,
+        funding_8h: ticker.funding_8h,
+        interest_value: None, // Not available in subscription data
+        greeks_delta: ticker.greeks.as_ref().map(|g| g.delta),
+        greeks_gamma: ticker.greeks.as_ref().map(|g| g.gamma),
+        greeks_vega: ticker.greeks.as_ref().map(|g| g.vega),
+        greeks_theta: ticker.greeks.as_ref().map(|g| g.theta),
+        greeks_rho: ticker.greeks.as_ref().map(|g| g.rho),
+    })))
+}
+
+/// Funding of a perpetual; None for other instruments or when the ticker carries none.
+/// The caller decides whether the rate changed
+pub(super) fn funding_rate_from_ticker(
+    ticker: &DeribitTickerData,
+    received_at: Timestamp,
+) -> Result<Option<FundingRate>> {
+    if InstrumentClass::from_instrument_name(&ticker.instrument_name)
+        != Some(InstrumentClass::Perpetual)
+    {
+        return Ok(None);
+    }
+    let Some(current_funding) = ticker.current_funding else {
+        return Ok(None);
+    };
+
+    Ok(Some(FundingRate {
+        symbol: ticker.instrument_name.clone(),
+        venue: "deribit".to_string(),
+        current_funding,
+        funding_8h: ticker.funding_8h,
+        index_price: Price::exact(ticker.index_price)?,
+        mark_price: Price::exact(ticker.mark_price)?,
+        timestamp: exchange_time(ticker.timestamp),
+        ingestion_timestamp: received_at,
+        publish_timestamp: None,
+    }))
+}
+
+/// Settlement carried by a ticker; the caller decides whether the price is new
+pub(super) fn settlement_from_ticker(
+    ticker: &DeribitTickerData,
+    received_at: Timestamp,
+) -> Result<Option<Settlement>> {
+    let Some(settlement_price) = ticker.settlement_price else {
+        return Ok(None);
+    };
+
+    Ok(Some(Settlement {
+        symbol: ticker.instrument_name.clone(),
+        venue: "deribit".to_string(),
+        settlement_price: Price::exact(settlement_price)?,
+        delivery_price: ticker.delivery_price.map(Price::exact).transpose()?,
+        instrument_class: InstrumentClass::from_instrument_name(&ticker.instrument_name),
+        timestamp: exchange_time(ticker.timestamp),
+        ingestion_timestamp: received_at,
+        publish_timestamp: None,
+    }))
+}
+
+pub(super) fn convert_quote_to_market_data(
+    quote: DeribitQuoteData,
+    scales: &InstrumentScales,
+    received_at: Timestamp,
+) -> Result<MarketData> {
+    let symbol = quote.instrument_name.as_str();
+    let price = |p: f64| scales.price(symbol, p);
+    let quantity = |a: f64| scales.quantity(symbol, a);
+
+    Ok(MarketData::Quote(Quote {
+        symbol: quote.instrument_name.clone(),
+        venue: "deribit".to_string(),
+        best_bid_price: quote.best_bid_price.map(price).transpose()?,
+        best_bid_amount: quote.best_bid_amount.map(quantity).transpose()?,
+        best_ask_price: quote.best_ask_price.map(price).transpose()?,
+        best_ask_amount: quote.best_ask_amount.map(quantity).transpose()?,
+        instrument_class: InstrumentClass::from_instrument_name(symbol),
+        timestamp: exchange_time(quote.timestamp),
+        ingestion_timestamp: received_at,
+        publish_timestamp: None,
+    }))
+}
+
+fn exchange_time(timestamp_ms: u64) -> Timestamp {
+    Timestamp::from_millis(timestamp_ms as i64)
+}
+
+fn side(direction: Direction) -> Side {
+    match direction {
+        Direction::Buy => Side::Buy,
+        Direction::Sell => Side::Sell,
+    }
+}
+
+/// Unknown codes are logged and left out rather than dropping the trade
+fn tick_direction(trade: &DeribitTradesData) -> Option<TickDirection> {
+    match TickDirection::try_from(trade.tick_direction as i64) {
+        Ok(direction) => Some(direction),
+        Err(e) => {
+            warn!(
+                component = "deribit",
+                instrument = %trade.instrument_name,
+                trade_id = %trade.trade_id,
+                error = %e,
+                "Unexpected tick direction"
+            );
+            None
+        }
+    }
+}
+
+fn instrument_state(state: TickerState) -> InstrumentState {
+    match state {
+        TickerState::Open => InstrumentState::Open,
+        TickerState::Closed => InstrumentState::Closed,
+    }
+}
+
+fn lifecycle_state(state: DeribitInstrumentState) -> LifecycleState {
+    match state {
+        DeribitInstrumentState::Created => LifecycleState::Created,
+        DeribitInstrumentState::Started => LifecycleState::Started,
+        DeribitInstrumentState::Settled => LifecycleState::Settled,
+        DeribitInstrumentState::Closed => LifecycleState::Closed,
+        DeribitInstrumentState::Terminated => LifecycleState::Terminated,
+    }
+}
+
+/// Last segment of a channel name, e.g. `btc_usd` for `estimated_expiration_price.btc_usd`
+pub(super) fn channel_key(channel: &str) -> String {
+    channel.rsplit('.').next().unwrap_or(channel).to_string()
+}
+
+pub(super) fn convert_price_index_to_market_data(
+    data: DeribitPriceIndexData,
+    received_at: Timestamp,
+) -> MarketData {
+    MarketData::IndexPrice(IndexPrice {
+        index_name: data.index_name,
+        venue: "deribit".to_string(),
+        price: data.price,
+        timestamp: exchange_time(data.timestamp),
+        ingestion_timestamp: received_at,
+        publish_timestamp: None,
+    })
+}
+
+pub(super) fn convert_volatility_index_to_market_data(
+    data: DeribitVolatilityIndexData,
+    received_at: Timestamp,
+) -> MarketData {
+    MarketData::VolatilityIndex(VolatilityIndex {
+        index_name: data.index_name,
+        venue: "deribit".to_string(),
+        volatility: data.volatility,
+        timestamp: exchange_time(data.timestamp),
+        ingestion_timestamp: received_at,
+        publish_timestamp: None,
+    })
+}
+
+pub(super) fn convert_mark_prices_to_market_data(
+    data: Vec<MarkPriceOptionData>,
+    received_at: Timestamp,
+) -> Vec<MarketData> {
+    data.into_iter()
+        .map(|mark| {
+            MarketData::MarkPrice(MarkPrice {
+                symbol: mark.instrument_name,
+                venue: "deribit".to_string(),
+                mark_price: mark.mark_price,
+                iv: Some(mark.iv),
+                timestamp: exchange_time(mark.timestamp),
+                ingestion_timestamp: received_at,
+                publish_timestamp: None,
+            })
+        })
+        .collect()
+}
+
+pub(super) fn convert_expiration_price_to_market_data(
+    channel: &str,
+    data: EstimatedExpirationPriceData,
+    received_at: Timestamp,
+) -> MarketData {
+    MarketData::EstimatedExpirationPrice(EstimatedExpirationPrice {
+        index_name: channel_key(channel),
+        venue: "deribit".to_string(),
+        price: data.price,
+        is_estimated: data.is_estimated,
+        seconds_to_expiry: data.seconds,
+        timestamp: received_at,
+        ingestion_timestamp: received_at,
+        publish_timestamp: None,
+    })
+}
+
+pub(super) fn convert_instrument_state_to_market_data(
+    data: InstrumentStateData,
+    received_at: Timestamp,
+) -> MarketData {
+    MarketData::InstrumentStatus(InstrumentStatus {
+        symbol: data.instrument_name,
+        venue: "deribit".to_string(),
+        state: lifecycle_state(data.state),
+        timestamp: exchange_time(data.timestamp),
+        ingestion_timestamp: received_at,
+        publish_timestamp: None,
+    })
+}
+
+pub(super) fn convert_platform_state_to_market_data(
+    data: PlatformStateData,
+    received_at: Timestamp,
+) -> MarketData {
+    MarketData::PlatformState(PlatformState {
+        venue: "deribit".to_string(),
+        price_index: data.price_index,
+        locked: data.locked,
+        maintenance: data.maintenance,
+        allow_unauthenticated_public_requests: data.allow_unauthenticated_public_requests,
+        ingestion_timestamp: received_at,
+        publish_timestamp: None,
+    })
+}
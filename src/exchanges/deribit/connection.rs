@@ -0,0 +1,869 @@
+use super::auth::{self, AuthToken};
+use super::backfill::{TradeBackfill, TradeTracker};
+use super::book::{BookUpdate, OrderBookBuilder};
+use super::channels::{self, ChannelKind};
+use market_data_types::decimal::Price;
+use super::exchange::DeribitConfig;
+use super::instruments::InstrumentScales;
+use super::envelope::Sequencer;
+use market_data_types::Envelope;
+use super::models::{FundingRate, MarketData, Settlement, SubscriptionStatus};
+use market_data_types::timestamp::Timestamp;
+use crate::errors::{MarketDataError, Result};
+use deribit::{
+    models::{
+        subscription::{
+            HeartbeatType, PrivateSubscribeRequest, PrivateUnsubscribeRequest,
+            PublicSubscribeRequest, PublicUnsubscribeRequest, SubscriptionData,
+            SubscriptionMessage, SubscriptionParams,
+        },
+        session_management::SetHeartbeatRequest,
+        TestRequest,
+    },
+    DeribitAPIClient, DeribitSubscriptionClient,
+};
+use futures::StreamExt;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, Mutex, Notify, RwLock};
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::{debug, error, info, warn};
+
+/// Delay before retrying after the token could neither be refreshed nor re-issued
+const AUTH_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// An open socket, authenticated when credentials are configured
+struct Session {
+    api_client: DeribitAPIClient,
+    subscription_client: DeribitSubscriptionClient,
+    token: Option<AuthToken>,
+}
+
+/// One Deribit WebSocket session shared by every channel family.
+///
+/// A reader task owns the subscription client and fans notifications out to one broadcast
+/// channel per `ChannelKind`, so book, trades and ticker consumers run concurrently over a
+/// single socket. Heartbeats are answered by their own task, and a watchdog forces a
+/// reconnect when the socket goes silent. The reader reconnects on disconnect, restoring
+/// every channel recorded in `channels` that the exchange has not rejected. With credentials
+/// configured the session is authenticated on every (re)connect and its token is rotated
+/// before it expires.
+pub struct DeribitConnection {
+    /// Position in the connection pool, used in logs
+    id: usize,
+    config: DeribitConfig,
+    api_client: RwLock<DeribitAPIClient>,
+    token: RwLock<Option<AuthToken>>,
+    channels: RwLock<HashMap<String, SubscriptionStatus>>,
+    /// Paces subscribe/unsubscribe requests to `subscribe_interval_ms`
+    pacer: Mutex<Interval>,
+    /// Last trade per instrument and recent trade ids, shared by live and backfilled trades
+    trades: StdMutex<TradeTracker>,
+    /// Last settlement price seen per instrument, so only changes are published
+    settlements: StdMutex<HashMap<String, Price>>,
+    /// Last `(current_funding, funding_8h)` published per perpetual, so only changes are published
+    fundings: StdMutex<HashMap<String, (f64, Option<f64>)>>,
+    backfill: Option<TradeBackfill>,
+    /// Tick-size scales used to convert prices and amounts
+    scales: InstrumentScales,
+    /// Unix millis of the last frame read from the socket
+    last_frame_ms: AtomicU64,
+    /// Signalled by the watchdog when the socket has gone silent
+    watchdog: Notify,
+    /// Bumped on every reconnect so a restore left over from an earlier session stops
+    session: AtomicU64,
+    /// Numbers and wraps every message; shared across the pool
+    sequencer: Arc<Sequencer>,
+    senders: HashMap<ChannelKind, broadcast::Sender<Envelope>>,
+}
+
+impl DeribitConnection {
+    /// Open the session and start the reader task.
+    ///
+    /// Notifications are published to `senders`, which every connection in a pool shares.
+    pub async fn connect(
+        id: usize,
+        config: DeribitConfig,
+        senders: HashMap<ChannelKind, broadcast::Sender<Envelope>>,
+        scales: InstrumentScales,
+        sequencer: Arc<Sequencer>,
+    ) -> Result<Arc<Self>> {
+        let session = Self::open_session(&config).await?;
+
+        let mut pacer = tokio::time::interval(Duration::from_millis(config.subscribe_interval_ms.max(1)));
+        pacer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let backfill = config.trade_backfill.then(|| TradeBackfill::new(&config.rest_url()));
+
+        let connection = Arc::new(Self {
+            id,
+            config,
+            api_client: RwLock::new(session.api_client),
+            token: RwLock::new(session.token),
+            channels: RwLock::new(HashMap::new()),
+            pacer: Mutex::new(pacer),
+            trades: StdMutex::new(TradeTracker::default()),
+            settlements: StdMutex::new(HashMap::new()),
+            fundings: StdMutex::new(HashMap::new()),
+            backfill,
+            scales,
+            last_frame_ms: AtomicU64::new(now_ms()),
+            watchdog: Notify::new(),
+            session: AtomicU64::new(0),
+            sequencer,
+            senders,
+        });
+
+        let (heartbeat_tx, heartbeat_rx) = mpsc::channel(1);
+        tokio::spawn(connection.clone().run_heartbeat(heartbeat_rx));
+        tokio::spawn(connection.clone().run_watchdog());
+        tokio::spawn(connection.clone().run_reader(session.subscription_client, heartbeat_tx));
+        if connection.config.credentials.is_some() {
+            tokio::spawn(connection.clone().run_token_refresh());
+        }
+
+        Ok(connection)
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Status of every channel requested so far
+    pub async fn subscription_status(&self) -> HashMap<String, SubscriptionStatus> {
+        self.channels.read().await.clone()
+    }
+
+    /// Subscribe in chunks; channels the exchange does not acknowledge are marked rejected
+    pub async fn subscribe(&self, channels: Vec<String>) -> Result<()> {
+        if channels.is_empty() {
+            return Ok(());
+        }
+        {
+            let mut status = self.channels.write().await;
+            for channel in &channels {
+                status.insert(channel.clone(), SubscriptionStatus::Pending);
+            }
+        }
+        self.subscribe_chunked(&channels).await
+    }
+
+    pub async fn unsubscribe(&self, channels: Vec<String>) -> Result<()> {
+        if channels.is_empty() {
+            return Ok(());
+        }
+        // Forget them first so a concurrent reconnect does not restore them
+        {
+            let mut status = self.channels.write().await;
+            for channel in &channels {
+                status.remove(channel);
+            }
+        }
+        for chunk in channels.chunks(self.config.subscribe_chunk_size.max(1)) {
+            self.send_unsubscribe(chunk.to_vec()).await?;
+        }
+        Ok(())
+    }
+
+    /// Resubscribe a single channel so Deribit sends a fresh snapshot
+    async fn resync(&self, channel: String) -> Result<()> {
+        self.send_unsubscribe(vec![channel.clone()]).await?;
+        let acked = self.send_subscribe(vec![channel.clone()]).await?;
+        self.record_acks(&[channel], &acked).await;
+        Ok(())
+    }
+
+    async fn subscribe_chunked(&self, channels: &[String]) -> Result<()> {
+        for chunk in channels.chunks(self.config.subscribe_chunk_size.max(1)) {
+            let acked = self.send_subscribe(chunk.to_vec()).await?;
+            self.record_acks(chunk, &acked).await;
+        }
+        Ok(())
+    }
+
+    /// Compare a subscribe response against the request and update channel status
+    async fn record_acks(&self, requested: &[String], acked: &[String]) {
+        let acked: HashSet<&String> = acked.iter().collect();
+        let mut rejected = Vec::new();
+        {
+            let mut status = self.channels.write().await;
+            for channel in requested {
+                // Unsubscribed while the request was in flight
+                let Some(entry) = status.get_mut(channel) else {
+                    continue;
+                };
+                if acked.contains(channel) {
+                    *entry = SubscriptionStatus::Active;
+                } else {
+                    *entry = SubscriptionStatus::Rejected;
+                    rejected.push(channel.as_str());
+                }
+            }
+        }
+
+        if !rejected.is_empty() {
+            warn!(
+                component = "deribit",
+                connection = self.id,
+                requested = requested.len(),
+                rejected = ?rejected,
+                "Exchange did not acknowledge channels"
+            );
+        }
+    }
+
+    /// Open a WebSocket connection, configure the heartbeat and authenticate
+    async fn open_session(config: &DeribitConfig) -> Result<Session> {
+        info!("Connecting to Deribit WebSocket...");
+
+        // Build the Deribit client
+        let mut builder = deribit::Deribit::builder();
+        builder.testnet(config.testnet);
+        if let Some(url) = &config.ws_url {
+            builder.url(url.clone());
+        }
+        let drb = builder
+            .build()
+            .map_err(|e| MarketDataError::ConnectionError(e.to_string()))?;
+
+        let (mut api_client, subscription_client) = drb
+            .connect()
+            .await
+            .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?;
+
+        // Set heartbeat
+        info!("Setting heartbeat interval to {}s", config.heartbeat_interval);
+        let _ = api_client
+            .call(SetHeartbeatRequest::with_interval(config.heartbeat_interval))
+            .await
+            .map_err(|e| MarketDataError::ConnectionError(e.to_string()))?
+            .await;
+
+        let token = match &config.credentials {
+            Some(credentials) => Some(auth::login(&mut api_client, credentials).await?),
+            None => None,
+        };
+
+        Ok(Session {
+            api_client,
+            subscription_client,
+            token,
+        })
+    }
+
+    /// Subscribe through `private/subscribe` on authenticated sessions so raw channels are allowed.
+    ///
+    /// Returns the channels listed in the response.
+    async fn send_subscribe(&self, channels: Vec<String>) -> Result<Vec<String>> {
+        self.pacer.lock().await.tick().await;
+        let mut api_client = self.api_client.write().await;
+
+        debug!("Subscribing to channels: {:?}", channels);
+        let acked = if self.config.credentials.is_some() {
+            api_client
+                .call(PrivateSubscribeRequest::new(&channels))
+                .await
+                .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?
+                .await
+                .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?
+        } else {
+            api_client
+                .call(PublicSubscribeRequest::new(&channels))
+                .await
+                .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?
+                .await
+                .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?
+        };
+
+        Ok(acked)
+    }
+
+    async fn send_unsubscribe(&self, channels: Vec<String>) -> Result<()> {
+        self.pacer.lock().await.tick().await;
+        let mut api_client = self.api_client.write().await;
+
+        debug!("Unsubscribing from channels: {:?}", channels);
+        if self.config.credentials.is_some() {
+            api_client
+                .call(PrivateUnsubscribeRequest::new(&channels))
+                .await
+                .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?
+                .await
+                .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?;
+        } else {
+            api_client
+                .call(PublicUnsubscribeRequest::new(&channels))
+                .await
+                .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?
+                .await
+                .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Rotate the access token before it expires; never returns.
+    ///
+    /// Falls back to a fresh `client_credentials` login when the refresh token is rejected,
+    /// e.g. because a reconnect already issued a new one.
+    async fn run_token_refresh(self: Arc<Self>) {
+        let Some(credentials) = self.config.credentials.clone() else {
+            return;
+        };
+
+        loop {
+            let refresh_at = match &*self.token.read().await {
+                Some(token) => token.refresh_at(),
+                None => Instant::now() + AUTH_RETRY_DELAY,
+            };
+            tokio::time::sleep(refresh_at.saturating_duration_since(Instant::now())).await;
+
+            let current = self.token.read().await.clone();
+            let result = {
+                let mut api_client = self.api_client.write().await;
+                let refreshed = match &current {
+                    Some(token) => auth::refresh(&mut api_client, token).await,
+                    None => Err(MarketDataError::AuthenticationError("no active token".to_string())),
+                };
+                match refreshed {
+                    Ok(token) => Ok(token),
+                    Err(e) => {
+                        warn!(
+                            component = "deribit",
+                            connection = self.id,
+                            error = %e,
+                            "Token refresh failed, logging in again"
+                        );
+                        auth::login(&mut api_client, &credentials).await
+                    }
+                }
+            };
+
+            match result {
+                Ok(token) => *self.token.write().await = Some(token),
+                Err(e) => {
+                    error!(
+                        component = "deribit",
+                        connection = self.id,
+                        error = %e,
+                        "Re-authentication failed"
+                    );
+                    *self.token.write().await = None;
+                }
+            }
+        }
+    }
+
+    /// Read every frame from the socket and route it; never returns.
+    ///
+    /// Nothing here waits on consumers or on the API client: heartbeat replies go to
+    /// `run_heartbeat` and book resyncs run in their own tasks, so a slow sink cannot stall
+    /// the socket.
+    async fn run_reader(
+        self: Arc<Self>,
+        mut sub_client: DeribitSubscriptionClient,
+        heartbeats: mpsc::Sender<()>,
+    ) {
+        let mut books = OrderBookBuilder::new(self.config.book_feed, self.scales.clone());
+
+        loop {
+            let message = tokio::select! {
+                message = sub_client.next() => message,
+                _ = self.watchdog.notified() => {
+                    // Stale wake-up from before the last reconnect
+                    if self.silence() < self.silence_timeout() {
+                        continue;
+                    }
+                    warn!(
+                        component = "deribit",
+                        connection = self.id,
+                        silent_ms = self.silence().as_millis() as u64,
+                        "No frames within silence timeout, forcing reconnect"
+                    );
+                    sub_client = self.restart(&mut books).await;
+                    continue;
+                }
+            };
+
+            // Stamped before any parsing so latency figures include our own processing
+            let received_at = Timestamp::now();
+            if let Some(Ok(_)) = &message {
+                self.last_frame_ms.store(received_at.as_millis() as u64, Ordering::Relaxed);
+            }
+
+            match message {
+                Some(Ok(SubscriptionMessage {
+                    params: SubscriptionParams::Heartbeat { r#type: HeartbeatType::TestRequest },
+                    ..
+                })) => {
+                    // A full queue already holds a pending reply
+                    if heartbeats.try_send(()).is_err() {
+                        debug!("Heartbeat reply already queued");
+                    }
+                }
+                Some(Ok(SubscriptionMessage {
+                    params: SubscriptionParams::Subscription(data),
+                    ..
+                })) => self.route(&mut books, data, received_at),
+                Some(Ok(_)) => {
+                    debug!("Ignoring non-subscription message");
+                }
+                Some(Err(e)) => {
+                    warn!(
+                        component = "deribit",
+                        connection = self.id,
+                        error = %e,
+                        "WebSocket error, reconnecting"
+                    );
+                    sub_client = self.restart(&mut books).await;
+                }
+                None => {
+                    warn!(
+                        component = "deribit",
+                        connection = self.id,
+                        "WebSocket stream ended, reconnecting"
+                    );
+                    sub_client = self.restart(&mut books).await;
+                }
+            }
+        }
+    }
+
+    /// Reconnect, then restore channels and backfill missed trades in the background
+    async fn restart(self: &Arc<Self>, books: &mut OrderBookBuilder) -> DeribitSubscriptionClient {
+        // Capture before resubscribing: live trades on the new socket move the tracker past the gap
+        let last_trades = self.trades.lock().unwrap_or_else(|e| e.into_inner()).snapshot();
+        // The watchdog fires up to `silence_timeout_secs` after the feed went quiet
+        let last_frame = self.last_frame_ms.load(Ordering::Relaxed);
+        books.reset();
+        let sub_client = self.reconnect().await;
+        self.last_frame_ms.store(now_ms(), Ordering::Relaxed);
+        let session = self.session.fetch_add(1, Ordering::Relaxed) + 1;
+        tokio::spawn(self.clone().restore(session, last_trades, last_frame));
+        sub_client
+    }
+
+    /// Resubscribe every recorded channel on a new session, then backfill missed trades.
+    ///
+    /// Runs beside the reader so frames and heartbeats on the new socket are handled while
+    /// the paced chunks go out. Channels still pending after a failure are retried with
+    /// backoff until a newer session takes over.
+    async fn restore(
+        self: Arc<Self>,
+        session: u64,
+        last_trades: HashMap<String, (u64, u64)>,
+        last_frame: u64,
+    ) {
+        // Rejected channels stay rejected; everything else is pending until acked again
+        let mut restoring = 0;
+        for status in self.channels.write().await.values_mut() {
+            if *status != SubscriptionStatus::Rejected {
+                *status = SubscriptionStatus::Pending;
+                restoring += 1;
+            }
+        }
+        if restoring == 0 {
+            info!(
+                component = "deribit",
+                connection = self.id,
+                "Reconnected, no channels to restore"
+            );
+            return;
+        }
+
+        let mut attempt: u32 = 0;
+        loop {
+            if self.session.load(Ordering::Relaxed) != session {
+                return;
+            }
+            let pending: Vec<String> = self
+                .channels
+                .read()
+                .await
+                .iter()
+                .filter(|(_, s)| **s == SubscriptionStatus::Pending)
+                .map(|(channel, _)| channel.clone())
+                .collect();
+
+            match self.subscribe_chunked(&pending).await {
+                Ok(()) => {
+                    info!(
+                        component = "deribit",
+                        connection = self.id,
+                        channels = restoring,
+                        "Reconnected and resubscribed"
+                    );
+                    break;
+                }
+                Err(e) => {
+                    let delay = Self::backoff_delay(&self.config, attempt);
+                    attempt = attempt.saturating_add(1);
+                    warn!(
+                        component = "deribit",
+                        connection = self.id,
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %e,
+                        "Resubscribe failed"
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+
+        self.backfill_trades(last_trades, last_frame).await;
+    }
+
+    /// Answer heartbeat test requests; never blocks the reader
+    async fn run_heartbeat(self: Arc<Self>, mut requests: mpsc::Receiver<()>) {
+        while requests.recv().await.is_some() {
+            debug!("Received heartbeat test request, responding...");
+            let mut api = self.api_client.write().await;
+            if let Err(e) = api.call(TestRequest::default()).await {
+                debug!("Failed to respond to heartbeat: {:?}", e);
+            }
+        }
+    }
+
+    /// Wake the reader when no frame has arrived within `silence_timeout_secs`
+    async fn run_watchdog(self: Arc<Self>) {
+        let timeout = self.silence_timeout();
+        let mut interval = tokio::time::interval((timeout / 4).max(Duration::from_secs(1)));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            if self.silence() >= timeout {
+                self.watchdog.notify_one();
+            }
+        }
+    }
+
+    /// Time since the last frame was read
+    fn silence(&self) -> Duration {
+        Duration::from_millis(now_ms().saturating_sub(self.last_frame_ms.load(Ordering::Relaxed)))
+    }
+
+    fn silence_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.silence_timeout_secs)
+    }
+
+    /// Convert a notification and publish it to its channel family
+    fn route(
+        self: &Arc<Self>,
+        books: &mut OrderBookBuilder,
+        data: SubscriptionData,
+        received_at: Timestamp,
+    ) {
+        match data {
+            SubscriptionData::GroupedBook(data) => {
+                let converted = channels::convert_grouped_book_to_market_data(
+                    data.data,
+                    &self.scales,
+                    received_at,
+                );
+                if let Some(market_data) = self.checked(&data.channel, converted) {
+                    self.publish(ChannelKind::Orderbook, &data.channel, market_data);
+                }
+            }
+            SubscriptionData::Book(data) => match books.apply(data.data, received_at) {
+                BookUpdate::Emit(market_data) => {
+                    self.publish(ChannelKind::IncrementalBook, &data.channel, market_data);
+                }
+                BookUpdate::Skipped => {
+                    debug!("Skipping book update while awaiting snapshot");
+                }
+                BookUpdate::Resync(instrument) => {
+                    let channel =
+                        ChannelKind::IncrementalBook.channel(&self.config.channels, &instrument);
+                    let connection = self.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = connection.resync(channel).await {
+                            warn!(
+                                component = "deribit",
+                                connection = connection.id,
+                                instrument = %instrument,
+                                error = %e,
+                                "Failed to resync order book"
+                            );
+                        }
+                    });
+                }
+            },
+            SubscriptionData::Trades(data) => {
+                // Drop trades the backfill already emitted
+                let trades = {
+                    let mut tracker = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+                    data.data
+                        .into_iter()
+                        .filter(|t| {
+                            tracker.record(&t.instrument_name, &t.trade_id, t.trade_seq, t.timestamp)
+                        })
+                        .collect()
+                };
+                let converted =
+                    channels::convert_trades_to_market_data(trades, &self.scales, received_at);
+                for market_data in self.checked(&data.channel, converted).unwrap_or_default() {
+                    self.publish(ChannelKind::Trades, &data.channel, market_data);
+                }
+            }
+            SubscriptionData::Ticker(data) => {
+                let funding = channels::funding_rate_from_ticker(&data.data, received_at);
+                let settlement = channels::settlement_from_ticker(&data.data, received_at);
+                let ticker =
+                    channels::convert_ticker_to_market_data(data.data, &self.scales, received_at);
+                // All or nothing, so a bad field never splits a ticker from its funding
+                let converted = ticker.and_then(|ticker| Ok((ticker, funding?, settlement?)));
+                let Some((ticker, funding, settlement)) = self.checked(&data.channel, converted)
+                else {
+                    return;
+                };
+                let funding = funding.filter(|funding| self.is_new_funding(funding));
+                let settlement = settlement.filter(|settlement| self.is_new_settlement(settlement));
+
+                self.publish(ChannelKind::Ticker, &data.channel, ticker);
+                if let Some(funding) = funding {
+                    self.publish(ChannelKind::Ticker, &data.channel, MarketData::FundingRate(funding));
+                }
+                if let Some(settlement) = settlement {
+                    self.publish(ChannelKind::Ticker, &data.channel, MarketData::Settlement(settlement));
+                }
+            }
+            SubscriptionData::Quote(data) => {
+                let converted =
+                    channels::convert_quote_to_market_data(data.data, &self.scales, received_at);
+                if let Some(market_data) = self.checked(&data.channel, converted) {
+                    self.publish(ChannelKind::Quote, &data.channel, market_data);
+                }
+            }
+            SubscriptionData::DeribitPriceIndex(data) => {
+                self.publish(
+                    ChannelKind::PriceIndex,
+                    &data.channel,
+                    channels::convert_price_index_to_market_data(data.data, received_at),
+                );
+            }
+            SubscriptionData::DeribitVolatilityIndex(data) => {
+                self.publish(
+                    ChannelKind::VolatilityIndex,
+                    &data.channel,
+                    channels::convert_volatility_index_to_market_data(data.data, received_at),
+                );
+            }
+            SubscriptionData::MarkPriceOption(data) => {
+                let converted = channels::convert_mark_prices_to_market_data(data.data, received_at);
+                for market_data in converted {
+                    self.publish(ChannelKind::MarkPriceOptions, &data.channel, market_data);
+                }
+            }
+            SubscriptionData::EstimatedExpirationPrice(data) => {
+                self.publish(
+                    ChannelKind::EstimatedExpirationPrice,
+                    &data.channel,
+                    channels::convert_expiration_price_to_market_data(
+                        &data.channel,
+                        data.data,
+                        received_at,
+                    ),
+                );
+            }
+            SubscriptionData::InstrumentState(data) => {
+                self.publish(
+                    ChannelKind::InstrumentState,
+                    &data.channel,
+                    channels::convert_instrument_state_to_market_data(data.data, received_at),
+                );
+            }
+            SubscriptionData::PlatformState(data) => {
+                self.publish(
+                    ChannelKind::PlatformState,
+                    &data.channel,
+                    channels::convert_platform_state_to_market_data(data.data, received_at),
+                );
+            }
+            _ => {
+                debug!("Ignoring unrouted subscription message");
+            }
+        }
+    }
+
+    /// Unwrap a conversion, logging and dropping a message whose values did not convert
+    fn checked<T>(&self, channel: &str, converted: Result<T>) -> Option<T> {
+        match converted {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!(
+                    component = "deribit",
+                    connection = self.id,
+                    channel = %channel,
+                    error = %e,
+                    "Dropping message that failed to convert"
+                );
+                None
+            }
+        }
+    }
+
+    /// Record the funding rate and report whether it changed since the last one published.
+    ///
+    /// Tickers repeat the rate every interval; the first one seen is published so consumers
+    /// learn the current rate after a start.
+    fn is_new_funding(&self, funding: &FundingRate) -> bool {
+        let rate = (funding.current_funding, funding.funding_8h);
+        let mut fundings = self.fundings.lock().unwrap_or_else(|e| e.into_inner());
+        fundings.insert(funding.symbol.clone(), rate) != Some(rate)
+    }
+
+    /// Record the settlement price and report whether it changed.
+    ///
+    /// The first price seen for an instrument only seeds the cache, so restarts and
+    /// reconnects do not republish a settlement that already happened.
+    fn is_new_settlement(&self, settlement: &Settlement) -> bool {
+        let mut settlements = self.settlements.lock().unwrap_or_else(|e| e.into_inner());
+        match settlements.insert(settlement.symbol.clone(), settlement.settlement_price) {
+            Some(previous) => previous != settlement.settlement_price,
+            None => false,
+        }
+    }
+
+    /// Fetch trades missed during the outage for every subscribed trades channel.
+    ///
+    /// Each instrument's window starts at its last trade in `last_trades`, taken before
+    /// reconnecting, or at `last_frame` when none was seen; trades at or below that `trade_seq`
+    /// and ids already emitted are skipped.
+    async fn backfill_trades(&self, last_trades: HashMap<String, (u64, u64)>, last_frame: u64) {
+        let Some(backfill) = &self.backfill else {
+            return;
+        };
+
+        let instruments: Vec<(String, String)> = self
+            .channels
+            .read()
+            .await
+            .iter()
+            .filter(|(_, status)| **status == SubscriptionStatus::Active)
+            .filter_map(|(channel, _)| {
+                ChannelKind::trades_instrument(channel)
+                    .map(|instrument| (channel.clone(), instrument.to_string()))
+            })
+            .collect();
+        let end = now_ms();
+
+        for (channel, instrument) in instruments {
+            let last = last_trades.get(&instrument).copied();
+            let start = last.map_or(last_frame, |(_, timestamp)| timestamp);
+
+            let trades = match backfill.fetch(&instrument, last.map(|(seq, _)| seq), start, end).await {
+                Ok(trades) => trades,
+                Err(e) => {
+                    warn!(
+                        component = "deribit",
+                        connection = self.id,
+                        instrument = %instrument,
+                        error = %e,
+                        "Trade backfill failed"
+                    );
+                    continue;
+                }
+            };
+
+            let missed: Vec<MarketData> = {
+                let mut tracker = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+                trades
+                    .into_iter()
+                    .filter_map(|trade| {
+                        let converted = trade.into_market_data(&mut tracker, &self.scales);
+                        self.checked(&channel, converted).flatten()
+                    })
+                    .collect()
+            };
+            if !missed.is_empty() {
+                info!(
+                    component = "deribit",
+                    connection = self.id,
+                    instrument = %instrument,
+                    trades = missed.len(),
+                    "Backfilled missed trades"
+                );
+            }
+            for market_data in missed {
+                self.publish(ChannelKind::Trades, &channel, market_data);
+            }
+        }
+    }
+
+    /// Wrap in an envelope and hand to the channel family's consumers
+    fn publish(&self, kind: ChannelKind, channel: &str, market_data: MarketData) {
+        let envelope = self.sequencer.seal(self.id, channel, market_data);
+        // Sending only fails when nobody is consuming this channel family
+        if self.senders[&kind].send(envelope).is_err() {
+            debug!("No {} consumers, dropping message", kind.as_str());
+        }
+    }
+
+    /// Re-establish the session.
+    ///
+    /// Retries forever with jittered exponential backoff and returns the new subscription
+    /// client as soon as the socket is open; `restore` resubscribes the channels.
+    async fn reconnect(&self) -> DeribitSubscriptionClient {
+        let mut attempt: u32 = 0;
+
+        loop {
+            let delay = Self::backoff_delay(&self.config, attempt);
+            attempt = attempt.saturating_add(1);
+            warn!(
+                component = "deribit",
+                connection = self.id,
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "Reconnecting to Deribit"
+            );
+            tokio::time::sleep(delay).await;
+
+            let session = match Self::open_session(&self.config).await {
+                Ok(session) => session,
+                Err(e @ MarketDataError::AuthenticationError(_)) => {
+                    error!(
+                        component = "deribit",
+                        connection = self.id,
+                        attempt,
+                        error = %e,
+                        "Re-authentication failed"
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    warn!(
+                        component = "deribit",
+                        connection = self.id,
+                        attempt,
+                        error = %e,
+                        "Reconnect failed"
+                    );
+                    continue;
+                }
+            };
+
+            *self.api_client.write().await = session.api_client;
+            *self.token.write().await = session.token;
+            return session.subscription_client;
+        }
+    }
+
+    /// Exponential backoff capped at `reconnect_max_backoff_ms`, with up to 50% jitter
+    fn backoff_delay(config: &DeribitConfig, attempt: u32) -> Duration {
+        let base = config
+            .reconnect_initial_backoff_ms
+            .saturating_mul(1u64 << attempt.min(16))
+            .min(config.reconnect_max_backoff_ms);
+        let jitter = rand::thread_rng().gen_range(0..=base / 2);
+        Duration::from_millis(base - base / 2 + jitter)
+    }
+}
+
+fn now_ms() -> u64 {
+    Timestamp::now().as_millis() as u64
+}
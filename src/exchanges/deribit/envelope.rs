@@ -0,0 +1,102 @@
+use market_data_types::envelope::SCHEMA_VERSION;
+use market_data_types::{Envelope, MarketData};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Wraps messages for one collector instance, numbering each stream.
+///
+/// Shared by every connection in the pool so a symbol keeps one sequence even if it moves.
+#[derive(Debug)]
+pub struct Sequencer {
+    instance_id: String,
+    next: Mutex<HashMap<(String, String, &'static str), u64>>,
+}
+
+impl Sequencer {
+    pub fn new(instance_id: impl Into<String>) -> Self {
+        Self {
+            instance_id: instance_id.into(),
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Assign the next sequence for the payload's stream and wrap it
+    pub fn seal(&self, connection_id: usize, channel: &str, payload: MarketData) -> Envelope {
+        let key = (
+            payload.venue().to_string(),
+            payload.stream_key().to_string(),
+            payload.data_type(),
+        );
+        let sequence = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let counter = next.entry(key).or_insert(0);
+            *counter += 1;
+            *counter
+        };
+
+        Envelope {
+            schema_version: SCHEMA_VERSION,
+            instance_id: self.instance_id.clone(),
+            connection_id: Some(connection_id),
+            sequence,
+            channel: Some(channel.to_string()),
+            payload,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::index_price;
+    use market_data_types::models::VolatilityIndex;
+    use market_data_types::Timestamp;
+
+    fn volatility_index(index_name: &str) -> MarketData {
+        MarketData::VolatilityIndex(VolatilityIndex {
+            index_name: index_name.to_string(),
+            venue: "deribit".to_string(),
+            volatility: 52.1,
+            timestamp: Timestamp::from_millis(1_700_000_000_000),
+            ingestion_timestamp: Timestamp::from_millis(1_700_000_000_010),
+            publish_timestamp: None,
+        })
+    }
+
+    #[test]
+    fn counts_each_stream_independently() {
+        let sequencer = Sequencer::new("collector-1");
+        let channel = "deribit_price_index.btc_usd";
+        let sequences: Vec<u64> = [
+            index_price("btc_usd"),
+            index_price("btc_usd"),
+            index_price("eth_usd"),
+            // Same symbol, different data type
+            volatility_index("btc_usd"),
+            index_price("btc_usd"),
+            index_price("eth_usd"),
+        ]
+        .into_iter()
+        .map(|payload| sequencer.seal(0, channel, payload).sequence)
+        .collect();
+
+        assert_eq!(sequences, vec![1, 2, 1, 1, 3, 2]);
+    }
+
+    #[test]
+    fn streams_keep_counting_across_connections() {
+        let sequencer = Sequencer::new("collector-1");
+        let first = sequencer.seal(0, "deribit_price_index.btc_usd", index_price("btc_usd"));
+        let second = sequencer.seal(3, "deribit_price_index.btc_usd", index_price("btc_usd"));
+
+        assert_eq!((first.sequence, second.sequence), (1, 2));
+        assert_eq!(second.connection_id, Some(3));
+        assert_eq!(second.instance_id, "collector-1");
+        assert_eq!(second.schema_version, SCHEMA_VERSION);
+        assert_eq!(second.channel.as_deref(), Some("deribit_price_index.btc_usd"));
+    }
+}
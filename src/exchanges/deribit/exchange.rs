@@ -1,234 +1,401 @@
+use super::auth::DeribitCredentials;
+use super::book::BookFeed;
+use super::channels::{ChannelKind, ChannelSettings, ReferenceChannels};
+use super::instruments::{InstrumentResolver, InstrumentScales};
 use super::models::{
-    Exchange, MarketData, OrderBookSnapshot, TradeSnapshot, TickerRow,
+    Exchange, SubscriptionStatus, SymbolCommand, SymbolUpdate, SymbolUpdateReport,
 };
+use super::pool::ConnectionPool;
 use crate::errors::{MarketDataError, Result};
 use async_trait::async_trait;
-use deribit::{
-    models::{
-        subscription::{
-            GroupedBookData, PublicSubscribeRequest, PublicUnsubscribeRequest, SubscriptionData,
-            SubscriptionMessage, SubscriptionParams, TickerData as DeribitTickerData,
-            TradesData as DeribitTradesData, HeartbeatType,
-        },
-        session_management::SetHeartbeatRequest,
-        TestRequest,
-    },
-    DeribitAPIClient, DeribitSubscriptionClient,
-};
-use futures::{stream::BoxStream, StreamExt};
-use std::collections::HashSet;
+use futures::stream::BoxStream;
+use market_data_types::Envelope;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
-use tracing::{debug, info};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tracing::{info, warn};
 
+#[derive(Debug, Clone)]
 pub struct DeribitConfig {
     pub testnet: bool,
+    /// WebSocket endpoint override; defaults to the mainnet or testnet URL
+    pub ws_url: Option<String>,
+    /// API key for `public/auth`; the session stays public when unset
+    pub credentials: Option<DeribitCredentials>,
     pub heartbeat_interval: u64,
+    /// Reconnect when no frame (data or heartbeat) arrives for this many seconds
+    pub silence_timeout_secs: u64,
+    /// First reconnect delay, doubled after every failed attempt
+    pub reconnect_initial_backoff_ms: u64,
+    /// Upper bound for the reconnect delay
+    pub reconnect_max_backoff_ms: u64,
+    /// Grouped snapshots or the incremental feed with a local book
+    pub book_feed: BookFeed,
+    /// Interval, grouping and depth used to build channel names
+    pub channels: ChannelSettings,
+    /// Index, mark price, instrument state and platform channels
+    pub reference_channels: ReferenceChannels,
+    /// Messages buffered per channel family before slow consumers start losing data
+    pub stream_buffer: usize,
+    /// Fetch trades missed during a disconnect over REST after reconnecting
+    pub trade_backfill: bool,
+    /// Channels pinned to one connection before another is opened
+    pub max_channels_per_connection: usize,
+    /// Upper bound on concurrent connections
+    pub max_connections: usize,
+    /// Maximum channels per subscribe request
+    pub subscribe_chunk_size: usize,
+    /// Minimum delay between subscribe/unsubscribe requests
+    pub subscribe_interval_ms: u64,
+    /// Collector instance written into every message envelope
+    pub instance_id: String,
 }
 
 impl Default for DeribitConfig {
     fn default() -> Self {
         Self {
             testnet: false,
+            ws_url: None,
+            credentials: None,
             heartbeat_interval: 10,
+            silence_timeout_secs: 30,
+            reconnect_initial_backoff_ms: 500,
+            reconnect_max_backoff_ms: 30_000,
+            book_feed: BookFeed::default(),
+            channels: ChannelSettings::default(),
+            reference_channels: ReferenceChannels::default(),
+            stream_buffer: 10_000,
+            trade_backfill: true,
+            max_channels_per_connection: 500,
+            max_connections: 4,
+            subscribe_chunk_size: 100,
+            subscribe_interval_ms: 100,
+            instance_id: "market-data".to_string(),
+        }
+    }
+}
+
+impl DeribitConfig {
+    /// REST base URL on the same host as the WebSocket endpoint
+    pub fn rest_url(&self) -> String {
+        match &self.ws_url {
+            Some(url) => url
+                .replacen("wss://", "https://", 1)
+                .replacen("ws://", "http://", 1)
+                .replacen("/ws/api/", "/api/", 1),
+            None if self.testnet => "https://test.deribit.com/api/v2".to_string(),
+            None => "https://www.deribit.com/api/v2".to_string(),
         }
     }
 }
 
+/// Where a symbol update comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SymbolSource {
+    /// The symbol channel or `Exchange::subscribe`/`unsubscribe`
+    Manual,
+    /// An instrument refresh of the configured selectors
+    Resolved,
+}
+
+/// Subscribed symbols and the channel families streaming them, shared with background tasks
+struct Subscriptions {
+    pool: Arc<ConnectionPool>,
+    symbols: RwLock<HashSet<String>>,
+    active_kinds: RwLock<HashSet<ChannelKind>>,
+    /// Symbols added manually, which instrument refreshes never remove. Locked for the whole
+    /// of every update so symbol channel commands, refreshes and `subscribe` calls apply one
+    /// at a time.
+    manual_symbols: Mutex<HashSet<String>>,
+}
+
+impl Subscriptions {
+    /// Diff a symbol update against the current set and apply it on the wire.
+    ///
+    /// Channels are only (un)subscribed for channel families that are already streaming;
+    /// the rest pick up the new symbol set when they connect. If subscribing fails, the new
+    /// channels are released again and the symbol set is left unchanged.
+    async fn apply(&self, update: SymbolUpdate, source: SymbolSource) -> Result<SymbolUpdateReport> {
+        let mut manual = self.manual_symbols.lock().await;
+        let current = self.symbols.read().await.clone();
+
+        let (added, removed): (HashSet<String>, HashSet<String>) = match &update {
+            SymbolUpdate::Add(symbols) => (
+                symbols.iter().filter(|s| !current.contains(*s)).cloned().collect(),
+                HashSet::new(),
+            ),
+            SymbolUpdate::Remove(symbols) => (
+                HashSet::new(),
+                symbols
+                    .iter()
+                    .filter(|s| current.contains(*s))
+                    .filter(|s| source == SymbolSource::Manual || !manual.contains(*s))
+                    .cloned()
+                    .collect(),
+            ),
+            SymbolUpdate::Replace(symbols) => {
+                let target: HashSet<String> = symbols.iter().cloned().collect();
+                (
+                    target.difference(&current).cloned().collect(),
+                    current.difference(&target).cloned().collect(),
+                )
+            }
+        };
+
+        let kinds: Vec<ChannelKind> = self.active_kinds.read().await.iter().copied().collect();
+
+        if !added.is_empty() {
+            let channels: Vec<String> =
+                kinds.iter().flat_map(|k| self.pool.channels(*k, &added)).collect();
+            if let Err(e) = self.pool.subscribe(channels.clone()).await {
+                // Some connections may have subscribed; drop those and every new assignment
+                if let Err(rollback) = self.pool.unsubscribe(channels).await {
+                    warn!(component = "deribit", error = %rollback, "Failed to roll back subscribe");
+                }
+                return Err(e);
+            }
+            self.symbols.write().await.extend(added.iter().cloned());
+        }
+
+        if source == SymbolSource::Manual {
+            match update {
+                SymbolUpdate::Add(symbols) => manual.extend(symbols),
+                SymbolUpdate::Remove(symbols) => {
+                    for symbol in &symbols {
+                        manual.remove(symbol);
+                    }
+                }
+                SymbolUpdate::Replace(symbols) => *manual = symbols.into_iter().collect(),
+            }
+        }
+
+        if !removed.is_empty() {
+            {
+                let mut symbols = self.symbols.write().await;
+                for symbol in &removed {
+                    symbols.remove(symbol);
+                }
+            }
+            let channels: Vec<String> =
+                kinds.iter().flat_map(|k| self.pool.channels(*k, &removed)).collect();
+            self.pool.unsubscribe(channels).await?;
+        }
+
+        Ok(SymbolUpdateReport {
+            added: added.into_iter().collect(),
+            removed: removed.into_iter().collect(),
+        })
+    }
+}
+
+/// Deribit adapter: a pool of connections, any number of concurrent channel-family streams
 pub struct Deribit {
-    api_client: Arc<RwLock<DeribitAPIClient>>,
-    subscription_client: Arc<RwLock<DeribitSubscriptionClient>>,
-    subscribed_symbols: Arc<RwLock<HashSet<String>>>,
-    symbol_receiver: Arc<RwLock<Option<mpsc::Receiver<Vec<String>>>>>,
+    config: DeribitConfig,
+    pool: Arc<ConnectionPool>,
+    scales: InstrumentScales,
+    subscriptions: Arc<Subscriptions>,
 }
 
 impl Deribit {
     pub async fn new(
         config: DeribitConfig,
-        symbol_rx: mpsc::Receiver<Vec<String>>,
+        symbol_rx: mpsc::Receiver<SymbolCommand>,
     ) -> Result<Self> {
-        info!("Connecting to Deribit WebSocket...");
-
-        // Build the Deribit client
-        let drb = if config.testnet {
-            deribit::Deribit::builder()
-                .testnet(true)
-                .build()
-                .map_err(|e| MarketDataError::ConnectionError(e.to_string()))?
-        } else {
-            deribit::Deribit::new()
+        let scales = InstrumentScales::default();
+        let pool = Arc::new(ConnectionPool::connect(config.clone(), scales.clone()).await?);
+
+        let deribit = Self {
+            config,
+            subscriptions: Arc::new(Subscriptions {
+                pool: pool.clone(),
+                symbols: RwLock::new(HashSet::new()),
+                active_kinds: RwLock::new(HashSet::new()),
+                manual_symbols: Mutex::new(HashSet::new()),
+            }),
+            pool,
+            scales,
         };
 
-        let (mut api_client, subscription_client) = drb
-            .connect()
-            .await
-            .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?;
-
-        // Set heartbeat
-        info!("Setting heartbeat interval to {}s", config.heartbeat_interval);
-        let _ = api_client
-            .call(SetHeartbeatRequest::with_interval(config.heartbeat_interval))
-            .await
-            .map_err(|e| MarketDataError::ConnectionError(e.to_string()))?
-            .await;
-
-        Ok(Self {
-            api_client: Arc::new(RwLock::new(api_client)),
-            subscription_client: Arc::new(RwLock::new(subscription_client)),
-            subscribed_symbols: Arc::new(RwLock::new(HashSet::new())),
-            symbol_receiver: Arc::new(RwLock::new(Some(symbol_rx))),
-        })
+        deribit.start_dynamic_subscription_handler(symbol_rx);
+
+        Ok(deribit)
     }
 
-    async fn subscribe_channels(&self, channels: Vec<String>) -> Result<()> {
-        let mut api_client = self.api_client.write().await;
+    /// Tick-size scales shared with the connections; hand to the `InstrumentResolver`
+    pub fn instrument_scales(&self) -> InstrumentScales {
+        self.scales.clone()
+    }
 
-        debug!("Subscribing to channels: {:?}", channels);
-        let req = PublicSubscribeRequest::new(&channels);
+    fn start_dynamic_subscription_handler(&self, mut receiver: mpsc::Receiver<SymbolCommand>) {
+        let subscriptions = self.subscriptions.clone();
 
-        api_client
-            .call(req)
-            .await
-            .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?
-            .await
-            .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?;
+        tokio::spawn(async move {
+            while let Some(command) = receiver.recv().await {
+                info!("Received dynamic symbol update: {:?}", command.update);
+
+                let result = subscriptions.apply(command.update, SymbolSource::Manual).await;
+
+                match &result {
+                    Ok(report) => info!(
+                        component = "deribit",
+                        added = ?report.added,
+                        removed = ?report.removed,
+                        "Applied symbol update"
+                    ),
+                    Err(e) => warn!(component = "deribit", error = %e, "Symbol update failed"),
+                }
 
-        Ok(())
+                if let Some(reply) = command.reply {
+                    // The sender may have stopped waiting for the result
+                    let _ = reply.send(result);
+                }
+            }
+        });
     }
 
-    async fn unsubscribe_channels(&self, channels: Vec<String>) -> Result<()> {
-        let mut api_client = self.api_client.write().await;
+    /// Resolve the configured symbol selectors, subscribe to the result and keep it current.
+    ///
+    /// Selectors that are not exact names are re-resolved every `refresh_interval`: newly
+    /// listed instruments are subscribed and expired or delisted ones are dropped. Symbols
+    /// added through the symbol channel or `subscribe` are never dropped by a refresh.
+    pub async fn track_instruments(
+        &mut self,
+        resolver: InstrumentResolver,
+        refresh_interval: Duration,
+    ) -> Result<()> {
+        let initial = resolver.resolve().await?;
+        info!(
+            component = "deribit",
+            instruments = initial.len(),
+            "Resolved configured symbols"
+        );
+        self.subscriptions
+            .apply(SymbolUpdate::Add(initial.iter().cloned().collect()), SymbolSource::Resolved)
+            .await?;
+
+        if resolver.is_static() {
+            return Ok(());
+        }
+
+        let subscriptions = self.subscriptions.clone();
+
+        tokio::spawn(async move {
+            let mut tracked = initial;
+            let mut interval = tokio::time::interval(refresh_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval.tick().await; // First tick completes immediately
+
+            loop {
+                interval.tick().await;
+
+                let current = match resolver.resolve().await {
+                    Ok(current) => current,
+                    Err(e) => {
+                        warn!(component = "deribit", error = %e, "Instrument refresh failed");
+                        continue;
+                    }
+                };
 
-        debug!("Unsubscribing from channels: {:?}", channels);
-        let req = PublicUnsubscribeRequest::new(&channels);
+                let listed: Vec<String> = current.difference(&tracked).cloned().collect();
+                let expired: Vec<String> = tracked.difference(&current).cloned().collect();
+                if listed.is_empty() && expired.is_empty() {
+                    continue;
+                }
 
-        api_client
-            .call(req)
-            .await
-            .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?
-            .await
-            .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?;
+                info!(
+                    component = "deribit",
+                    listed = ?listed,
+                    expired = ?expired,
+                    "Instrument set changed"
+                );
+
+                // Only what was applied counts as tracked, so the next refresh retries the rest
+                let added = subscriptions
+                    .apply(SymbolUpdate::Add(listed.clone()), SymbolSource::Resolved)
+                    .await;
+                match added {
+                    Ok(_) => tracked.extend(listed),
+                    Err(e) => {
+                        warn!(component = "deribit", error = %e, "Failed to subscribe listed instruments")
+                    }
+                }
+                let removed = subscriptions
+                    .apply(SymbolUpdate::Remove(expired.clone()), SymbolSource::Resolved)
+                    .await;
+                match removed {
+                    Ok(_) => {
+                        for symbol in &expired {
+                            tracked.remove(symbol);
+                        }
+                    }
+                    Err(e) => {
+                        warn!(component = "deribit", error = %e, "Failed to drop expired instruments")
+                    }
+                }
+            }
+        });
 
         Ok(())
     }
 
-    fn convert_grouped_book_to_market_data(data: GroupedBookData) -> MarketData {
-        use time::OffsetDateTime;
-
-        let timestamp = OffsetDateTime::from_unix_timestamp_nanos((data.timestamp as i128) * 1_000_000)
-            .unwrap_or_else(|_| OffsetDateTime::now_utc());
-        let ingestion_timestamp = OffsetDateTime::now_utc();
-
-        MarketData::Orderbook(OrderBookSnapshot {
-            symbol: data.instrument_name.clone(),
-            venue: "deribit".to_string(),
-            bids: data.bids,
-            asks: data.asks,
-            seq_id: data.change_id as u64,
-            instrument_class: None, // Not provided in Deribit book data
-            timestamp,
-            ingestion_timestamp,
-        })
-    }
+    /// Subscribe to one channel family and stream it from the connection pool
+    async fn connect_channel(
+        &mut self,
+        kind: ChannelKind,
+    ) -> Result<BoxStream<'static, Result<Envelope>>> {
+        // A symbol update must not slip in between reading the symbols and marking the kind active
+        let _guard = self.subscriptions.manual_symbols.lock().await;
+        let symbols = self.subscriptions.symbols.read().await.clone();
+        let channels = self.pool.channels(kind, &symbols);
 
-    fn convert_trades_to_market_data(trades: Vec<DeribitTradesData>) -> Vec<MarketData> {
-        use time::OffsetDateTime;
-
-        trades
-            .into_iter()
-            .map(|trade| {
-                let timestamp = OffsetDateTime::from_unix_timestamp_nanos((trade.timestamp as i128) * 1_000_000)
-                    .unwrap_or_else(|_| OffsetDateTime::now_utc());
-                let ingestion_timestamp = OffsetDateTime::now_utc();
-
-                MarketData::Trade(TradeSnapshot {
-                    symbol: trade.instrument_name.clone(),
-                    venue: "deribit".to_string(),
-                    trade_id: trade.trade_id,
-                    price: trade.price,
-                    amount: trade.amount,
-                    side: format!("{:?}", trade.direction).to_lowercase(),
-                    seq_id: Some(trade.trade_seq),
-                    instrument_class: None,
-                    timestamp,
-                    ingestion_timestamp,
-                    contracts: Some(trade.amount), // Use amount as contracts
-                    index_price: Some(trade.index_price),
-                    mark_price: None, // Not provided in trades subscription
-                    tick_direction: Some(trade.tick_direction as i32),
-                })
-            })
-            .collect()
-    }
+        if channels.is_empty() {
+            return Err(MarketDataError::ConfigError(format!(
+                "No symbols subscribed for {}",
+                kind.as_str()
+            )));
+        }
 
-    fn convert_ticker_to_market_data(ticker: DeribitTickerData) -> MarketData {
-        use time::OffsetDateTime;
+        // Take the receiver first so snapshots sent right after subscribing are not missed
+        let receiver = self.pool.receiver(kind);
 
-        // Convert state to u8: open=1, closed=0
-        let state = match format!("{:?}", ticker.state).to_lowercase().as_str() {
-            "open" => 1,
-            _ => 0,
-        };
+        info!("Subscribing to {} channels: {:?}", kind.as_str(), channels);
+        self.pool.subscribe(channels).await?;
+        self.subscriptions.active_kinds.write().await.insert(kind);
 
-        let ingestion_timestamp = OffsetDateTime::now_utc().unix_timestamp();
-
-        MarketData::Ticker(TickerRow {
-            timestamp: ticker.timestamp as i64,
-            ingestion_timestamp,
-            venue: "deribit".to_string(),
-            state,
-            symbol: ticker.instrument_name.clone(),
-            index_price: Some(ticker.index_price),
-            settlement_price: ticker.settlement_price,
-            open_interest: Some(ticker.open_interest),
-            mark_price: Some(ticker.mark_price),
-            best_bid_price: ticker.best_bid_price,
-            mark_iv: ticker.mark_iv,
-            ask_iv: ticker.ask_iv,
-            bid_iv: ticker.bid_iv,
-            underlying_price: ticker.underlying_price,
-            underlying_index: ticker.underlying_index.clone(),
-            best_ask_price: ticker.best_ask_price,
-            interest_rate: ticker.interest_rate,
-            estimated_delivery_price: Some(ticker.estimated_delivery_price),
-            best_ask_amount: Some(ticker.best_ask_amount),
-            best_bid_amount: Some(ticker.best_bid_amount),
-            current_funding: ticker.current_funding,
-            delivery_price: ticker.delivery_price,
-            funding_8h: ticker.funding_8h,
-            interest_value: None, // Not available in subscription data
-            greeks_delta: ticker.greeks.as_ref().map(|g| g.delta),
-            greeks_gamma: ticker.greeks.as_ref().map(|g| g.gamma),
-            greeks_vega: ticker.greeks.as_ref().map(|g| g.vega),
-            greeks_theta: ticker.greeks.as_ref().map(|g| g.theta),
-            greeks_rho: ticker.greeks.as_ref().map(|g| g.rho),
-        })
+        Ok(Self::receiver_stream(kind, receiver))
     }
 
-    async fn start_dynamic_subscription_handler(&self) {
-        let symbols = self.subscribed_symbols.clone();
-        let _api_client = self.api_client.clone();
-        let symbol_receiver = self.symbol_receiver.clone();
-
-        tokio::spawn(async move {
-            let rx = {
-                let mut guard = symbol_receiver.write().await;
-                guard.take()
-            };
-
-            if let Some(mut receiver) = rx {
-                while let Some(new_symbols) = receiver.recv().await {
-                    info!("Received dynamic symbol update: {:?}", new_symbols);
-
-                    // This is where you'd implement logic to subscribe to new symbols
-                    // For now, just update the symbols list
-                    let mut symbols_guard = symbols.write().await;
-                    for symbol in new_symbols {
-                        symbols_guard.insert(symbol);
+    /// Adapt a channel-family receiver into the stream handed to collectors
+    fn receiver_stream(
+        kind: ChannelKind,
+        mut receiver: broadcast::Receiver<Envelope>,
+    ) -> BoxStream<'static, Result<Envelope>> {
+        let stream = async_stream::stream! {
+            loop {
+                match receiver.recv().await {
+                    Ok(envelope) => {
+                        yield Ok(envelope);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(
+                            component = "deribit",
+                            channel = kind.as_str(),
+                            skipped,
+                            "Consumer fell behind the connection"
+                        );
+                        yield Err(MarketDataError::StreamLagged(format!(
+                            "{} consumer skipped {} messages",
+                            kind.as_str(),
+                            skipped
+                        )));
                     }
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
-        });
+        };
+
+        Box::pin(stream)
     }
 
     /// Unsubscribe from all channels with timeout for graceful shutdown
@@ -237,32 +404,49 @@ impl Deribit {
         timeout: std::time::Duration,
         channel_type: &str,
     ) -> Result<()> {
-        use tokio::time::timeout as tokio_timeout;
-
         info!(
             "Unsubscribing from all {} channels (timeout: {:?})",
             channel_type, timeout
         );
 
-        let symbols = self.subscribed_symbols.read().await.clone();
-        if symbols.is_empty() {
-            info!("No symbols to unsubscribe from");
-            return Ok(());
-        }
-
         // Build channels based on type
-        let channels: Vec<String> = match channel_type {
-            "orderbook" => symbols
-                .iter()
-                .map(|s| format!("book.{}.none.10.100ms", s))
-                .collect(),
-            "trades" => symbols.iter().map(|s| format!("trades.{}.100ms", s)).collect(),
-            "ticker" => symbols.iter().map(|s| format!("ticker.{}.100ms", s)).collect(),
+        let kind = match channel_type {
+            "orderbook" => ChannelKind::orderbook(&self.config),
+            "trades" => ChannelKind::Trades,
+            "ticker" => ChannelKind::Ticker,
+            "quotes" => ChannelKind::Quote,
+            "reference" => {
+                let channels = self.config.reference_channels.channels();
+                return self.unsubscribe_with_timeout(timeout, channel_type, channels).await;
+            }
             _ => return Ok(()),
         };
 
+        // Once the kind is inactive, later symbol updates leave its channels alone
+        let symbols = {
+            let _guard = self.subscriptions.manual_symbols.lock().await;
+            self.subscriptions.active_kinds.write().await.remove(&kind);
+            self.subscriptions.symbols.read().await.clone()
+        };
+        let channels = self.pool.channels(kind, &symbols);
+        self.unsubscribe_with_timeout(timeout, channel_type, channels).await
+    }
+
+    async fn unsubscribe_with_timeout(
+        &self,
+        timeout: std::time::Duration,
+        channel_type: &str,
+        channels: Vec<String>,
+    ) -> Result<()> {
+        use tokio::time::timeout as tokio_timeout;
+
+        if channels.is_empty() {
+            info!("No {} channels to unsubscribe from", channel_type);
+            return Ok(());
+        }
+
         // Unsubscribe with timeout
-        match tokio_timeout(timeout, self.unsubscribe_channels(channels)).await {
+        match tokio_timeout(timeout, self.pool.unsubscribe(channels)).await {
             Ok(result) => {
                 info!("Successfully unsubscribed from {} channels", channel_type);
                 result
@@ -285,197 +469,56 @@ impl Exchange for Deribit {
     }
 
     async fn subscribe(&mut self, symbols: &[String]) -> Result<()> {
-        // Store symbols for later use
-        let mut subscribed = self.subscribed_symbols.write().await;
-        for symbol in symbols {
-            subscribed.insert(symbol.clone());
-        }
+        self.subscriptions
+            .apply(SymbolUpdate::Add(symbols.to_vec()), SymbolSource::Manual)
+            .await?;
         Ok(())
     }
 
     async fn unsubscribe(&mut self, symbols: &[String]) -> Result<()> {
-        let mut subscribed = self.subscribed_symbols.write().await;
-        for symbol in symbols {
-            subscribed.remove(symbol);
-        }
+        self.subscriptions
+            .apply(SymbolUpdate::Remove(symbols.to_vec()), SymbolSource::Manual)
+            .await?;
         Ok(())
     }
 
-    async fn connect_orderbook(&mut self) -> Result<BoxStream<'static, Result<MarketData>>> {
-        let symbols = self.subscribed_symbols.read().await.clone();
-
-        // Build channel strings: book.{instrument}.none.10.100ms (grouped book with 10 levels, 100ms interval)
-        let channels: Vec<String> = symbols
-            .iter()
-            .map(|symbol| format!("book.{}.none.10.100ms", symbol))
-            .collect();
-
-        if channels.is_empty() {
-            return Err(MarketDataError::ConfigError(
-                "No symbols subscribed for orderbook".to_string(),
-            ));
-        }
-
-        info!("Subscribing to orderbook channels: {:?}", channels);
-        self.subscribe_channels(channels).await?;
-
-        // Start dynamic subscription handler
-        self.start_dynamic_subscription_handler().await;
-
-        let subscription_client = self.subscription_client.clone();
-        let api_client = self.api_client.clone();
-
-        let stream = async_stream::stream! {
-            let mut sub_client = subscription_client.write().await;
-
-            while let Some(message_result) = sub_client.next().await {
-                match message_result {
-                    Ok(SubscriptionMessage {
-                        params: SubscriptionParams::Heartbeat { r#type: HeartbeatType::TestRequest },
-                        ..
-                    }) => {
-                        // Respond to heartbeat test request
-                        debug!("Received heartbeat test request, responding...");
-                        let mut api = api_client.write().await;
-                        if let Err(e) = api.call(TestRequest::default()).await {
-                            debug!("Failed to respond to heartbeat: {:?}", e);
-                        }
-                    }
-                    Ok(SubscriptionMessage {
-                        params: SubscriptionParams::Subscription(SubscriptionData::GroupedBook(data)),
-                        ..
-                    }) => {
-                        let market_data = Self::convert_grouped_book_to_market_data(data.data);
-                        yield Ok(market_data);
-                    }
-                    Err(e) => {
-                        yield Err(MarketDataError::WebSocketError(e.to_string()));
-                    }
-                    _ => {
-                        // Ignore other message types (heartbeats, other subscriptions)
-                        debug!("Ignoring non-orderbook message");
-                    }
-                }
-            }
-        };
+    async fn connect_orderbook(&mut self) -> Result<BoxStream<'static, Result<Envelope>>> {
+        let kind = ChannelKind::orderbook(&self.config);
+        self.connect_channel(kind).await
+    }
 
-        Ok(Box::pin(stream))
+    async fn connect_trades(&mut self) -> Result<BoxStream<'static, Result<Envelope>>> {
+        self.connect_channel(ChannelKind::Trades).await
     }
 
-    async fn connect_trades(&mut self) -> Result<BoxStream<'static, Result<MarketData>>> {
-        let symbols = self.subscribed_symbols.read().await.clone();
+    async fn connect_ticker(&mut self) -> Result<BoxStream<'static, Result<Envelope>>> {
+        self.connect_channel(ChannelKind::Ticker).await
+    }
 
-        // Build channel strings: trades.{instrument}.100ms
-        let channels: Vec<String> = symbols
-            .iter()
-            .map(|symbol| format!("trades.{}.100ms", symbol))
-            .collect();
+    async fn connect_quotes(&mut self) -> Result<BoxStream<'static, Result<Envelope>>> {
+        self.connect_channel(ChannelKind::Quote).await
+    }
 
-        if channels.is_empty() {
+    async fn connect_reference_data(&mut self) -> Result<BoxStream<'static, Result<Envelope>>> {
+        if self.config.reference_channels.is_empty() {
             return Err(MarketDataError::ConfigError(
-                "No symbols subscribed for trades".to_string(),
+                "No reference channels configured".to_string(),
             ));
         }
 
-        info!("Subscribing to trade channels: {:?}", channels);
-        self.subscribe_channels(channels).await?;
-
-        let subscription_client = self.subscription_client.clone();
-        let api_client = self.api_client.clone();
-
-        let stream = async_stream::stream! {
-            let mut sub_client = subscription_client.write().await;
-
-            while let Some(message_result) = sub_client.next().await {
-                match message_result {
-                    Ok(SubscriptionMessage {
-                        params: SubscriptionParams::Heartbeat { r#type: HeartbeatType::TestRequest },
-                        ..
-                    }) => {
-                        // Respond to heartbeat test request
-                        debug!("Received heartbeat test request, responding...");
-                        let mut api = api_client.write().await;
-                        if let Err(e) = api.call(TestRequest::default()).await {
-                            debug!("Failed to respond to heartbeat: {:?}", e);
-                        }
-                    }
-                    Ok(SubscriptionMessage {
-                        params: SubscriptionParams::Subscription(SubscriptionData::Trades(data)),
-                        ..
-                    }) => {
-                        for market_data in Self::convert_trades_to_market_data(data.data) {
-                            yield Ok(market_data);
-                        }
-                    }
-                    Err(e) => {
-                        yield Err(MarketDataError::WebSocketError(e.to_string()));
-                    }
-                    _ => {
-                        // Ignore other message types
-                        debug!("Ignoring non-trade message");
-                    }
-                }
-            }
-        };
-
-        Ok(Box::pin(stream))
-    }
-
-    async fn connect_ticker(&mut self) -> Result<BoxStream<'static, Result<MarketData>>> {
-        let symbols = self.subscribed_symbols.read().await.clone();
-
-        // Build channel strings: ticker.{instrument}.100ms
-        let channels: Vec<String> = symbols
+        let streams: Vec<_> = ChannelKind::REFERENCE
             .iter()
-            .map(|symbol| format!("ticker.{}.100ms", symbol))
+            .map(|kind| Self::receiver_stream(*kind, self.pool.receiver(*kind)))
             .collect();
 
-        if channels.is_empty() {
-            return Err(MarketDataError::ConfigError(
-                "No symbols subscribed for ticker".to_string(),
-            ));
-        }
-
-        info!("Subscribing to ticker channels: {:?}", channels);
-        self.subscribe_channels(channels).await?;
+        let names = self.config.reference_channels.channels();
+        info!("Subscribing to reference channels: {:?}", names);
+        self.pool.subscribe(names).await?;
 
-        let subscription_client = self.subscription_client.clone();
-        let api_client = self.api_client.clone();
-
-        let stream = async_stream::stream! {
-            let mut sub_client = subscription_client.write().await;
-
-            while let Some(message_result) = sub_client.next().await {
-                match message_result {
-                    Ok(SubscriptionMessage {
-                        params: SubscriptionParams::Heartbeat { r#type: HeartbeatType::TestRequest },
-                        ..
-                    }) => {
-                        // Respond to heartbeat test request
-                        debug!("Received heartbeat test request, responding...");
-                        let mut api = api_client.write().await;
-                        if let Err(e) = api.call(TestRequest::default()).await {
-                            debug!("Failed to respond to heartbeat: {:?}", e);
-                        }
-                    }
-                    Ok(SubscriptionMessage {
-                        params: SubscriptionParams::Subscription(SubscriptionData::Ticker(data)),
-                        ..
-                    }) => {
-                        let market_data = Self::convert_ticker_to_market_data(data.data);
-                        yield Ok(market_data);
-                    }
-                    Err(e) => {
-                        yield Err(MarketDataError::WebSocketError(e.to_string()));
-                    }
-                    _ => {
-                        // Ignore other message types
-                        debug!("Ignoring non-ticker message");
-                    }
-                }
-            }
-        };
+        Ok(Box::pin(futures::stream::select_all(streams)))
+    }
 
-        Ok(Box::pin(stream))
+    async fn subscription_status(&self) -> HashMap<String, SubscriptionStatus> {
+        self.pool.subscription_status().await
     }
 }
@@ -0,0 +1,520 @@
+use market_data_types::decimal::{scale_of, Price, Quantity};
+use crate::errors::{MarketDataError, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use time::OffsetDateTime;
+use tracing::{debug, warn};
+
+/// Instrument as returned by `public/get_instruments` (only the fields we filter on)
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstrumentInfo {
+    pub instrument_name: String,
+    pub kind: String,
+    pub base_currency: String,
+    pub expiration_timestamp: i64,
+    #[serde(default)]
+    pub settlement_period: Option<String>,
+    #[serde(default)]
+    pub option_type: Option<String>,
+    pub is_active: bool,
+    pub tick_size: f64,
+    #[serde(default)]
+    pub min_trade_amount: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetInstrumentsResponse {
+    result: Vec<InstrumentInfo>,
+}
+
+/// Instrument kind filter accepted in selectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstrumentKind {
+    Future,
+    Perpetual,
+    Option,
+    Spot,
+    FutureCombo,
+    OptionCombo,
+}
+
+impl InstrumentKind {
+    fn parse(word: &str) -> Option<Self> {
+        match word.to_lowercase().trim_end_matches('s') {
+            "future" => Some(InstrumentKind::Future),
+            "perpetual" => Some(InstrumentKind::Perpetual),
+            "option" => Some(InstrumentKind::Option),
+            "spot" => Some(InstrumentKind::Spot),
+            "future_combo" => Some(InstrumentKind::FutureCombo),
+            "option_combo" => Some(InstrumentKind::OptionCombo),
+            _ => None,
+        }
+    }
+
+    /// `kind` parameter for `public/get_instruments`
+    fn api_kind(&self) -> &'static str {
+        match self {
+            InstrumentKind::Future | InstrumentKind::Perpetual => "future",
+            InstrumentKind::Option => "option",
+            InstrumentKind::Spot => "spot",
+            InstrumentKind::FutureCombo => "future_combo",
+            InstrumentKind::OptionCombo => "option_combo",
+        }
+    }
+
+    fn matches(&self, instrument: &InstrumentInfo) -> bool {
+        let perpetual = instrument.settlement_period.as_deref() == Some("perpetual");
+        instrument.kind == self.api_kind()
+            && match self {
+                InstrumentKind::Perpetual => perpetual,
+                InstrumentKind::Future => !perpetual,
+                _ => true,
+            }
+    }
+}
+
+/// One entry of `ExchangeConfig.symbols`.
+///
+/// Supported forms:
+/// - exact instrument name: `BTC-PERPETUAL`
+/// - glob with `*` wildcards: `BTC-*-C`
+/// - currency and kind: `ETH futures`, `BTC options`, optionally led by `all`
+/// - with an expiry window: `all BTC options expiring within 30 days` (or `within 30d`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolSelector {
+    Exact(String),
+    Glob(String),
+    Kind {
+        currency: String,
+        kind: InstrumentKind,
+        within_days: Option<u32>,
+    },
+}
+
+impl SymbolSelector {
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let mut words: Vec<&str> = input.split_whitespace().collect();
+        if words.len() > 2 && words[0].eq_ignore_ascii_case("all") {
+            words.remove(0);
+        }
+
+        match words.as_slice() {
+            [] => Err(MarketDataError::ConfigError("Empty symbol selector".to_string())),
+            [single] if single.contains('*') => Ok(SymbolSelector::Glob(single.to_string())),
+            [single] => Ok(SymbolSelector::Exact(single.to_string())),
+            [currency, kind, rest @ ..] => {
+                let kind = InstrumentKind::parse(kind).ok_or_else(|| {
+                    MarketDataError::ConfigError(format!(
+                        "Unknown instrument kind in selector '{}'",
+                        input
+                    ))
+                })?;
+                Ok(SymbolSelector::Kind {
+                    currency: currency.to_uppercase(),
+                    kind,
+                    within_days: Self::parse_expiry_window(input, rest)?,
+                })
+            }
+        }
+    }
+
+    /// Parse `[expiring] within <N> days` / `within <N>d`; no words means no window
+    fn parse_expiry_window(input: &str, words: &[&str]) -> Result<Option<u32>> {
+        let invalid = || {
+            MarketDataError::ConfigError(format!("Invalid expiry window in selector '{}'", input))
+        };
+
+        let words: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+        let mut rest = words.as_slice();
+        if rest.is_empty() {
+            return Ok(None);
+        }
+        let mut introduced = false;
+        for keyword in ["expiring", "within"] {
+            if let [first, tail @ ..] = rest {
+                if first == keyword {
+                    introduced = true;
+                    rest = tail;
+                }
+            }
+        }
+
+        let days = match rest {
+            [days] => days.strip_suffix('d').unwrap_or(days),
+            [days, unit] if unit == "days" || unit == "day" => days.as_str(),
+            _ => return Err(invalid()),
+        };
+        if !introduced {
+            return Err(invalid());
+        }
+        days.parse::<u32>().map(Some).map_err(|_| invalid())
+    }
+
+    pub fn is_exact(&self) -> bool {
+        matches!(self, SymbolSelector::Exact(_))
+    }
+
+    /// Currency and kind to fetch for this selector
+    fn query(&self) -> (String, Option<&'static str>) {
+        match self {
+            SymbolSelector::Exact(name) | SymbolSelector::Glob(name) => {
+                // Use the leading currency segment when it is not itself a wildcard
+                let currency = name
+                    .split('-')
+                    .next()
+                    .filter(|c| !c.contains('*') && !c.is_empty())
+                    .map(|c| c.split('_').next().unwrap_or(c).to_uppercase())
+                    .unwrap_or_else(|| "any".to_string());
+                (currency, None)
+            }
+            SymbolSelector::Kind { currency, kind, .. } => (currency.clone(), Some(kind.api_kind())),
+        }
+    }
+
+    fn matches(&self, instrument: &InstrumentInfo, now_ms: i64) -> bool {
+        match self {
+            SymbolSelector::Exact(name) => instrument.instrument_name == *name,
+            SymbolSelector::Glob(pattern) => glob_match(pattern, &instrument.instrument_name),
+            SymbolSelector::Kind {
+                currency,
+                kind,
+                within_days,
+            } => {
+                instrument.base_currency.eq_ignore_ascii_case(currency)
+                    && kind.matches(instrument)
+                    && within_days.is_none_or(|days| {
+                        instrument.expiration_timestamp <= now_ms + i64::from(days) * 86_400_000
+                    })
+            }
+        }
+    }
+}
+
+/// Match `*` wildcards against a whole instrument name
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    if !name.starts_with(first) || name.len() < first.len() + last.len() || !name.ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Decimal places of an instrument's prices and amounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstrumentScale {
+    pub price: u8,
+    /// None when the instrument lists no minimum trade amount; amounts then stay exact
+    pub quantity: Option<u8>,
+}
+
+/// Tick-size scales per instrument, filled from `public/get_instruments`.
+///
+/// Shared by the resolver that learns them and the connections that convert prices;
+/// instruments not seen yet keep every decimal the exchange sent.
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentScales(Arc<RwLock<HashMap<String, InstrumentScale>>>);
+
+impl InstrumentScales {
+    pub fn get(&self, instrument: &str) -> Option<InstrumentScale> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).get(instrument).copied()
+    }
+
+    fn record(&self, instruments: &[InstrumentInfo]) {
+        let mut scales = self.0.write().unwrap_or_else(|e| e.into_inner());
+        for instrument in instruments {
+            let price = match scale_of(instrument.tick_size) {
+                Ok(price) => price,
+                Err(e) => {
+                    warn!(
+                        component = "deribit",
+                        instrument = %instrument.instrument_name,
+                        error = %e,
+                        "Ignoring invalid tick size, prices stay exact"
+                    );
+                    continue;
+                }
+            };
+            scales.insert(
+                instrument.instrument_name.clone(),
+                InstrumentScale {
+                    price,
+                    quantity: instrument.min_trade_amount.and_then(|amount| scale_of(amount).ok()),
+                },
+            );
+        }
+    }
+
+    /// Price on the instrument's tick grid
+    pub fn price(&self, instrument: &str, value: f64) -> Result<Price> {
+        let price = match self.get(instrument) {
+            Some(scale) => Price::from_f64(value, scale.price)?,
+            None => Price::exact(value)?,
+        };
+        Ok(price)
+    }
+
+    /// Amount at the instrument's trade-size precision
+    pub fn quantity(&self, instrument: &str, value: f64) -> Result<Quantity> {
+        let quantity = match self.get(instrument).and_then(|scale| scale.quantity) {
+            Some(scale) => Quantity::from_f64(value, scale)?,
+            None => Quantity::exact(value)?,
+        };
+        Ok(quantity)
+    }
+}
+
+/// Resolves configured symbol selectors to concrete instrument names via `public/get_instruments`
+#[derive(Debug, Clone)]
+pub struct InstrumentResolver {
+    http: reqwest::Client,
+    base_url: String,
+    selectors: Vec<SymbolSelector>,
+    scales: InstrumentScales,
+}
+
+impl InstrumentResolver {
+    /// `scales` is filled with the tick size of every instrument fetched
+    pub fn new(rest_url: &str, symbols: &[String], scales: InstrumentScales) -> Result<Self> {
+        let selectors = symbols
+            .iter()
+            .map(|s| SymbolSelector::parse(s))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            http: reqwest::Client::new(),
+            base_url: rest_url.trim_end_matches('/').to_string(),
+            selectors,
+            scales,
+        })
+    }
+
+    /// True when every selector is an exact name, so no lookup or refresh is needed
+    pub fn is_static(&self) -> bool {
+        self.selectors.iter().all(SymbolSelector::is_exact)
+    }
+
+    /// Resolve every selector to the set of currently listed, active instruments.
+    ///
+    /// Exact names are passed through untouched (their currency is only fetched to learn tick
+    /// sizes); everything else is matched against the instrument list, fetched once per
+    /// distinct (currency, kind) pair to respect the endpoint's rate limit.
+    pub async fn resolve(&self) -> Result<HashSet<String>> {
+        let mut resolved = HashSet::new();
+        let mut fetched: HashMap<(String, Option<&'static str>), Vec<InstrumentInfo>> = HashMap::new();
+        let now_ms = (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as i64;
+
+        for selector in &self.selectors {
+            let query = selector.query();
+
+            if let SymbolSelector::Exact(name) = selector {
+                resolved.insert(name.clone());
+                // Only needed for the tick size, so a failed lookup is not fatal
+                if self.scales.get(name).is_none() && !fetched.contains_key(&query) {
+                    match self.fetch(&query.0, query.1).await {
+                        Ok(instruments) => {
+                            fetched.insert(query, instruments);
+                        }
+                        Err(e) => warn!(
+                            component = "deribit",
+                            instrument = %name,
+                            error = %e,
+                            "Tick size lookup failed, keeping exchange precision"
+                        ),
+                    }
+                }
+                continue;
+            }
+
+            if !fetched.contains_key(&query) {
+                let instruments = self.fetch(&query.0, query.1).await?;
+                fetched.insert(query.clone(), instruments);
+            }
+
+            resolved.extend(
+                fetched[&query]
+                    .iter()
+                    .filter(|i| i.is_active && i.expiration_timestamp > now_ms)
+                    .filter(|i| selector.matches(i, now_ms))
+                    .map(|i| i.instrument_name.clone()),
+            );
+        }
+
+        debug!(component = "deribit", instruments = resolved.len(), "Resolved symbol selectors");
+        Ok(resolved)
+    }
+
+    async fn fetch(&self, currency: &str, kind: Option<&str>) -> Result<Vec<InstrumentInfo>> {
+        let mut query = vec![("currency", currency), ("expired", "false")];
+        if let Some(kind) = kind {
+            query.push(("kind", kind));
+        }
+
+        let response: GetInstrumentsResponse = self
+            .http
+            .get(format!("{}/public/get_instruments", self.base_url))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        self.scales.record(&response.result);
+        Ok(response.result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instrument(name: &str, tick_size: f64, min_trade_amount: Option<f64>) -> InstrumentInfo {
+        InstrumentInfo {
+            instrument_name: name.to_string(),
+            kind: "future".to_string(),
+            base_currency: "BTC".to_string(),
+            expiration_timestamp: 32_503_680_000_000,
+            settlement_period: Some("perpetual".to_string()),
+            option_type: None,
+            is_active: true,
+            tick_size,
+            min_trade_amount,
+        }
+    }
+
+    #[test]
+    fn rounds_to_tick_size_and_minimum_trade_amount() {
+        let scales = InstrumentScales::default();
+        scales.record(&[instrument("BTC-PERPETUAL", 0.5, Some(10.0))]);
+
+        assert_eq!(
+            scales.price("BTC-PERPETUAL", 43500.5).unwrap(),
+            Price::from_f64(43500.5, 1).unwrap()
+        );
+        assert_eq!(
+            scales.quantity("BTC-PERPETUAL", 120.0).unwrap(),
+            Quantity::from_f64(120.0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn amounts_stay_exact_without_a_minimum_trade_amount() {
+        let scales = InstrumentScales::default();
+        scales.record(&[instrument("BTC-PERPETUAL", 0.5, None)]);
+
+        assert_eq!(scales.get("BTC-PERPETUAL").unwrap().quantity, None);
+        assert_eq!(
+            scales.quantity("BTC-PERPETUAL", 0.0001).unwrap(),
+            Quantity::exact(0.0001).unwrap()
+        );
+    }
+
+    #[test]
+    fn unknown_instruments_keep_every_decimal() {
+        let scales = InstrumentScales::default();
+
+        assert_eq!(
+            scales.price("ETH-PERPETUAL", 2500.05).unwrap(),
+            Price::exact(2500.05).unwrap()
+        );
+        assert_eq!(
+            scales.quantity("ETH-PERPETUAL", 1.25).unwrap(),
+            Quantity::exact(1.25).unwrap()
+        );
+    }
+
+    #[test]
+    fn non_finite_values_are_errors() {
+        let scales = InstrumentScales::default();
+        scales.record(&[instrument("BTC-PERPETUAL", 0.5, Some(10.0))]);
+
+        assert!(scales.price("BTC-PERPETUAL", f64::NAN).is_err());
+        assert!(scales.quantity("BTC-PERPETUAL", f64::INFINITY).is_err());
+        assert!(scales.price("ETH-PERPETUAL", f64::NEG_INFINITY).is_err());
+    }
+
+    fn kind(currency: &str, kind: InstrumentKind, within_days: Option<u32>) -> SymbolSelector {
+        SymbolSelector::Kind {
+            currency: currency.to_string(),
+            kind,
+            within_days,
+        }
+    }
+
+    #[test]
+    fn parses_exact_names_and_globs() {
+        assert_eq!(
+            SymbolSelector::parse("BTC-PERPETUAL").unwrap(),
+            SymbolSelector::Exact("BTC-PERPETUAL".to_string())
+        );
+        assert_eq!(
+            SymbolSelector::parse(" BTC-*-C ").unwrap(),
+            SymbolSelector::Glob("BTC-*-C".to_string())
+        );
+        assert!(SymbolSelector::parse("   ").is_err());
+    }
+
+    #[test]
+    fn parses_currency_and_kind_selectors() {
+        assert_eq!(
+            SymbolSelector::parse("eth futures").unwrap(),
+            kind("ETH", InstrumentKind::Future, None)
+        );
+        assert_eq!(
+            SymbolSelector::parse("all BTC options expiring within 30 days").unwrap(),
+            kind("BTC", InstrumentKind::Option, Some(30))
+        );
+        assert_eq!(
+            SymbolSelector::parse("BTC options within 7d").unwrap(),
+            kind("BTC", InstrumentKind::Option, Some(7))
+        );
+        assert_eq!(
+            SymbolSelector::parse("BTC perpetual expiring 1 day").unwrap(),
+            kind("BTC", InstrumentKind::Perpetual, Some(1))
+        );
+        assert!(SymbolSelector::parse("BTC swaps").is_err());
+    }
+
+    #[test]
+    fn expiry_window_needs_a_number() {
+        for input in [
+            "BTC options within",
+            "BTC options days",
+            "BTC options expiring within days",
+            "BTC options 30 days",
+            "BTC options within 30 weeks",
+        ] {
+            assert!(SymbolSelector::parse(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn glob_matches_whole_names() {
+        assert!(glob_match("BTC-*-C", "BTC-27DEC24-100000-C"));
+        assert!(glob_match("*-PERPETUAL", "ETH-PERPETUAL"));
+        assert!(glob_match("BTC-*", "BTC-PERPETUAL"));
+        assert!(glob_match("BTC-*DEC*-C", "BTC-27DEC24-100000-C"));
+        assert!(glob_match("BTC-PERPETUAL", "BTC-PERPETUAL"));
+
+        assert!(!glob_match("BTC-*-C", "BTC-27DEC24-100000-P"));
+        assert!(!glob_match("BTC-*", "ETH-PERPETUAL"));
+        assert!(!glob_match("BTC-*DEC*-C", "BTC-27JUN25-100000-C"));
+        // The prefix and suffix may not overlap
+        assert!(!glob_match("BTC-*-C", "BTC-C"));
+    }
+}
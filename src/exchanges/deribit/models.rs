@@ -1,95 +1,63 @@
-use serde::{Deserialize, Serialize};
 use crate::errors::Result;
 use futures::stream::BoxStream;
 use async_trait::async_trait;
-use time::OffsetDateTime;
-
-// Unified Kafka schema for market data
-// All data types include common metadata fields for efficient querying and partitioning
-
-/// Order book snapshot data
-/// Represents the current state of the order book for an instrument
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OrderBookSnapshot {
-    pub symbol: String,
-    pub venue: String,
-    pub bids: Vec<(f64, f64)>,
-    pub asks: Vec<(f64, f64)>,
-    pub seq_id: u64,
-    pub instrument_class: Option<String>,
-    #[serde(with = "time::serde::rfc3339")]
-    pub timestamp: OffsetDateTime,
-    #[serde(with = "time::serde::rfc3339")]
-    pub ingestion_timestamp: OffsetDateTime,
+use market_data_types::Envelope;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::oneshot;
+
+// The venue-neutral types live in `market-data-types` so consumers need not depend on the collector
+pub use market_data_types::models::*;
+
+/// Subscription state of one exchange channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubscriptionStatus {
+    /// Requested, waiting for the exchange to acknowledge it
+    Pending,
+    /// Listed in the exchange's subscribe response
+    Active,
+    /// Missing from the subscribe response; it will not be restored on reconnect
+    Rejected,
+}
+
+/// Change to the set of subscribed symbols
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolUpdate {
+    /// Subscribe to these symbols on top of the current set
+    Add(Vec<String>),
+    /// Drop these symbols from the current set
+    Remove(Vec<String>),
+    /// Make this the exact subscribed set
+    Replace(Vec<String>),
 }
 
-/// Trade execution data
-/// Represents an individual trade that occurred on the exchange
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TradeSnapshot {
-    pub symbol: String,
-    pub venue: String,
-    pub trade_id: String,
-    pub price: f64,
-    pub amount: f64,
-    pub side: String, // "buy" or "sell"
-    pub seq_id: Option<u64>,
-    pub instrument_class: Option<String>,
-    #[serde(with = "time::serde::rfc3339")]
-    pub timestamp: OffsetDateTime,
-    #[serde(with = "time::serde::rfc3339")]
-    pub ingestion_timestamp: OffsetDateTime,
-
-    // Extended Deribit fields
-    pub contracts: Option<f64>,       // Trade size in contract units
-    pub index_price: Option<f64>,     // Index Price at the moment of trade
-    pub mark_price: Option<f64>,      // Mark Price at the moment of trade
-    pub tick_direction: Option<i32>,  // Direction of the "tick" (0-3)
+/// Symbols actually added and removed after diffing an update against the current set
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolUpdateReport {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
 }
 
-/// Ticker data with comprehensive market information (flattened structure)
-/// Represents the current market state for an instrument
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TickerRow {
-    pub timestamp: i64,
-    pub ingestion_timestamp: i64,
-    pub venue: String,
-    pub state: u8,
-    pub symbol: String,
-    pub index_price: Option<f64>,
-    pub settlement_price: Option<f64>,
-    pub open_interest: Option<f64>,
-    pub mark_price: Option<f64>,
-    pub best_bid_price: Option<f64>,
-    pub mark_iv: Option<f64>,
-    pub ask_iv: Option<f64>,
-    pub bid_iv: Option<f64>,
-    pub underlying_price: Option<f64>,
-    pub underlying_index: Option<String>,
-    pub best_ask_price: Option<f64>,
-    pub interest_rate: Option<f64>,
-    pub estimated_delivery_price: Option<f64>,
-    pub best_ask_amount: Option<f64>,
-    pub best_bid_amount: Option<f64>,
-    pub current_funding: Option<f64>,
-    pub delivery_price: Option<f64>,
-    pub funding_8h: Option<f64>,
-    pub interest_value: Option<f64>,
-    pub greeks_delta: Option<f64>,
-    pub greeks_gamma: Option<f64>,
-    pub greeks_vega: Option<f64>,
-    pub greeks_theta: Option<f64>,
-    pub greeks_rho: Option<f64>,
+/// Symbol update sent to a running exchange over its symbol channel
+#[derive(Debug)]
+pub struct SymbolCommand {
+    pub update: SymbolUpdate,
+    pub reply: Option<oneshot::Sender<Result<SymbolUpdateReport>>>,
 }
 
-/// Unified market data enum for streaming
-/// Tagged with type for easy deserialization and routing
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "data_type", rename_all = "lowercase")]
-pub enum MarketData {
-    Orderbook(OrderBookSnapshot),
-    Trade(TradeSnapshot),
-    Ticker(TickerRow),
+impl SymbolCommand {
+    /// Build a command together with the receiver its result is reported on
+    pub fn with_reply(update: SymbolUpdate) -> (Self, oneshot::Receiver<Result<SymbolUpdateReport>>) {
+        let (tx, rx) = oneshot::channel();
+        (Self { update, reply: Some(tx) }, rx)
+    }
+}
+
+impl From<SymbolUpdate> for SymbolCommand {
+    fn from(update: SymbolUpdate) -> Self {
+        Self { update, reply: None }
+    }
 }
 
 #[async_trait]
@@ -100,9 +68,18 @@ pub trait Exchange: Send + Sync {
 
     async fn unsubscribe(&mut self, symbols: &[String]) -> Result<()>;
 
-    async fn connect_orderbook(&mut self) -> Result<BoxStream<'static, Result<MarketData>>>;
+    async fn connect_orderbook(&mut self) -> Result<BoxStream<'static, Result<Envelope>>>;
+
+    async fn connect_trades(&mut self) -> Result<BoxStream<'static, Result<Envelope>>>;
+
+    async fn connect_ticker(&mut self) -> Result<BoxStream<'static, Result<Envelope>>>;
+
+    /// Top-of-book quotes
+    async fn connect_quotes(&mut self) -> Result<BoxStream<'static, Result<Envelope>>>;
 
-    async fn connect_trades(&mut self) -> Result<BoxStream<'static, Result<MarketData>>>;
+    /// Index, mark price, instrument state and platform notifications
+    async fn connect_reference_data(&mut self) -> Result<BoxStream<'static, Result<Envelope>>>;
 
-    async fn connect_ticker(&mut self) -> Result<BoxStream<'static, Result<MarketData>>>;
+    /// Status of every channel requested so far, keyed by channel name
+    async fn subscription_status(&self) -> HashMap<String, SubscriptionStatus>;
 }
\ No newline at end of file
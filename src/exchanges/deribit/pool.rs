@@ -0,0 +1,191 @@
+use super::channels::ChannelKind;
+use super::connection::DeribitConnection;
+use super::exchange::DeribitConfig;
+use super::instruments::InstrumentScales;
+use super::envelope::Sequencer;
+use market_data_types::Envelope;
+use super::models::SubscriptionStatus;
+use crate::errors::{MarketDataError, Result};
+use futures::future::join_all;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::info;
+
+/// Channels spread over several Deribit connections.
+///
+/// Each channel is pinned to one connection, filling the least-loaded connection up to
+/// `max_channels_per_connection` and opening another (up to `max_connections`) when all are
+/// full. Every connection publishes into the same broadcast senders, so consumers see one
+/// merged stream per channel family while each socket reconnects independently.
+pub struct ConnectionPool {
+    config: DeribitConfig,
+    senders: HashMap<ChannelKind, broadcast::Sender<Envelope>>,
+    scales: InstrumentScales,
+    sequencer: Arc<Sequencer>,
+    connections: RwLock<Vec<Arc<DeribitConnection>>>,
+    /// Connection index each subscribed channel lives on
+    assignments: RwLock<HashMap<String, usize>>,
+}
+
+impl ConnectionPool {
+    /// Open the first connection; further ones are opened as channels are added
+    pub async fn connect(config: DeribitConfig, scales: InstrumentScales) -> Result<Self> {
+        let senders: HashMap<ChannelKind, broadcast::Sender<Envelope>> = ChannelKind::ALL
+            .iter()
+            .map(|kind| (*kind, broadcast::channel(config.stream_buffer).0))
+            .collect();
+
+        let sequencer = Arc::new(Sequencer::new(config.instance_id.clone()));
+
+        let first = DeribitConnection::connect(
+            0,
+            config.clone(),
+            senders.clone(),
+            scales.clone(),
+            sequencer.clone(),
+        )
+        .await?;
+
+        Ok(Self {
+            config,
+            senders,
+            scales,
+            sequencer,
+            connections: RwLock::new(vec![first]),
+            assignments: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Channel names for a set of instruments using the configured channel parameters
+    pub fn channels(&self, kind: ChannelKind, symbols: &HashSet<String>) -> Vec<String> {
+        kind.channels(&self.config.channels, symbols)
+    }
+
+    /// Receiver for one channel family across all connections; take it before subscribing
+    pub fn receiver(&self, kind: ChannelKind) -> broadcast::Receiver<Envelope> {
+        self.senders[&kind].subscribe()
+    }
+
+    /// Status of every channel requested so far, across all connections
+    pub async fn subscription_status(&self) -> HashMap<String, SubscriptionStatus> {
+        let connections = self.connections.read().await.clone();
+        let mut status = HashMap::new();
+        for connection in connections {
+            status.extend(connection.subscription_status().await);
+        }
+        status
+    }
+
+    pub async fn subscribe(&self, channels: Vec<String>) -> Result<()> {
+        let batches = self.assign(channels).await?;
+
+        let results = join_all(
+            batches
+                .into_iter()
+                .map(|(connection, channels)| async move { connection.subscribe(channels).await }),
+        )
+        .await;
+        results.into_iter().collect()
+    }
+
+    pub async fn unsubscribe(&self, channels: Vec<String>) -> Result<()> {
+        let by_index: HashMap<usize, Vec<String>> = {
+            let mut assignments = self.assignments.write().await;
+            let mut by_index: HashMap<usize, Vec<String>> = HashMap::new();
+            for channel in channels {
+                // Never subscribed, nothing to send
+                if let Some(index) = assignments.remove(&channel) {
+                    by_index.entry(index).or_default().push(channel);
+                }
+            }
+            by_index
+        };
+
+        let connections = self.connections.read().await.clone();
+        let results = join_all(
+            by_index
+                .into_iter()
+                .map(|(index, channels)| {
+                    let connection = connections[index].clone();
+                    async move { connection.unsubscribe(channels).await }
+                }),
+        )
+        .await;
+        results.into_iter().collect()
+    }
+
+    /// Pin new channels to connections, opening connections as needed
+    async fn assign(
+        &self,
+        channels: Vec<String>,
+    ) -> Result<Vec<(Arc<DeribitConnection>, Vec<String>)>> {
+        let max_per_connection = self.config.max_channels_per_connection.max(1);
+        let mut assignments = self.assignments.write().await;
+        let mut connections = self.connections.write().await;
+
+        let new_channels = channels
+            .iter()
+            .filter(|c| !assignments.contains_key(*c))
+            .collect::<HashSet<_>>()
+            .len();
+        let capacity = self.config.max_connections * max_per_connection;
+        if assignments.len() + new_channels > capacity {
+            return Err(MarketDataError::ConnectionError(format!(
+                "Channel capacity exhausted: {} subscribed + {} new exceeds {} connections x {} channels",
+                assignments.len(),
+                new_channels,
+                self.config.max_connections,
+                max_per_connection
+            )));
+        }
+
+        // Open every connection needed up front so a failure leaves no channel half-assigned
+        let needed = (assignments.len() + new_channels).div_ceil(max_per_connection);
+        while connections.len() < needed {
+            let index = connections.len();
+            info!(
+                component = "deribit",
+                connection = index,
+                "All connections full, opening another"
+            );
+            let connection = DeribitConnection::connect(
+                index,
+                self.config.clone(),
+                self.senders.clone(),
+                self.scales.clone(),
+                self.sequencer.clone(),
+            )
+            .await?;
+            connections.push(connection);
+        }
+
+        let mut load = vec![0usize; connections.len()];
+        for index in assignments.values() {
+            load[*index] += 1;
+        }
+
+        let mut batches: HashMap<usize, Vec<String>> = HashMap::new();
+        for channel in channels {
+            // Already pinned: resubscribe on the same connection
+            if let Some(index) = assignments.get(&channel) {
+                batches.entry(*index).or_default().push(channel);
+                continue;
+            }
+
+            let index = (0..load.len())
+                .filter(|i| load[*i] < max_per_connection)
+                .min_by_key(|i| load[*i])
+                .expect("pool was sized for every channel above");
+
+            load[index] += 1;
+            assignments.insert(channel.clone(), index);
+            batches.entry(index).or_default().push(channel);
+        }
+
+        Ok(batches
+            .into_iter()
+            .map(|(index, channels)| (connections[index].clone(), channels))
+            .collect())
+    }
+}
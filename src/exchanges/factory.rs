@@ -1,8 +1,9 @@
 use crate::config::Config;
 use crate::errors::{MarketDataError, Result};
-use crate::exchanges::deribit::models::Exchange;
-use crate::exchanges::deribit::{Deribit, DeribitConfig};
+use crate::exchanges::deribit::models::{Exchange, SymbolCommand};
+use crate::exchanges::deribit::{Deribit, DeribitConfig, InstrumentResolver};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 pub struct ExchangeFactory {
@@ -17,7 +18,7 @@ impl ExchangeFactory {
     pub async fn create_exchange(
         &self,
         name: &str,
-        symbol_rx: mpsc::Receiver<Vec<String>>,
+        symbol_rx: mpsc::Receiver<SymbolCommand>,
     ) -> Result<Box<dyn Exchange>> {
         match name.to_lowercase().as_str() {
             "deribit" => {
@@ -27,13 +28,39 @@ impl ExchangeFactory {
                     .get("deribit")
                     .ok_or_else(|| MarketDataError::ConfigError("Deribit config not found".to_string()))?;
 
-                // Use default Deribit configuration
-                let deribit_config = DeribitConfig::default();
+                let deribit_config = DeribitConfig {
+                    testnet: exchange_config.testnet,
+                    ws_url: exchange_config.ws_url.clone(),
+                    credentials: exchange_config.credentials(),
+                    heartbeat_interval: exchange_config.heartbeat_interval,
+                    silence_timeout_secs: exchange_config.silence_timeout_secs,
+                    book_feed: exchange_config.book_feed,
+                    channels: exchange_config.channels.clone(),
+                    reference_channels: exchange_config.reference_channels.clone(),
+                    trade_backfill: exchange_config.trade_backfill,
+                    max_channels_per_connection: exchange_config.max_channels_per_connection,
+                    max_connections: exchange_config.max_connections,
+                    subscribe_chunk_size: exchange_config.subscribe_chunk_size,
+                    subscribe_interval_ms: exchange_config.subscribe_interval_ms,
+                    instance_id: self.config.collector.instance_id.clone(),
+                    ..DeribitConfig::default()
+                };
+                let rest_url = deribit_config.rest_url();
                 let mut deribit = Deribit::new(deribit_config, symbol_rx).await?;
+                let resolver = InstrumentResolver::new(
+                    &rest_url,
+                    &exchange_config.symbols,
+                    deribit.instrument_scales(),
+                )?;
 
-                // Subscribe to configured symbols if any
+                // Resolve configured symbols and patterns, refreshing patterns periodically
                 if !exchange_config.symbols.is_empty() {
-                    deribit.subscribe(&exchange_config.symbols).await?;
+                    deribit
+                        .track_instruments(
+                            resolver,
+                            Duration::from_secs(exchange_config.instrument_refresh_secs),
+                        )
+                        .await?;
                 }
 
                 Ok(Box::new(deribit))
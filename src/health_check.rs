@@ -1,14 +1,20 @@
-use axum::{response::Json, routing::get, Router};
+use crate::infra::{KafkaProducer, ProducerStatistics};
+use axum::{extract::State, http::StatusCode, response::Json, routing::get, Router};
 use serde_json::{json, Value};
 use std::future::Future;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tracing::info;
 
 pub async fn start_server(
     port: u16,
+    kafka_producer: Arc<KafkaProducer>,
     shutdown_signal: impl Future<Output = ()> + Send + 'static,
 ) -> Result<(), std::io::Error> {
-    let app = Router::new().route("/health", get(health_handler));
+    let app = Router::new()
+        .route("/health", get(health_handler))
+        .route("/kafka/stats", get(kafka_stats_handler))
+        .with_state(kafka_producer);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     info!(component = "health_check", "Starting health check server on {}", addr);
@@ -22,15 +28,41 @@ pub async fn start_server(
     Ok(())
 }
 
-async fn health_handler() -> Json<Value> {
+async fn health_handler(State(kafka_producer): State<Arc<KafkaProducer>>) -> Json<Value> {
     use time::{OffsetDateTime, format_description::well_known::Rfc3339};
 
     let timestamp = OffsetDateTime::now_utc()
         .format(&Rfc3339)
         .unwrap_or_else(|_| String::from("unknown"));
 
+    // Still serving, but Kafka is behind while records wait on disk
+    let spool = kafka_producer.spool_stats();
+    let status = if spool.records == 0 { "ok" } else { "degraded" };
+
+    // librdkafka's own queue, from the latest statistics report
+    let producer_queue = kafka_producer.statistics().map(|statistics| {
+        json!({
+            "messages": statistics.msg_cnt,
+            "max_messages": statistics.msg_max,
+            "partition_backlog": statistics.partition_backlog()
+        })
+    });
+
     Json(json!({
-        "status": "ok",
-        "timestamp": timestamp
+        "status": status,
+        "timestamp": timestamp,
+        "kafka": kafka_producer.delivery_stats(),
+        "producer_queue": producer_queue,
+        "spool": spool
     }))
 }
+
+/// Latest librdkafka statistics, or 503 until the first report arrives
+async fn kafka_stats_handler(
+    State(kafka_producer): State<Arc<KafkaProducer>>,
+) -> Result<Json<ProducerStatistics>, StatusCode> {
+    kafka_producer
+        .statistics()
+        .map(|statistics| Json(ProducerStatistics::clone(&statistics)))
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)
+}
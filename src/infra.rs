@@ -1,7 +1,18 @@
+pub mod codec;
+pub mod dead_letter;
+pub mod headers;
 pub mod kafka_producer;
+pub mod kafka_stats;
 pub mod kafka_consumer;
 pub mod redis;
+pub mod schema_registry;
+pub mod spool;
 
+pub use codec::{AvroCodec, Codec, CodecKind};
+pub use dead_letter::ErrorClass;
 pub use kafka_producer::{KafkaProducer, KafkaProducerConfig};
 pub use kafka_consumer::KafkaConsumer;
+pub use kafka_stats::ProducerStatistics;
 pub use redis::RedisStorage;
+pub use schema_registry::{MockSchemaRegistry, SchemaRegistry};
+pub use spool::{Spool, SpoolStats};
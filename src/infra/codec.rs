@@ -0,0 +1,189 @@
+use crate::errors::Result;
+use crate::infra::schema_registry::{value_subject, Compatibility, SchemaRegistry};
+use apache_avro::Schema;
+use market_data_types::{CodecError, Envelope, MarketData, Result as CodecResult};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::info;
+
+pub use market_data_types::codec::{
+    decode, Codec, CodecKind, JsonCodec, MsgPackCodec, ProtobufCodec, CODEC_HEADER,
+};
+
+/// Avro schema of the enveloped messages, `avro/market_data.avsc`
+pub const AVRO_SCHEMA: &str = include_str!("../../avro/market_data.avsc");
+
+/// First byte of every Confluent-framed record
+const MAGIC_BYTE: u8 = 0;
+
+fn avro_error(e: apache_avro::Error) -> CodecError {
+    CodecError::Invalid(e.to_string())
+}
+
+/// Avro in the Confluent wire format: magic byte, big-endian schema id, then the datum.
+///
+/// Records are decoded with our schema as the reader schema, so any writer schema the
+/// registry accepted as compatible can be read once `load_writer_schema` has fetched it.
+pub struct AvroCodec {
+    schema: Schema,
+    schema_id: u32,
+    registry: Arc<dyn SchemaRegistry>,
+    writer_schemas: RwLock<HashMap<u32, Schema>>,
+}
+
+impl AvroCodec {
+    pub fn schema() -> Result<Schema> {
+        Ok(Schema::parse_str(AVRO_SCHEMA).map_err(avro_error)?)
+    }
+
+    /// Register the schema for `topic`'s values, enforcing `compatibility` on the subject
+    pub async fn register(
+        registry: Arc<dyn SchemaRegistry>,
+        topic: &str,
+        compatibility: Compatibility,
+    ) -> Result<Self> {
+        let schema = Self::schema()?;
+        let subject = value_subject(topic);
+
+        registry.set_compatibility(&subject, compatibility).await?;
+        let schema_id = registry.register(&subject, &schema).await?;
+
+        info!(
+            component = "schema_registry",
+            subject = %subject,
+            schema_id,
+            compatibility = compatibility.as_str(),
+            "Registered Avro schema"
+        );
+
+        Ok(Self {
+            writer_schemas: RwLock::new(HashMap::from([(schema_id, schema.clone())])),
+            schema,
+            schema_id,
+            registry,
+        })
+    }
+
+    pub fn schema_id(&self) -> u32 {
+        self.schema_id
+    }
+
+    /// Schema id from a framed record's header
+    pub fn schema_id_of(bytes: &[u8]) -> CodecResult<u32> {
+        match bytes {
+            [MAGIC_BYTE, a, b, c, d, ..] => Ok(u32::from_be_bytes([*a, *b, *c, *d])),
+            _ => Err(CodecError::Invalid(
+                "not a Confluent-framed Avro record".to_string(),
+            )),
+        }
+    }
+
+    /// Fetch and cache a writer schema so records written with it can be decoded
+    pub async fn load_writer_schema(&self, id: u32) -> Result<()> {
+        if self.writer_schemas.read().unwrap_or_else(|e| e.into_inner()).contains_key(&id) {
+            return Ok(());
+        }
+        let schema = self.registry.schema_by_id(id).await?;
+        self.writer_schemas
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, schema);
+        Ok(())
+    }
+}
+
+impl Codec for AvroCodec {
+    fn kind(&self) -> CodecKind {
+        CodecKind::Avro
+    }
+
+    fn encode(&self, envelope: &Envelope) -> CodecResult<Vec<u8>> {
+        let value = apache_avro::to_value(envelope)
+            .map_err(avro_error)?
+            .resolve(&self.schema)
+            .map_err(avro_error)?;
+        let datum = apache_avro::to_avro_datum(&self.schema, value).map_err(avro_error)?;
+
+        let mut bytes = Vec::with_capacity(5 + datum.len());
+        bytes.push(MAGIC_BYTE);
+        bytes.extend_from_slice(&self.schema_id.to_be_bytes());
+        bytes.extend_from_slice(&datum);
+        Ok(bytes)
+    }
+
+    fn encode_payload(&self, _data: &MarketData) -> CodecResult<Vec<u8>> {
+        Err(CodecError::Invalid(
+            "the avro codec only encodes enveloped Kafka records".to_string(),
+        ))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> CodecResult<Envelope> {
+        let id = Self::schema_id_of(bytes)?;
+        let writer_schemas = self.writer_schemas.read().unwrap_or_else(|e| e.into_inner());
+        let writer = writer_schemas.get(&id).ok_or_else(|| {
+            CodecError::Invalid(format!("unknown writer schema {}, load it first", id))
+        })?;
+
+        let value = apache_avro::from_avro_datum(writer, &mut &bytes[5..], Some(&self.schema))
+            .map_err(avro_error)?;
+        apache_avro::from_value(&value).map_err(avro_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::schema_registry::MockSchemaRegistry;
+    use crate::test_support;
+
+    const TOPIC: &str = "market.index";
+
+    fn envelope() -> Envelope {
+        test_support::envelope(test_support::index_price("btc_usd"))
+    }
+
+    async fn avro_codec(registry: Arc<MockSchemaRegistry>) -> AvroCodec {
+        AvroCodec::register(registry, TOPIC, Compatibility::Backward).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn registers_the_schema_under_the_value_subject() {
+        let registry = Arc::new(MockSchemaRegistry::new());
+        let codec = avro_codec(registry.clone()).await;
+
+        assert_eq!(registry.versions(&value_subject(TOPIC)), vec![codec.schema_id()]);
+        // Registering again, as a restart does, keeps the id
+        assert_eq!(avro_codec(registry).await.schema_id(), codec.schema_id());
+    }
+
+    #[tokio::test]
+    async fn round_trips_in_the_confluent_wire_format() {
+        let codec = avro_codec(Arc::new(MockSchemaRegistry::new())).await;
+        let envelope = envelope();
+
+        let bytes = codec.encode(&envelope).unwrap();
+        assert_eq!(bytes[0], MAGIC_BYTE);
+        assert_eq!(bytes[1..5], codec.schema_id().to_be_bytes());
+        assert_eq!(AvroCodec::schema_id_of(&bytes).unwrap(), codec.schema_id());
+
+        let decoded = codec.decode(&bytes).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&envelope).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unframed_records_and_unknown_schemas() {
+        let codec = avro_codec(Arc::new(MockSchemaRegistry::new())).await;
+        let mut bytes = codec.encode(&envelope()).unwrap();
+
+        assert!(AvroCodec::schema_id_of(&bytes[..4]).is_err());
+        assert!(AvroCodec::schema_id_of(&[1, 0, 0, 0, 1]).is_err());
+
+        let unknown = codec.schema_id() + 1;
+        bytes[1..5].copy_from_slice(&unknown.to_be_bytes());
+        assert!(codec.decode(&bytes).is_err());
+        assert!(codec.load_writer_schema(unknown).await.is_err());
+    }
+}
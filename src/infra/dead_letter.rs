@@ -0,0 +1,173 @@
+use crate::errors::MarketDataError;
+use crate::infra::spool::KafkaRecord;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+
+/// Topic the record was meant for
+pub const SOURCE_TOPIC_HEADER: &str = "dlq.source.topic";
+/// `RDKafkaErrorCode` name of the failure, or `Serialization` when the record could not be encoded
+pub const ERROR_CODE_HEADER: &str = "dlq.error.code";
+/// Display text of the failure
+pub const ERROR_MESSAGE_HEADER: &str = "dlq.error.message";
+/// Delivery attempts made before giving up, as a decimal string
+pub const ATTEMPTS_HEADER: &str = "dlq.attempts";
+
+/// Whether sending the same record again can succeed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Broker or network trouble; the record is kept and retried
+    Retryable,
+    /// The record itself is at fault; retrying would fail the same way
+    Permanent,
+}
+
+impl ErrorClass {
+    pub fn of(error: &MarketDataError) -> Self {
+        match error {
+            MarketDataError::KafkaError(e) => Self::of_kafka(e),
+            MarketDataError::CodecError(_) => ErrorClass::Permanent,
+            _ => ErrorClass::Retryable,
+        }
+    }
+
+    /// `UnknownTopicOrPartition` stays retryable: brokers return it while a new topic's
+    /// metadata or a partition's leadership is still settling
+    fn of_kafka(error: &KafkaError) -> Self {
+        match error.rdkafka_error_code() {
+            Some(
+                RDKafkaErrorCode::MessageSizeTooLarge
+                | RDKafkaErrorCode::InvalidMessageSize
+                | RDKafkaErrorCode::MessageBatchTooLarge
+                | RDKafkaErrorCode::InvalidMessage
+                | RDKafkaErrorCode::InvalidRecord
+                | RDKafkaErrorCode::UnknownTopic
+                | RDKafkaErrorCode::InvalidTopic
+                | RDKafkaErrorCode::TopicAuthorizationFailed
+                | RDKafkaErrorCode::ClusterAuthorizationFailed
+                | RDKafkaErrorCode::PolicyViolation,
+            ) => ErrorClass::Permanent,
+            _ => ErrorClass::Retryable,
+        }
+    }
+}
+
+/// Short, stable name of the failure for the `dlq.error.code` header
+pub fn error_code(error: &MarketDataError) -> String {
+    match error {
+        MarketDataError::KafkaError(e) => e
+            .rdkafka_error_code()
+            .map(|code| format!("{:?}", code))
+            .unwrap_or_else(|| "Unknown".to_string()),
+        MarketDataError::CodecError(_) => "Serialization".to_string(),
+        _ => "Unknown".to_string(),
+    }
+}
+
+/// The record to publish on `dead_letter_topic` in place of `record`: same key, payload and
+/// headers, plus the failure details
+pub fn dead_letter_record(
+    dead_letter_topic: &str,
+    record: &KafkaRecord,
+    error: &MarketDataError,
+    attempts: u32,
+) -> KafkaRecord {
+    let mut headers = record.headers.clone();
+    headers.extend([
+        (SOURCE_TOPIC_HEADER.to_string(), record.topic.clone().into_bytes()),
+        (ERROR_CODE_HEADER.to_string(), error_code(error).into_bytes()),
+        (ERROR_MESSAGE_HEADER.to_string(), error.to_string().into_bytes()),
+        (ATTEMPTS_HEADER.to_string(), attempts.to_string().into_bytes()),
+    ]);
+
+    KafkaRecord {
+        topic: dead_letter_topic.to_string(),
+        key: record.key.clone(),
+        payload: record.payload.clone(),
+        headers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn produce_error(code: RDKafkaErrorCode) -> MarketDataError {
+        MarketDataError::KafkaError(KafkaError::MessageProduction(code))
+    }
+
+    fn codec_error() -> MarketDataError {
+        MarketDataError::CodecError(market_data_types::CodecError::Invalid("bad".to_string()))
+    }
+
+    #[test]
+    fn classifies_failures_by_cause() {
+        let permanent = [
+            RDKafkaErrorCode::MessageSizeTooLarge,
+            RDKafkaErrorCode::MessageBatchTooLarge,
+            RDKafkaErrorCode::InvalidRecord,
+            RDKafkaErrorCode::UnknownTopic,
+            RDKafkaErrorCode::TopicAuthorizationFailed,
+            RDKafkaErrorCode::PolicyViolation,
+        ];
+        for code in permanent {
+            assert_eq!(ErrorClass::of(&produce_error(code)), ErrorClass::Permanent, "{:?}", code);
+        }
+
+        let retryable = [
+            RDKafkaErrorCode::UnknownTopicOrPartition,
+            RDKafkaErrorCode::MessageTimedOut,
+            RDKafkaErrorCode::AllBrokersDown,
+            RDKafkaErrorCode::QueueFull,
+            RDKafkaErrorCode::NotLeaderForPartition,
+        ];
+        for code in retryable {
+            assert_eq!(ErrorClass::of(&produce_error(code)), ErrorClass::Retryable, "{:?}", code);
+        }
+
+        assert_eq!(ErrorClass::of(&codec_error()), ErrorClass::Permanent);
+        assert_eq!(
+            ErrorClass::of(&MarketDataError::KafkaError(KafkaError::Canceled)),
+            ErrorClass::Retryable
+        );
+        assert_eq!(
+            ErrorClass::of(&MarketDataError::SpoolError("disk full".to_string())),
+            ErrorClass::Retryable
+        );
+    }
+
+    #[test]
+    fn dead_letter_record_adds_failure_headers() {
+        let record = KafkaRecord {
+            topic: "market.trades".to_string(),
+            key: "deribit.BTC-PERPETUAL".to_string(),
+            payload: b"{}".to_vec(),
+            headers: vec![("codec".to_string(), b"json".to_vec())],
+        };
+        let error = produce_error(RDKafkaErrorCode::MessageSizeTooLarge);
+
+        let dead_letter = dead_letter_record("market-data-dead-letter", &record, &error, 3);
+
+        assert_eq!(dead_letter.topic, "market-data-dead-letter");
+        assert_eq!(dead_letter.key, record.key);
+        assert_eq!(dead_letter.payload, record.payload);
+        assert_eq!(
+            dead_letter.headers,
+            vec![
+                ("codec".to_string(), b"json".to_vec()),
+                (SOURCE_TOPIC_HEADER.to_string(), b"market.trades".to_vec()),
+                (ERROR_CODE_HEADER.to_string(), b"MessageSizeTooLarge".to_vec()),
+                (ERROR_MESSAGE_HEADER.to_string(), error.to_string().into_bytes()),
+                (ATTEMPTS_HEADER.to_string(), b"3".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn error_codes_name_the_cause() {
+        assert_eq!(error_code(&codec_error()), "Serialization");
+        assert_eq!(
+            error_code(&produce_error(RDKafkaErrorCode::TopicAuthorizationFailed)),
+            "TopicAuthorizationFailed"
+        );
+        assert_eq!(error_code(&MarketDataError::KafkaError(KafkaError::Canceled)), "Unknown");
+    }
+}
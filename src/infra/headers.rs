@@ -0,0 +1,131 @@
+use crate::config::KafkaHeadersConfig;
+use market_data_types::headers::{
+    CONTENT_TYPE_HEADER, DATA_TYPE_HEADER, EXCHANGE_TIMESTAMP_HEADER, SCHEMA_VERSION_HEADER,
+    SYMBOL_HEADER, TRACEPARENT_HEADER, VENUE_HEADER,
+};
+use market_data_types::{CodecKind, Envelope, CODEC_HEADER};
+use rand::Rng;
+
+/// Headers for a record carrying `envelope` encoded with `codec`
+pub fn record_headers(
+    envelope: &Envelope,
+    codec: CodecKind,
+    config: &KafkaHeadersConfig,
+) -> Vec<(String, Vec<u8>)> {
+    let data = &envelope.payload;
+    let mut headers = vec![(CODEC_HEADER.to_string(), codec.as_str().as_bytes().to_vec())];
+    let mut add = |name: &str, value: String| headers.push((name.to_string(), value.into_bytes()));
+
+    if config.data_type {
+        add(DATA_TYPE_HEADER, data.data_type().to_string());
+    }
+    if config.venue {
+        add(VENUE_HEADER, data.venue().to_string());
+    }
+    if config.symbol {
+        add(SYMBOL_HEADER, data.stream_key().to_string());
+    }
+    if config.schema_version {
+        add(SCHEMA_VERSION_HEADER, envelope.schema_version.to_string());
+    }
+    if config.content_type {
+        add(CONTENT_TYPE_HEADER, codec.content_type().to_string());
+    }
+    if config.exchange_timestamp {
+        if let Some(timestamp) = data.exchange_timestamp() {
+            add(EXCHANGE_TIMESTAMP_HEADER, timestamp.as_nanos().to_string());
+        }
+    }
+    if config.trace_context {
+        add(TRACEPARENT_HEADER, traceparent());
+    }
+
+    headers
+}
+
+/// A sampled root `traceparent`; the record is where the trace starts
+fn traceparent() -> String {
+    let mut rng = rand::thread_rng();
+    // All-zero ids are invalid
+    let trace_id: u128 = rng.gen_range(1..=u128::MAX);
+    let span_id: u64 = rng.gen_range(1..=u64::MAX);
+    format!("00-{:032x}-{:016x}-01", trace_id, span_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{envelope, index_price};
+    use market_data_types::models::PlatformState;
+    use market_data_types::{MarketData, Timestamp};
+
+    fn header<'a>(headers: &'a [(String, Vec<u8>)], name: &str) -> Option<&'a str> {
+        headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| std::str::from_utf8(value).unwrap())
+    }
+
+    #[test]
+    fn writes_every_header_by_default() {
+        let index = envelope(index_price("btc_usd"));
+        let headers = record_headers(&index, CodecKind::Protobuf, &KafkaHeadersConfig::default());
+
+        assert_eq!(header(&headers, CODEC_HEADER), Some("protobuf"));
+        assert_eq!(header(&headers, DATA_TYPE_HEADER), Some("index_price"));
+        assert_eq!(header(&headers, VENUE_HEADER), Some("deribit"));
+        assert_eq!(header(&headers, SYMBOL_HEADER), Some("btc_usd"));
+        assert_eq!(header(&headers, SCHEMA_VERSION_HEADER), Some("1"));
+        assert_eq!(header(&headers, CONTENT_TYPE_HEADER), Some("application/x-protobuf"));
+        assert_eq!(header(&headers, EXCHANGE_TIMESTAMP_HEADER), Some("1700000000000000000"));
+        assert!(header(&headers, TRACEPARENT_HEADER).is_some());
+    }
+
+    #[test]
+    fn codec_header_survives_disabling_the_rest() {
+        let config = KafkaHeadersConfig {
+            data_type: false,
+            venue: false,
+            symbol: false,
+            schema_version: false,
+            content_type: false,
+            exchange_timestamp: false,
+            trace_context: false,
+        };
+        let headers = record_headers(&envelope(index_price("btc_usd")), CodecKind::Json, &config);
+
+        assert_eq!(headers, vec![(CODEC_HEADER.to_string(), b"json".to_vec())]);
+    }
+
+    #[test]
+    fn omits_exchange_timestamp_without_one() {
+        let platform = envelope(MarketData::PlatformState(PlatformState {
+            venue: "deribit".to_string(),
+            price_index: None,
+            locked: Some(true),
+            maintenance: None,
+            allow_unauthenticated_public_requests: None,
+            ingestion_timestamp: Timestamp::from_millis(1_700_000_000_000),
+            publish_timestamp: None,
+        }));
+        let headers = record_headers(&platform, CodecKind::Json, &KafkaHeadersConfig::default());
+
+        assert_eq!(header(&headers, SYMBOL_HEADER), Some("platform"));
+        assert_eq!(header(&headers, EXCHANGE_TIMESTAMP_HEADER), None);
+    }
+
+    #[test]
+    fn traceparent_is_a_valid_w3c_root() {
+        let value = traceparent();
+        let parts: Vec<&str> = value.split('-').collect();
+
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], "00");
+        assert_eq!(parts[1].len(), 32);
+        assert_eq!(parts[2].len(), 16);
+        assert_eq!(parts[3], "01");
+        assert!(parts[1..3].iter().all(|id| id.chars().all(|c| c.is_ascii_hexdigit())));
+        assert_ne!(parts[1], "0".repeat(32));
+        assert_ne!(traceparent(), value);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::config::KafkaConfig;
 use crate::errors::Result;
+use crate::exchanges::deribit::models::SymbolCommand;
 use rdkafka::consumer::StreamConsumer;
 use rdkafka::ClientConfig;
 use tokio::sync::mpsc;
@@ -10,7 +11,7 @@ pub struct KafkaConsumer {
 }
 
 impl KafkaConsumer {
-    pub fn new(config: KafkaConfig, _symbol_tx: mpsc::Sender<Vec<String>>) -> Result<Self> {
+    pub fn new(config: KafkaConfig, _symbol_tx: mpsc::Sender<SymbolCommand>) -> Result<Self> {
         let consumer: StreamConsumer = ClientConfig::new()
             .set("bootstrap.servers", &config.bootstrap_servers)
             .set("group.id", &config.consumer.group_id)
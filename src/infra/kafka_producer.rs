@@ -1,20 +1,101 @@
+use futures::future::{join_all, BoxFuture};
+use futures::stream::{FuturesUnordered, StreamExt};
 use rdkafka::config::ClientConfig;
+use rdkafka::error::KafkaError;
+use rdkafka::message::{Header, OwnedHeaders};
 use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use crate::config::KafkaConfig;
 use crate::errors::{Result, MarketDataError};
-use crate::exchanges::deribit::models::MarketData;
+use crate::infra::codec::{AvroCodec, Codec, CodecKind};
+use crate::infra::dead_letter::{self, ErrorClass};
+use crate::infra::headers;
+use crate::infra::kafka_stats::{ProducerStatistics, StatsContext};
+use crate::infra::schema_registry;
+use crate::infra::spool::{KafkaRecord, Spool, SpoolStats};
+use market_data_types::{naming, Envelope, MarketData, TimestampFormat};
 use tracing::{debug, error, warn, info};
-use tokio::time::sleep;
+use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
+use tokio::time::{sleep, Instant};
+
+/// Longest wait between replay attempts while the brokers stay unreachable
+const MAX_REPLAY_BACKOFF_MS: u64 = 30_000;
+
+/// An enqueued record, holding its in-flight slot until the delivery report settles it
+struct InFlight {
+    record: KafkaRecord,
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Resolves with the record once its delivery report arrives
+type PendingDelivery = BoxFuture<'static, (InFlight, Result<()>)>;
+
+/// Sent to the delivery handler
+enum Delivery {
+    Pending(PendingDelivery),
+    /// Answered once every report sent before it is settled
+    Drain(oneshot::Sender<()>),
+}
+
+/// Sent from the delivery handler to the task that spools or dead-letters failed records
+enum Failure {
+    Undelivered(InFlight, MarketDataError),
+    /// Answered once every failure queued before it is handled
+    Drain(oneshot::Sender<()>),
+}
+
+#[derive(Debug, Default)]
+struct DeliveryCounters {
+    enqueued: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    spooled: AtomicU64,
+    dead_lettered: AtomicU64,
+}
+
+/// Outcome counts of enqueued messages since startup
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryStats {
+    /// Enqueued and not yet delivered, spooled or dead-lettered
+    pub in_flight: usize,
+    pub enqueued: u64,
+    pub delivered: u64,
+    /// Enqueue or delivery failures; each failed record goes to the spool for replay
+    pub failed: u64,
+    /// Records written to the spool, including those queued behind it
+    pub spooled: u64,
+    /// Records that failed permanently and went to the dead-letter topic
+    pub dead_lettered: u64,
+}
 
 #[derive(Clone)]
 pub struct KafkaProducer {
-    client: FutureProducer,
-    #[allow(dead_code)]
-    config: KafkaConfig, // Kept for future reconnection logic
+    client: FutureProducer<StatsContext>,
+    stats: StatsContext,
+    config: KafkaConfig,
     orderbook_topic: String,
     trade_topic: String,
     ticker_topic: String,
+    index_price_topic: String,
+    volatility_index_topic: String,
+    mark_price_topic: String,
+    estimated_expiration_price_topic: String,
+    instrument_state_topic: String,
+    platform_state_topic: String,
+    quote_topic: String,
+    liquidation_topic: String,
+    funding_rate_topic: String,
+    settlement_topic: String,
+    codecs: HashMap<String, Arc<dyn Codec>>,
+    spool: Arc<Spool>,
+    in_flight: Arc<Semaphore>,
+    max_in_flight: usize,
+    deliveries: mpsc::UnboundedSender<Delivery>,
+    counters: Arc<DeliveryCounters>,
 }
 
 pub struct KafkaProducerConfig {
@@ -25,26 +106,84 @@ pub struct KafkaProducerConfig {
 }
 
 impl KafkaProducer {
-    pub fn new(config: KafkaConfig) -> Result<Self> {
-        let client = Self::create_producer(&config)?;
+    /// Create the producer, registering Avro schemas first for topics that use them.
+    ///
+    /// JSON and MessagePack topics write timestamps in `timestamps`.
+    pub async fn new(config: KafkaConfig, timestamps: TimestampFormat) -> Result<Self> {
+        let stats = StatsContext::default();
+        let client = Self::create_producer(&config, stats.clone())?;
+        let codecs = Self::create_codecs(&config, timestamps).await?;
+        let spool = Arc::new(Spool::open(&config.spool)?);
+        let (deliveries, delivery_reports) = mpsc::unbounded_channel();
+        // Every queued failure holds an in-flight permit, so the handler never waits on this
+        // queue; the extra slot is for a drain
+        let (failures, failed_deliveries) = mpsc::channel(config.producer.max_in_flight + 1);
 
         info!(
             component = "kafka",
             brokers = %config.bootstrap_servers,
+            codec = config.codec.as_str(),
             "Created Kafka producer with optimized config"
         );
 
-        Ok(Self {
+        let producer = Self {
             client,
+            stats,
             orderbook_topic: config.orderbook_topic.clone(),
             trade_topic: config.trade_topic.clone(),
             ticker_topic: config.ticker_topic.clone(),
+            index_price_topic: config.index_price_topic.clone(),
+            volatility_index_topic: config.volatility_index_topic.clone(),
+            mark_price_topic: config.mark_price_topic.clone(),
+            estimated_expiration_price_topic: config.estimated_expiration_price_topic.clone(),
+            instrument_state_topic: config.instrument_state_topic.clone(),
+            platform_state_topic: config.platform_state_topic.clone(),
+            quote_topic: config.quote_topic.clone(),
+            liquidation_topic: config.liquidation_topic.clone(),
+            funding_rate_topic: config.funding_rate_topic.clone(),
+            settlement_topic: config.settlement_topic.clone(),
+            codecs,
+            spool,
+            in_flight: Arc::new(Semaphore::new(config.producer.max_in_flight)),
+            max_in_flight: config.producer.max_in_flight,
+            deliveries,
+            counters: Arc::new(DeliveryCounters::default()),
             config,
-        })
+        };
+        tokio::spawn(producer.clone().replay_spool());
+        tokio::spawn(Self::handle_deliveries(delivery_reports, producer.counters.clone(), failures));
+        tokio::spawn(producer.clone().handle_failures(failed_deliveries));
+        Ok(producer)
+    }
+
+    /// One codec per configured topic
+    async fn create_codecs(
+        config: &KafkaConfig,
+        timestamps: TimestampFormat,
+    ) -> Result<HashMap<String, Arc<dyn Codec>>> {
+        let registry = config
+            .schema_registry
+            .as_ref()
+            .map(|r| (schema_registry::connect(r), r.compatibility));
+        let mut codecs: HashMap<String, Arc<dyn Codec>> = HashMap::new();
+
+        for topic in config.topics() {
+            if codecs.contains_key(topic) {
+                continue;
+            }
+            let codec: Arc<dyn Codec> = match (config.codec_for(topic), &registry) {
+                (CodecKind::Avro, Some((registry, compatibility))) => {
+                    Arc::new(AvroCodec::register(registry.clone(), topic, *compatibility).await?)
+                }
+                (kind, _) => kind.codec(timestamps)?,
+            };
+            codecs.insert(topic.to_string(), codec);
+        }
+        Ok(codecs)
     }
 
     /// Create a new Kafka producer with optimized settings
-    fn create_producer(config: &KafkaConfig) -> Result<FutureProducer> {
+    fn create_producer(config: &KafkaConfig, stats: StatsContext) -> Result<FutureProducer<StatsContext>> {
         ClientConfig::new()
             .set("bootstrap.servers", &config.bootstrap_servers)
             .set("message.timeout.ms", &config.producer.timeout_ms.to_string())
@@ -61,18 +200,30 @@ impl KafkaProducer {
             .set("max.in.flight.requests.per.connection", "5")  // Pipeline requests
             // Enable idempotence for exactly-once semantics within retry window
             .set("enable.idempotence", "true")
-            .create()
+            // Snapshots go to `StatsContext`; 0 turns them off
+            .set("statistics.interval.ms", &config.producer.statistics_interval_ms.to_string())
+            .create_with_context(stats)
             .map_err(|e| MarketDataError::KafkaError(e))
     }
 
-    /// Send market data with auto-reconnection on failure
-    pub async fn send_market_data(&self, data: &MarketData) -> Result<()> {
+    /// Send an enveloped message, retrying with backoff and spooling it to disk once
+    /// retries run out. Permanent failures go to the dead-letter topic instead.
+    pub async fn send_market_data(&self, envelope: &Envelope) -> Result<()> {
+        let record = match self.record_for(envelope) {
+            Ok(record) => record,
+            Err(e) => return self.dead_letter_unencodable(envelope, e).await,
+        };
+
+        if self.queue_behind_spool() {
+            return self.spool_record(&record).await;
+        }
+
         let mut attempts = 0;
         let mut backoff = self.config.producer.initial_backoff_ms;
         let max_attempts = self.config.producer.max_reconnect_attempts;
 
         loop {
-            match self.try_send_market_data(data).await {
+            match self.try_send(&record).await {
                 Ok(_) => {
                     if attempts > 0 {
                         info!(component = "kafka", attempts, "Kafka send recovered");
@@ -82,18 +233,17 @@ impl KafkaProducer {
                 Err(e) => {
                     attempts += 1;
 
+                    if ErrorClass::of(&e) == ErrorClass::Permanent {
+                        return self.dead_letter(&record, &e, attempts).await;
+                    }
                     if attempts > max_attempts {
-                        error!(
+                        warn!(
                             component = "kafka",
                             attempts,
                             error = %e,
-                            "Failed to send to Kafka after {} attempts - PANICKING",
-                            max_attempts
-                        );
-                        panic!(
-                            "Kafka send failed after {} attempts. Error: {}. Pod will restart.",
-                            max_attempts, e
+                            "Kafka unavailable, spooling to disk until it recovers"
                         );
+                        return self.spool_record(&record).await;
                     }
 
                     warn!(
@@ -111,45 +261,389 @@ impl KafkaProducer {
         }
     }
 
-    /// Try to send data once (may fail if broker is down)
-    async fn try_send_market_data(&self, data: &MarketData) -> Result<()> {
-        let (topic, key) = match data {
-            MarketData::Orderbook(ob) => {
-                (&self.orderbook_topic, format!("{}.{}", ob.venue, ob.symbol))
+    /// Queue an enveloped message without waiting for the broker; the delivery report is
+    /// handled in the background. Waits only while `max_in_flight` messages are unsettled.
+    ///
+    /// Order is not preserved across a failure: a record whose delivery fails is spooled after
+    /// later records for the same key may already have been delivered. Consumers that need
+    /// order sort by the envelope `sequence`.
+    pub async fn enqueue(&self, envelope: &Envelope) -> Result<()> {
+        let record = match self.record_for(envelope) {
+            Ok(record) => record,
+            Err(e) => return self.dead_letter_unencodable(envelope, e).await,
+        };
+
+        if self.queue_behind_spool() {
+            return self.spool_record(&record).await;
+        }
+
+        let permit = self
+            .in_flight
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("in-flight semaphore is never closed");
+
+        let sent = self.client.send_result(Self::future_record(&record)).map_err(|(e, _)| e);
+        let delivery = match sent {
+            Ok(delivery) => delivery,
+            Err(e) => {
+                self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                let e = MarketDataError::KafkaError(e);
+                if ErrorClass::of(&e) == ErrorClass::Permanent {
+                    return self.dead_letter(&record, &e, 1).await;
+                }
+                // Usually the local queue is full
+                warn!(
+                    component = "kafka",
+                    topic = %record.topic,
+                    error = %e,
+                    "Kafka enqueue failed, spooling"
+                );
+                return self.spool_record(&record).await;
             }
-            MarketData::Trade(trade) => {
-                (&self.trade_topic, format!("{}.{}", trade.venue, trade.symbol))
+        };
+        self.counters.enqueued.fetch_add(1, Ordering::Relaxed);
+
+        let in_flight = InFlight {
+            record,
+            _permit: permit,
+        };
+        let pending: PendingDelivery = Box::pin(async move {
+            let result = match delivery.await {
+                Ok(Ok(_)) => Ok(()),
+                Ok(Err((e, _))) => Err(MarketDataError::KafkaError(e)),
+                Err(_) => Err(MarketDataError::KafkaError(KafkaError::Canceled)),
+            };
+            (in_flight, result)
+        });
+        // Fails only if the handler task panicked
+        let _ = self.deliveries.send(Delivery::Pending(pending));
+        Ok(())
+    }
+
+    /// Delivery-report handler: settles enqueued records off the stream read path.
+    ///
+    /// Delivered records are settled here; failed ones go to `handle_failures`, so a dead-letter
+    /// send or spool write never holds up the reports behind it.
+    async fn handle_deliveries(
+        mut reports: mpsc::UnboundedReceiver<Delivery>,
+        counters: Arc<DeliveryCounters>,
+        failures: mpsc::Sender<Failure>,
+    ) {
+        let mut pending = FuturesUnordered::new();
+
+        loop {
+            tokio::select! {
+                report = reports.recv() => match report {
+                    Some(Delivery::Pending(report)) => pending.push(report),
+                    Some(Delivery::Drain(done)) => {
+                        while let Some((in_flight, result)) = pending.next().await {
+                            Self::settle(in_flight, result, &counters, &failures).await;
+                        }
+                        // Queued behind every failure sent so far
+                        let _ = failures.send(Failure::Drain(done)).await;
+                    }
+                    // Only once every producer handle, including those of the replay and
+                    // failure tasks, is dropped
+                    None => return,
+                },
+                Some((in_flight, result)) = pending.next(), if !pending.is_empty() => {
+                    Self::settle(in_flight, result, &counters, &failures).await;
+                }
             }
-            MarketData::Ticker(ticker) => {
-                (&self.ticker_topic, format!("{}.{}", ticker.venue, ticker.symbol))
+        }
+    }
+
+    /// Count a delivered record, releasing its in-flight slot, or queue a failed one
+    async fn settle(
+        in_flight: InFlight,
+        result: Result<()>,
+        counters: &DeliveryCounters,
+        failures: &mpsc::Sender<Failure>,
+    ) {
+        match result {
+            Ok(()) => {
+                counters.delivered.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                counters.failed.fetch_add(1, Ordering::Relaxed);
+                if failures.send(Failure::Undelivered(in_flight, e)).await.is_err() {
+                    error!(component = "kafka", "Failure handler stopped, dropping undelivered record");
+                }
             }
+        }
+    }
+
+    /// Spool or dead-letter records whose delivery failed, in the order they failed
+    async fn handle_failures(self, mut failures: mpsc::Receiver<Failure>) {
+        while let Some(failure) = failures.recv().await {
+            match failure {
+                Failure::Undelivered(in_flight, e) => self.on_delivery_failure(in_flight, e).await,
+                Failure::Drain(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+
+    /// A retryable failure is spooled behind whatever was enqueued since; the in-flight slot
+    /// is released once the record is stored
+    async fn on_delivery_failure(&self, in_flight: InFlight, e: MarketDataError) {
+        if ErrorClass::of(&e) == ErrorClass::Permanent {
+            if let Err(e) = self.dead_letter(&in_flight.record, &e, 1).await {
+                error!(component = "kafka", error = %e, "Failed to dead-letter record");
+            }
+            return;
+        }
+        // Once one record is spooled the rest of an outage follows it; log the first only
+        if self.spool.is_empty() {
+            warn!(
+                component = "kafka",
+                topic = %in_flight.record.topic,
+                error = %e,
+                "Kafka delivery failed, spooling for replay"
+            );
+        } else {
+            debug!(
+                component = "kafka",
+                topic = %in_flight.record.topic,
+                error = %e,
+                "Kafka delivery failed"
+            );
+        }
+        if let Err(e) = self.spool_record(&in_flight.record).await {
+            error!(component = "spool", error = %e, "Failed to spool undelivered record");
+        }
+    }
+
+    /// Publish a permanently failing record to the dead-letter topic so the stream moves on
+    async fn dead_letter(&self, record: &KafkaRecord, error: &MarketDataError, attempts: u32) -> Result<()> {
+        let dead_letter_topic = &self.config.dead_letter_topic;
+        if record.topic == *dead_letter_topic {
+            error!(
+                component = "kafka",
+                topic = %record.topic,
+                error = %error,
+                "Dropping record that failed permanently on the dead-letter topic"
+            );
+            return Ok(());
+        }
+
+        self.counters.dead_lettered.fetch_add(1, Ordering::Relaxed);
+        error!(
+            component = "kafka",
+            source_topic = %record.topic,
+            dead_letter_topic = %dead_letter_topic,
+            error_code = %dead_letter::error_code(error),
+            attempts,
+            error = %error,
+            "Sending record to dead-letter topic"
+        );
+
+        let dead_letter = dead_letter::dead_letter_record(dead_letter_topic, record, error, attempts);
+        match self.try_send(&dead_letter).await {
+            Err(e) if ErrorClass::of(&e) == ErrorClass::Retryable => self.spool_record(&dead_letter).await,
+            result => result,
+        }
+    }
+
+    /// Dead-letter an envelope that could not be encoded, carrying it as JSON
+    async fn dead_letter_unencodable(&self, envelope: &Envelope, error: MarketDataError) -> Result<()> {
+        let record = KafkaRecord {
+            topic: self.topic_for(&envelope.payload).clone(),
+            key: naming::kafka_key(&envelope.payload),
+            payload: serde_json::to_vec(envelope)?,
+            headers: headers::record_headers(envelope, CodecKind::Json, &self.config.headers),
         };
+        self.dead_letter(&record, &error, 0).await
+    }
 
-        let json_data = serde_json::to_string(data)
-            .map_err(|e| MarketDataError::JsonError(e))?;
+    /// Whether a new record should join the spool rather than go to Kafka directly.
+    ///
+    /// True until replay has emptied the spool, so new records never overtake spooled ones.
+    fn queue_behind_spool(&self) -> bool {
+        !self.spool.is_empty()
+    }
 
-        let record = FutureRecord::to(topic)
-            .key(&key)
-            .payload(&json_data);
+    async fn spool_record(&self, record: &KafkaRecord) -> Result<()> {
+        self.spool.append(record).await?;
+        self.counters.spooled.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
 
-        let timeout = Duration::from_millis(self.config.producer.send_timeout_ms);
-        match self.client.send(record, timeout).await {
-            Ok(_) => {
-                debug!("Successfully sent market data to {}", topic);
+    /// Topic, key, encoded payload and headers for an envelope
+    fn record_for(&self, envelope: &Envelope) -> Result<KafkaRecord> {
+        let topic = self.topic_for(&envelope.payload);
+        let codec = self.codec_for(topic)?;
+        Ok(KafkaRecord {
+            topic: topic.clone(),
+            key: naming::kafka_key(&envelope.payload),
+            payload: codec.encode(&envelope.published())?,
+            headers: headers::record_headers(envelope, codec.kind(), &self.config.headers),
+        })
+    }
+
+    fn topic_for(&self, data: &MarketData) -> &String {
+        match data {
+            MarketData::Orderbook(_) => &self.orderbook_topic,
+            MarketData::Trade(_) => &self.trade_topic,
+            MarketData::Ticker(_) => &self.ticker_topic,
+            // Deltas share the book topic and key so they stay ordered per instrument
+            MarketData::BookDelta(_) => &self.orderbook_topic,
+            MarketData::IndexPrice(_) => &self.index_price_topic,
+            MarketData::VolatilityIndex(_) => &self.volatility_index_topic,
+            MarketData::MarkPrice(_) => &self.mark_price_topic,
+            MarketData::EstimatedExpirationPrice(_) => &self.estimated_expiration_price_topic,
+            MarketData::InstrumentStatus(_) => &self.instrument_state_topic,
+            MarketData::PlatformState(_) => &self.platform_state_topic,
+            MarketData::Quote(_) => &self.quote_topic,
+            MarketData::Liquidation(_) => &self.liquidation_topic,
+            MarketData::FundingRate(_) => &self.funding_rate_topic,
+            MarketData::Settlement(_) => &self.settlement_topic,
+        }
+    }
+
+    /// Try to send a record once (may fail if broker is down)
+    async fn try_send(&self, record: &KafkaRecord) -> Result<()> {
+        match self.send_record(record).await {
+            Ok(()) => {
+                debug!("Successfully sent market data to {}", record.topic);
                 Ok(())
             }
-            Err((e, _)) => {
-                error!("Failed to send market data to {}: {}", topic, e);
-                Err(MarketDataError::KafkaError(e))
+            Err(e) => {
+                error!("Failed to send market data to {}: {}", record.topic, e);
+                Err(e)
             }
         }
     }
 
-    /// Get statistics about the Kafka producer
-    pub fn statistics(&self) -> String {
-        // This would return producer stats if rdkafka provides them
-        // For now, return a placeholder
-        "Kafka producer statistics not yet implemented".to_string()
+    /// Send once and wait for the delivery report, without logging the outcome
+    async fn send_record(&self, record: &KafkaRecord) -> Result<()> {
+        let timeout = Duration::from_millis(self.config.producer.send_timeout_ms);
+        self.client
+            .send(Self::future_record(record), timeout)
+            .await
+            .map(|_| ())
+            .map_err(|(e, _)| MarketDataError::KafkaError(e))
+    }
+
+    fn future_record(record: &KafkaRecord) -> FutureRecord<'_, String, Vec<u8>> {
+        let headers = record.headers.iter().fold(OwnedHeaders::new(), |headers, (key, value)| {
+            headers.insert(Header {
+                key: key.as_str(),
+                value: Some(value),
+            })
+        });
+
+        FutureRecord::to(&record.topic)
+            .key(&record.key)
+            .payload(&record.payload)
+            .headers(headers)
+    }
+
+    /// Deliver spooled records whenever there are any; runs for the producer's lifetime.
+    ///
+    /// Each batch of `replay_batch` records is sent at once and acknowledged up to the first
+    /// record that failed, so the cursor only moves over delivered records. Records after a
+    /// failure in the same batch are sent again on the next attempt, which can duplicate them.
+    async fn replay_spool(self) {
+        let initial_backoff = self.config.producer.initial_backoff_ms;
+        let batch_size = self.config.spool.replay_batch;
+        let mut backoff = initial_backoff;
+        // Replay attempts of the batch at the head of the spool
+        let mut attempts = 0;
+
+        loop {
+            let batch = match self.spool.read(batch_size).await {
+                Ok(batch) if batch.is_empty() => {
+                    self.spool.wait_for_records(Duration::from_secs(1)).await;
+                    continue;
+                }
+                Ok(batch) => batch,
+                Err(e) => {
+                    error!(component = "spool", error = %e, "Failed to read Kafka spool");
+                    sleep(Duration::from_millis(backoff)).await;
+                    backoff = (backoff * 2).min(MAX_REPLAY_BACKOFF_MS);
+                    continue;
+                }
+            };
+
+            attempts += 1;
+            let results = join_all(batch.iter().map(|spooled| self.send_record(&spooled.record))).await;
+
+            let mut delivered = 0;
+            let mut failure = None;
+            for (spooled, result) in batch.iter().zip(results) {
+                match result {
+                    Ok(()) => {}
+                    Err(e) if ErrorClass::of(&e) == ErrorClass::Permanent => {
+                        // Count the send that put the record in the spool too
+                        if let Err(e) = self.dead_letter(&spooled.record, &e, attempts + 1).await {
+                            // Keep the record and try again rather than lose it
+                            error!(component = "spool", error = %e, "Failed to dead-letter spooled record");
+                            failure = Some(e);
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        failure = Some(e);
+                        break;
+                    }
+                }
+                delivered += 1;
+            }
+
+            if let Err(e) = self.spool.ack(&batch[..delivered]).await {
+                error!(component = "spool", error = %e, "Failed to advance Kafka spool cursor");
+            }
+            if let Some(e) = failure {
+                debug!(
+                    component = "spool",
+                    delivered,
+                    error = %e,
+                    backoff_ms = backoff,
+                    "Spool replay send failed"
+                );
+                sleep(Duration::from_millis(backoff)).await;
+                backoff = (backoff * 2).min(MAX_REPLAY_BACKOFF_MS);
+                continue;
+            }
+
+            attempts = 0;
+            backoff = initial_backoff;
+            if self.spool.is_empty() {
+                info!(component = "spool", "Kafka spool drained, sending directly again");
+            }
+        }
+    }
+
+    /// Depth of the on-disk spool
+    pub fn spool_stats(&self) -> SpoolStats {
+        self.spool.stats()
+    }
+
+    pub fn delivery_stats(&self) -> DeliveryStats {
+        DeliveryStats {
+            in_flight: self.max_in_flight - self.in_flight.available_permits(),
+            enqueued: self.counters.enqueued.load(Ordering::Relaxed),
+            delivered: self.counters.delivered.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+            spooled: self.counters.spooled.load(Ordering::Relaxed),
+            dead_lettered: self.counters.dead_lettered.load(Ordering::Relaxed),
+        }
+    }
+
+    fn codec_for(&self, topic: &str) -> Result<&dyn Codec> {
+        self.codecs
+            .get(topic)
+            .map(|codec| codec.as_ref())
+            .ok_or_else(|| MarketDataError::ConfigError(format!("no codec for topic {}", topic)))
+    }
+
+    /// Latest librdkafka statistics; None before the first interval or when turned off
+    pub fn statistics(&self) -> Option<Arc<ProducerStatistics>> {
+        self.stats.latest()
     }
 
     /// Flush pending messages with timeout for graceful shutdown
@@ -160,6 +654,7 @@ impl KafkaProducer {
             "Flushing Kafka producer"
         );
 
+        let deadline = Instant::now() + timeout;
         self.client
             .flush(timeout)
             .map_err(|e| {
@@ -167,7 +662,85 @@ impl KafkaProducer {
                 MarketDataError::KafkaError(e)
             })?;
 
+        // Every report is in after the flush; give the handlers the rest of the budget to
+        // settle them, spooling or dead-lettering failures
+        let (done, drained) = oneshot::channel();
+        if self.deliveries.send(Delivery::Drain(done)).is_ok() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if tokio::time::timeout(remaining, drained).await.is_err() {
+                warn!(
+                    component = "kafka",
+                    in_flight = self.delivery_stats().in_flight,
+                    "Delivery reports still unsettled at the flush deadline"
+                );
+            }
+        }
+
         info!(component = "kafka", "Kafka flush completed successfully");
+
+        let spooled = self.spool.stats().records;
+        if spooled > 0 {
+            warn!(
+                component = "spool",
+                records = spooled,
+                "Records left in the Kafka spool will be replayed on the next start"
+            );
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_flight(slots: &Arc<Semaphore>, key: &str) -> InFlight {
+        InFlight {
+            record: KafkaRecord {
+                topic: "market.trades".to_string(),
+                key: key.to_string(),
+                payload: Vec::new(),
+                headers: Vec::new(),
+            },
+            _permit: slots.clone().try_acquire_owned().unwrap(),
+        }
+    }
+
+    fn report(in_flight: InFlight, result: Result<()>) -> Delivery {
+        Delivery::Pending(Box::pin(async move { (in_flight, result) }))
+    }
+
+    #[tokio::test]
+    async fn settles_deliveries_and_hands_failures_off() {
+        let slots = Arc::new(Semaphore::new(3));
+        let counters = Arc::new(DeliveryCounters::default());
+        let (deliveries, reports) = mpsc::unbounded_channel();
+        let (failures, mut failed) = mpsc::channel(4);
+        tokio::spawn(KafkaProducer::handle_deliveries(reports, counters.clone(), failures));
+
+        let canceled = MarketDataError::KafkaError(KafkaError::Canceled);
+        deliveries.send(report(in_flight(&slots, "a"), Ok(()))).unwrap();
+        deliveries.send(report(in_flight(&slots, "b"), Err(canceled))).unwrap();
+        deliveries.send(report(in_flight(&slots, "c"), Ok(()))).unwrap();
+        let (done, drained) = oneshot::channel();
+        deliveries.send(Delivery::Drain(done)).unwrap();
+
+        let Some(Failure::Undelivered(undelivered, _)) = failed.recv().await else {
+            panic!("expected the failed record first");
+        };
+        assert_eq!(undelivered.record.key, "b");
+        // The drain follows every failure and waits for the failure task to reach it
+        let Some(Failure::Drain(reached)) = failed.recv().await else {
+            panic!("expected the drain after the failure");
+        };
+        reached.send(()).unwrap();
+        drained.await.unwrap();
+
+        assert_eq!(counters.delivered.load(Ordering::Relaxed), 2);
+        assert_eq!(counters.failed.load(Ordering::Relaxed), 1);
+        // Delivered records released their slots; the failed one holds its slot until handled
+        assert_eq!(slots.available_permits(), 2);
+        drop(undelivered);
+        assert_eq!(slots.available_permits(), 3);
+    }
+}
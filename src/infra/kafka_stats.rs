@@ -0,0 +1,324 @@
+//! Typed view of the statistics JSON librdkafka emits every `statistics.interval.ms`.
+//! Only the producer-side fields are kept; see librdkafka's STATISTICS.md for the rest.
+//! Counters are totals since the client started, byte counts include protocol overhead.
+
+use rdkafka::ClientContext;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProducerStatistics {
+    /// librdkafka client instance name
+    pub name: String,
+    /// Wall clock time of the snapshot, seconds since the epoch
+    pub time: i64,
+    /// Microseconds since the client was created
+    pub age: i64,
+    /// Messages waiting in the producer queue, in memory or in flight
+    pub msg_cnt: u64,
+    pub msg_size: u64,
+    /// `queue.buffering.max.messages`; `msg_cnt` reaching it means enqueues fail
+    pub msg_max: u64,
+    pub msg_size_max: u64,
+    /// Requests sent to brokers
+    pub tx: u64,
+    pub tx_bytes: u64,
+    /// Responses received from brokers
+    pub rx: u64,
+    pub rx_bytes: u64,
+    /// Messages sent to brokers
+    pub txmsgs: u64,
+    pub txmsg_bytes: u64,
+    /// Keyed by `host:port/node_id`
+    pub brokers: BTreeMap<String, BrokerStatistics>,
+    pub topics: BTreeMap<String, TopicStatistics>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrokerStatistics {
+    pub nodeid: i32,
+    /// Connection state, `UP` when usable
+    pub state: String,
+    /// Requests waiting to be sent
+    pub outbuf_cnt: u64,
+    pub outbuf_msg_cnt: u64,
+    /// Requests sent and awaiting a response
+    pub waitresp_cnt: u64,
+    pub waitresp_msg_cnt: u64,
+    pub tx: u64,
+    pub txbytes: u64,
+    pub txerrs: u64,
+    pub txretries: u64,
+    pub req_timeouts: u64,
+    pub rx: u64,
+    pub rxbytes: u64,
+    pub rxerrs: u64,
+    pub connects: u64,
+    pub disconnects: u64,
+    /// Broker round-trip time, microseconds
+    pub rtt: Window,
+    /// Time produce requests waited in the output buffer, microseconds
+    pub outbuf_latency: Window,
+}
+
+/// Rolling window of a latency metric, reset every statistics interval
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Window {
+    pub min: i64,
+    pub max: i64,
+    pub avg: i64,
+    pub p50: i64,
+    pub p95: i64,
+    pub p99: i64,
+    /// Samples in the window
+    pub cnt: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TopicStatistics {
+    /// Keyed by partition id; `-1` holds messages not yet assigned a partition
+    pub partitions: BTreeMap<String, PartitionStatistics>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PartitionStatistics {
+    pub partition: i32,
+    /// Broker id of the leader, -1 when unknown
+    pub leader: i32,
+    /// Messages queued for the partition but not yet sent
+    pub msgq_cnt: u64,
+    pub msgq_bytes: u64,
+    /// Messages ready to go in the next produce request
+    pub xmit_msgq_cnt: u64,
+    pub xmit_msgq_bytes: u64,
+    /// Messages sent and awaiting acknowledgement
+    pub msgs_inflight: u64,
+    pub txmsgs: u64,
+    pub txbytes: u64,
+}
+
+impl ProducerStatistics {
+    /// Messages queued or in flight across every partition
+    pub fn partition_backlog(&self) -> u64 {
+        self.topics
+            .values()
+            .flat_map(|topic| topic.partitions.values())
+            .map(|p| p.msgq_cnt + p.xmit_msgq_cnt + p.msgs_inflight)
+            .sum()
+    }
+}
+
+/// Client context that keeps the latest statistics snapshot
+#[derive(Clone, Default)]
+pub struct StatsContext {
+    latest: Arc<Mutex<Option<Arc<ProducerStatistics>>>>,
+}
+
+impl StatsContext {
+    /// Latest snapshot; None until the first interval has elapsed or when statistics are off
+    pub fn latest(&self) -> Option<Arc<ProducerStatistics>> {
+        self.latest.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl ClientContext for StatsContext {
+    fn stats_raw(&self, statistics: &[u8]) {
+        match serde_json::from_slice::<ProducerStatistics>(statistics) {
+            Ok(statistics) => {
+                *self.latest.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(statistics))
+            }
+            Err(e) => warn!(component = "kafka", error = %e, "Failed to parse librdkafka statistics"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Producer statistics as emitted by librdkafka 2.x, trimmed to one broker and topic
+    const SAMPLE: &str = r#"{
+        "name": "rdkafka#producer-1",
+        "client_id": "market-data",
+        "type": "producer",
+        "ts": 5016483227792,
+        "time": 1700000000,
+        "age": 5000166,
+        "replyq": 0,
+        "msg_cnt": 23,
+        "msg_size": 11502,
+        "msg_max": 100000,
+        "msg_size_max": 1073741824,
+        "simple_cnt": 0,
+        "metadata_cache_cnt": 1,
+        "brokers": {
+            "localhost:9092/1": {
+                "name": "localhost:9092/1",
+                "nodeid": 1,
+                "nodename": "localhost:9092",
+                "source": "configured",
+                "state": "UP",
+                "stateage": 4998093,
+                "outbuf_cnt": 1,
+                "outbuf_msg_cnt": 5,
+                "waitresp_cnt": 2,
+                "waitresp_msg_cnt": 9,
+                "tx": 320,
+                "txbytes": 186404,
+                "txerrs": 0,
+                "txretries": 1,
+                "txidle": 1523,
+                "req_timeouts": 0,
+                "rx": 318,
+                "rxbytes": 23844,
+                "rxerrs": 0,
+                "rxcorriderrs": 0,
+                "rxpartial": 0,
+                "rxidle": 1481,
+                "zbuf_grow": 0,
+                "buf_grow": 0,
+                "wakeups": 712,
+                "connects": 1,
+                "disconnects": 0,
+                "int_latency": { "min": 4, "max": 3210, "avg": 58, "sum": 18560, "stddev": 201, "p50": 21, "p75": 33, "p90": 61, "p95": 110, "p99": 1503, "p99_99": 3210, "outofrange": 0, "hdrsize": 11376, "cnt": 320 },
+                "outbuf_latency": { "min": 2, "max": 145, "avg": 11, "sum": 3520, "stddev": 14, "p50": 8, "p75": 12, "p90": 19, "p95": 27, "p99": 88, "p99_99": 145, "outofrange": 0, "hdrsize": 11376, "cnt": 320 },
+                "rtt": { "min": 412, "max": 9850, "avg": 1210, "sum": 387200, "stddev": 730, "p50": 1002, "p75": 1390, "p90": 2011, "p95": 2559, "p99": 6143, "p99_99": 9850, "outofrange": 0, "hdrsize": 13424, "cnt": 320 },
+                "throttle": { "min": 0, "max": 0, "avg": 0, "sum": 0, "stddev": 0, "p50": 0, "p75": 0, "p90": 0, "p95": 0, "p99": 0, "p99_99": 0, "outofrange": 0, "hdrsize": 17520, "cnt": 0 },
+                "req": { "Produce": 316, "Metadata": 2, "ApiVersion": 1, "InitProducerId": 1 },
+                "toppars": {
+                    "market.trades-0": { "topic": "market.trades", "partition": 0 }
+                }
+            }
+        },
+        "topics": {
+            "market.trades": {
+                "topic": "market.trades",
+                "age": 4996310,
+                "metadata_age": 4996,
+                "batchsize": { "min": 512, "max": 16312, "avg": 2150, "sum": 688000, "stddev": 1900, "p50": 1620, "p75": 2400, "p90": 4020, "p95": 6010, "p99": 12030, "p99_99": 16312, "outofrange": 0, "hdrsize": 14448, "cnt": 320 },
+                "batchcnt": { "min": 1, "max": 40, "avg": 5, "sum": 1600, "stddev": 4, "p50": 4, "p75": 6, "p90": 10, "p95": 14, "p99": 30, "p99_99": 40, "outofrange": 0, "hdrsize": 8304, "cnt": 320 },
+                "partitions": {
+                    "0": {
+                        "partition": 0,
+                        "broker": 1,
+                        "leader": 1,
+                        "desired": false,
+                        "unknown": false,
+                        "msgq_cnt": 7,
+                        "msgq_bytes": 3402,
+                        "xmit_msgq_cnt": 5,
+                        "xmit_msgq_bytes": 2511,
+                        "fetchq_cnt": 0,
+                        "fetchq_size": 0,
+                        "fetch_state": "none",
+                        "query_offset": -1001,
+                        "next_offset": 0,
+                        "app_offset": -1001,
+                        "stored_offset": -1001,
+                        "committed_offset": -1001,
+                        "eof_offset": -1001,
+                        "lo_offset": -1001,
+                        "hi_offset": -1001,
+                        "consumer_lag": -1,
+                        "txmsgs": 1590,
+                        "txbytes": 795000,
+                        "rxmsgs": 0,
+                        "rxbytes": 0,
+                        "msgs": 1613,
+                        "rx_ver_drops": 0,
+                        "msgs_inflight": 9,
+                        "next_ack_seq": 0,
+                        "next_err_seq": 0,
+                        "acked_msgid": 0
+                    },
+                    "-1": {
+                        "partition": -1,
+                        "broker": -1,
+                        "leader": -1,
+                        "desired": false,
+                        "unknown": false,
+                        "msgq_cnt": 2,
+                        "msgq_bytes": 980,
+                        "xmit_msgq_cnt": 0,
+                        "xmit_msgq_bytes": 0,
+                        "msgs_inflight": 0,
+                        "txmsgs": 0,
+                        "txbytes": 0,
+                        "msgs": 2
+                    }
+                }
+            }
+        },
+        "tx": 320,
+        "tx_bytes": 186404,
+        "rx": 318,
+        "rx_bytes": 23844,
+        "txmsgs": 1590,
+        "txmsg_bytes": 795000,
+        "rxmsgs": 0,
+        "rxmsg_bytes": 0
+    }"#;
+
+    #[test]
+    fn parses_librdkafka_producer_statistics() {
+        let statistics: ProducerStatistics = serde_json::from_str(SAMPLE).unwrap();
+
+        assert_eq!(statistics.name, "rdkafka#producer-1");
+        assert_eq!(statistics.time, 1_700_000_000);
+        assert_eq!((statistics.msg_cnt, statistics.msg_max), (23, 100_000));
+        assert_eq!((statistics.txmsgs, statistics.txmsg_bytes), (1590, 795_000));
+
+        let broker = &statistics.brokers["localhost:9092/1"];
+        assert_eq!((broker.nodeid, broker.state.as_str()), (1, "UP"));
+        assert_eq!((broker.waitresp_cnt, broker.waitresp_msg_cnt), (2, 9));
+        assert_eq!(broker.txretries, 1);
+        assert_eq!((broker.rtt.p50, broker.rtt.p99, broker.rtt.cnt), (1002, 6143, 320));
+        assert_eq!(broker.outbuf_latency.max, 145);
+
+        let partitions = &statistics.topics["market.trades"].partitions;
+        assert_eq!(partitions["0"].leader, 1);
+        assert_eq!(partitions["-1"].partition, -1);
+        assert_eq!(partitions["0"].msgs_inflight, 9);
+    }
+
+    #[test]
+    fn backlog_counts_queued_and_in_flight_messages() {
+        let statistics: ProducerStatistics = serde_json::from_str(SAMPLE).unwrap();
+
+        // 7 queued + 5 ready + 9 in flight on partition 0, 2 still unassigned
+        assert_eq!(statistics.partition_backlog(), 23);
+    }
+
+    #[test]
+    fn context_keeps_the_last_good_snapshot() {
+        let context = StatsContext::default();
+        assert!(context.latest().is_none());
+
+        context.stats_raw(SAMPLE.as_bytes());
+        assert_eq!(context.latest().unwrap().msg_cnt, 23);
+
+        context.stats_raw(b"{ not json");
+        assert_eq!(context.latest().unwrap().msg_cnt, 23);
+    }
+
+    #[test]
+    fn context_survives_a_poisoned_lock() {
+        let context = StatsContext::default();
+        let poisoner = context.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.latest.lock().unwrap();
+            panic!("poison the statistics lock");
+        })
+        .join();
+
+        context.stats_raw(SAMPLE.as_bytes());
+        assert_eq!(context.latest().unwrap().msg_cnt, 23);
+    }
+}
@@ -2,7 +2,9 @@ use redis::aio::ConnectionManager;
 use redis::{AsyncCommands, Client};
 use crate::config::RedisConfig;
 use crate::errors::Result;
-use crate::exchanges::deribit::models::MarketData;
+use crate::infra::codec::Codec;
+use market_data_types::{naming, MarketData, TimestampFormat};
+use std::sync::Arc;
 use tracing::{info, warn, error};
 use std::time::Duration;
 use tokio::time::sleep;
@@ -12,10 +14,12 @@ pub struct RedisStorage {
     manager: ConnectionManager,
     url: String,
     config: RedisConfig,
+    codec: Arc<dyn Codec>,
 }
 
 impl RedisStorage {
-    pub async fn new(config: &RedisConfig) -> Result<Self> {
+    /// Connect, caching payloads with timestamps written in `timestamps`
+    pub async fn new(config: &RedisConfig, timestamps: TimestampFormat) -> Result<Self> {
         let client = Client::open(config.url.as_str())?;
         let manager = ConnectionManager::new(client).await?;
 
@@ -25,6 +29,7 @@ impl RedisStorage {
             manager,
             url: config.url.clone(),
             config: config.clone(),
+            codec: config.codec.codec(timestamps)?,
         })
     }
 
@@ -78,26 +83,13 @@ impl RedisStorage {
     async fn try_update_latest_data(&self, data: &MarketData) -> Result<()> {
         let mut con = self.manager.clone();
 
-        match data {
-            MarketData::Orderbook(ob) => {
-                // key: <venue>:<symbol>:orderbook
-                let key = format!("{}:{}:orderbook", ob.venue, ob.symbol);
-                let value = serde_json::to_string(ob)?;
-                con.set_ex::<_, _, ()>(&key, value, 3).await?; // 3 secs TTL
-            }
-            MarketData::Trade(trade) => {
-                // key: <venue>:<symbol>:last_trade
-                let key = format!("{}:{}:last_trade", trade.venue, trade.symbol);
-                let value = serde_json::to_string(trade)?;
-                con.set_ex::<_, _, ()>(&key, value, 60).await?; // 1 minute TTL
-            }
-            MarketData::Ticker(ticker) => {
-                // key: <venue>:<symbol>:ticker
-                let key = format!("{}:{}:ticker", ticker.venue, ticker.symbol);
-                let value = serde_json::to_string(ticker)?;
-                con.set_ex::<_, _, ()>(&key, value, 300).await?; // 5 min TTL
-            }
-        }
+        // Deltas are not a latest state on their own; nothing to cache
+        let Some(key) = naming::redis_key(data) else {
+            return Ok(());
+        };
+
+        let value = self.codec.encode_payload(&data.published())?;
+        con.set_ex::<_, _, ()>(&key, value, cache_ttl_secs(data)).await?;
 
         info!(component = "redis", "Updated Redis with latest market data");
         Ok(())
@@ -125,3 +117,21 @@ impl RedisStorage {
         info!(component = "redis", "Redis drain completed");
     }
 }
+
+/// How long the latest value of each kind stays cached
+fn cache_ttl_secs(data: &MarketData) -> u64 {
+    match data {
+        MarketData::Orderbook(_) | MarketData::BookDelta(_) | MarketData::Quote(_) => 3,
+        MarketData::Trade(_)
+        | MarketData::IndexPrice(_)
+        | MarketData::VolatilityIndex(_)
+        | MarketData::MarkPrice(_) => 60,
+        MarketData::Ticker(_)
+        | MarketData::EstimatedExpirationPrice(_)
+        | MarketData::FundingRate(_) => 300,
+        MarketData::Liquidation(_) => 3600,
+        MarketData::InstrumentStatus(_)
+        | MarketData::PlatformState(_)
+        | MarketData::Settlement(_) => 86400,
+    }
+}
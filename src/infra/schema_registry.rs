@@ -0,0 +1,430 @@
+use crate::config::SchemaRegistryConfig;
+use crate::errors::{MarketDataError, Result};
+use apache_avro::schema_compatibility::SchemaCompatibility;
+use apache_avro::Schema;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::info;
+
+/// URL scheme that selects the in-process registry
+pub const MOCK_REGISTRY_URL: &str = "mock://";
+
+/// Subject compatibility levels we set; named as the registry spells them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Compatibility {
+    #[default]
+    Backward,
+    BackwardTransitive,
+    Full,
+    None,
+}
+
+impl Compatibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Compatibility::Backward => "BACKWARD",
+            Compatibility::BackwardTransitive => "BACKWARD_TRANSITIVE",
+            Compatibility::Full => "FULL",
+            Compatibility::None => "NONE",
+        }
+    }
+}
+
+/// Subject of a topic's record values under the default `TopicNameStrategy`
+pub fn value_subject(topic: &str) -> String {
+    format!("{}-value", topic)
+}
+
+/// The subset of the Confluent Schema Registry API the producer uses
+#[async_trait]
+pub trait SchemaRegistry: Send + Sync {
+    async fn set_compatibility(&self, subject: &str, level: Compatibility) -> Result<()>;
+
+    /// Register `schema` under `subject`, or return its id if already registered.
+    /// Fails when the schema breaks the subject's compatibility level.
+    async fn register(&self, subject: &str, schema: &Schema) -> Result<u32>;
+
+    async fn schema_by_id(&self, id: u32) -> Result<Schema>;
+}
+
+/// Registry selected by `schema_registry.url`
+pub fn connect(config: &SchemaRegistryConfig) -> Arc<dyn SchemaRegistry> {
+    if config.url.starts_with(MOCK_REGISTRY_URL) {
+        info!(component = "schema_registry", "Using in-process mock schema registry");
+        Arc::new(MockSchemaRegistry::new())
+    } else {
+        Arc::new(HttpSchemaRegistry::new(config))
+    }
+}
+
+fn registry_error(message: impl Into<String>) -> MarketDataError {
+    MarketDataError::SchemaRegistryError(message.into())
+}
+
+fn parse_schema(text: &str) -> Result<Schema> {
+    Schema::parse_str(text).map_err(|e| registry_error(format!("invalid schema: {}", e)))
+}
+
+/// Full JSON of `schema`; unlike `canonical_form` it keeps defaults, docs and logical types
+fn schema_json(schema: &Schema) -> Result<String> {
+    serde_json::to_string(schema).map_err(|e| registry_error(format!("invalid schema: {}", e)))
+}
+
+#[derive(Deserialize)]
+struct RegisterResponse {
+    id: u32,
+}
+
+#[derive(Deserialize)]
+struct SchemaResponse {
+    schema: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    #[serde(default)]
+    error_code: u32,
+    #[serde(default)]
+    message: String,
+}
+
+/// Client for a Confluent-compatible registry over its REST API
+pub struct HttpSchemaRegistry {
+    http: reqwest::Client,
+    base_url: String,
+    credentials: Option<(String, String)>,
+}
+
+impl HttpSchemaRegistry {
+    pub fn new(config: &SchemaRegistryConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: config.url.trim_end_matches('/').to_string(),
+            credentials: config.username.clone().zip(config.password.clone()),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path))
+            .header(reqwest::header::ACCEPT, "application/vnd.schemaregistry.v1+json");
+        match &self.credentials {
+            Some((user, password)) => request.basic_auth(user, Some(password)),
+            None => request,
+        }
+    }
+
+    async fn send<T: serde::de::DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response.json().await?);
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        let detail = serde_json::from_str::<ErrorResponse>(&body)
+            .map(|e| format!("{} (error_code {})", e.message, e.error_code))
+            .unwrap_or(body);
+        Err(registry_error(format!("{}: {}", status, detail)))
+    }
+}
+
+#[async_trait]
+impl SchemaRegistry for HttpSchemaRegistry {
+    async fn set_compatibility(&self, subject: &str, level: Compatibility) -> Result<()> {
+        let request = self
+            .request(reqwest::Method::PUT, &format!("/config/{}", subject))
+            .json(&json!({ "compatibility": level.as_str() }));
+        self.send::<serde_json::Value>(request).await?;
+        Ok(())
+    }
+
+    async fn register(&self, subject: &str, schema: &Schema) -> Result<u32> {
+        // The registry rejects incompatible schemas with 409
+        let request = self
+            .request(reqwest::Method::POST, &format!("/subjects/{}/versions", subject))
+            .json(&json!({ "schemaType": "AVRO", "schema": schema_json(schema)? }));
+        let response: RegisterResponse = self.send(request).await?;
+        Ok(response.id)
+    }
+
+    async fn schema_by_id(&self, id: u32) -> Result<Schema> {
+        let request = self.request(reqwest::Method::GET, &format!("/schemas/ids/{}", id));
+        let response: SchemaResponse = self.send(request).await?;
+        parse_schema(&response.schema)
+    }
+}
+
+struct Registered {
+    /// Compared to tell whether a schema is already registered
+    canonical: String,
+    /// Full JSON as submitted, served back by `schema_by_id`
+    text: String,
+}
+
+#[derive(Default)]
+struct MockState {
+    /// Every registered schema, indexed by id - 1
+    schemas: Vec<Registered>,
+    /// Schema ids per subject, oldest first
+    subjects: HashMap<String, Vec<u32>>,
+    compatibility: HashMap<String, Compatibility>,
+}
+
+/// In-process registry for tests and local runs without a registry (`url = "mock://"`).
+///
+/// Assigns ids and enforces compatibility the way the real registry does, minus persistence.
+#[derive(Default)]
+pub struct MockSchemaRegistry {
+    state: Mutex<MockState>,
+}
+
+impl MockSchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Versions registered under `subject`, oldest first
+    pub fn versions(&self, subject: &str) -> Vec<u32> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.subjects.get(subject).cloned().unwrap_or_default()
+    }
+
+    fn check_compatible(
+        state: &MockState,
+        subject: &str,
+        versions: &[u32],
+        schema: &Schema,
+    ) -> Result<()> {
+        let level = state.compatibility.get(subject).copied().unwrap_or_default();
+        let previous: Vec<&u32> = match level {
+            Compatibility::None => return Ok(()),
+            Compatibility::Backward | Compatibility::Full => versions.last().into_iter().collect(),
+            Compatibility::BackwardTransitive => versions.iter().collect(),
+        };
+
+        for id in previous {
+            let existing = parse_schema(&state.schemas[*id as usize - 1].text)?;
+            // Backward: consumers on the new schema must read data written with the old one
+            let mut compatible = SchemaCompatibility::can_read(&existing, schema).is_ok();
+            if level == Compatibility::Full {
+                compatible &= SchemaCompatibility::can_read(schema, &existing).is_ok();
+            }
+            if !compatible {
+                return Err(registry_error(format!(
+                    "409 Conflict: schema is incompatible with schema {} under subject {} ({})",
+                    id,
+                    subject,
+                    level.as_str()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SchemaRegistry for MockSchemaRegistry {
+    async fn set_compatibility(&self, subject: &str, level: Compatibility) -> Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.compatibility.insert(subject.to_string(), level);
+        Ok(())
+    }
+
+    async fn register(&self, subject: &str, schema: &Schema) -> Result<u32> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let canonical = schema.canonical_form();
+        let versions = state.subjects.get(subject).cloned().unwrap_or_default();
+
+        if let Some(id) = versions
+            .iter()
+            .find(|id| state.schemas[**id as usize - 1].canonical == canonical)
+        {
+            return Ok(*id);
+        }
+        Self::check_compatible(&state, subject, &versions, schema)?;
+
+        // Ids are global, so the same schema under another subject keeps its id
+        let id = match state.schemas.iter().position(|s| s.canonical == canonical) {
+            Some(index) => index as u32 + 1,
+            None => {
+                let text = schema_json(schema)?;
+                state.schemas.push(Registered { canonical, text });
+                state.schemas.len() as u32
+            }
+        };
+        state.subjects.entry(subject.to_string()).or_default().push(id);
+        Ok(id)
+    }
+
+    async fn schema_by_id(&self, id: u32) -> Result<Schema> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let registered = id
+            .checked_sub(1)
+            .and_then(|index| state.schemas.get(index as usize))
+            .ok_or_else(|| registry_error(format!("404 Not Found: schema {} not found", id)))?;
+        parse_schema(&registered.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::codec::AVRO_SCHEMA;
+
+    const SUBJECT: &str = "market.trades-value";
+    const PRICE: &str = r#"{"name": "price", "type": "double"}"#;
+    const AMOUNT: &str = r#"{"name": "amount", "type": "double"}"#;
+    const AMOUNT_WITH_DEFAULT: &str = r#"{"name": "amount", "type": "double", "default": 0.0}"#;
+    const PRICE_AS_STRING: &str = r#"{"name": "price", "type": "string", "default": ""}"#;
+
+    fn record(fields: &str) -> Schema {
+        let text = format!(r#"{{"type": "record", "name": "Trade", "fields": [{}]}}"#, fields);
+        Schema::parse_str(&text).unwrap()
+    }
+
+    /// `AVRO_SCHEMA` with a defaulted `venue_sequence` field added to the trade record
+    fn evolved_market_data_schema() -> Schema {
+        let mut json: serde_json::Value = serde_json::from_str(AVRO_SCHEMA).unwrap();
+        let payload = json["fields"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|field| field["name"] == "payload")
+            .unwrap();
+        let trade = payload["type"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|record| record["name"] == "TradeSnapshot")
+            .unwrap();
+        trade["fields"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "name": "venue_sequence", "type": ["null", "long"], "default": null }));
+        Schema::parse(&json).unwrap()
+    }
+
+    async fn registry_with(level: Compatibility) -> MockSchemaRegistry {
+        let registry = MockSchemaRegistry::new();
+        registry.set_compatibility(SUBJECT, level).await.unwrap();
+        registry
+    }
+
+    #[tokio::test]
+    async fn registering_again_returns_the_same_id() {
+        let registry = registry_with(Compatibility::Backward).await;
+        let schema = record(PRICE);
+
+        let id = registry.register(SUBJECT, &schema).await.unwrap();
+        assert_eq!(registry.register(SUBJECT, &schema).await.unwrap(), id);
+        assert_eq!(registry.versions(SUBJECT), vec![id]);
+
+        // Ids are global across subjects
+        assert_eq!(registry.register("market.ticker-value", &schema).await.unwrap(), id);
+        assert_eq!(
+            registry.schema_by_id(id).await.unwrap().canonical_form(),
+            schema.canonical_form()
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_ids_are_not_found() {
+        let registry = MockSchemaRegistry::new();
+        assert!(registry.schema_by_id(0).await.is_err());
+        assert!(registry.schema_by_id(1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn backward_rejects_a_new_field_without_default() {
+        let registry = registry_with(Compatibility::Backward).await;
+        registry.register(SUBJECT, &record(PRICE)).await.unwrap();
+
+        let added = record(&format!("{}, {}", PRICE, AMOUNT));
+        assert!(registry.register(SUBJECT, &added).await.is_err());
+        assert_eq!(registry.versions(SUBJECT).len(), 1);
+
+        let defaulted = record(&format!("{}, {}", PRICE, AMOUNT_WITH_DEFAULT));
+        registry.register(SUBJECT, &defaulted).await.unwrap();
+        assert_eq!(registry.versions(SUBJECT).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn full_also_rejects_removing_a_field_without_default() {
+        let both = record(&format!("{}, {}", PRICE, AMOUNT));
+        let price_only = record(PRICE);
+
+        // New readers ignore the removed field, so backward allows it
+        let registry = registry_with(Compatibility::Backward).await;
+        registry.register(SUBJECT, &both).await.unwrap();
+        registry.register(SUBJECT, &price_only).await.unwrap();
+
+        // Old readers still need it
+        let registry = registry_with(Compatibility::Full).await;
+        registry.register(SUBJECT, &both).await.unwrap();
+        assert!(registry.register(SUBJECT, &price_only).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn backward_transitive_checks_every_version() {
+        let first = record(PRICE);
+        let second = record(AMOUNT_WITH_DEFAULT);
+        // Readable from the second version, which has no price, but not from the first
+        let third = record(&format!("{}, {}", AMOUNT_WITH_DEFAULT, PRICE_AS_STRING));
+
+        let registry = registry_with(Compatibility::Backward).await;
+        for schema in [&first, &second, &third] {
+            registry.register(SUBJECT, schema).await.unwrap();
+        }
+
+        let registry = registry_with(Compatibility::BackwardTransitive).await;
+        registry.register(SUBJECT, &first).await.unwrap();
+        registry.register(SUBJECT, &second).await.unwrap();
+        assert!(registry.register(SUBJECT, &third).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn none_accepts_anything() {
+        let registry = registry_with(Compatibility::None).await;
+        registry.register(SUBJECT, &record(PRICE)).await.unwrap();
+        registry.register(SUBJECT, &record(AMOUNT)).await.unwrap();
+        assert_eq!(registry.versions(SUBJECT).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn registered_schemas_keep_defaults_and_docs() {
+        let registry = registry_with(Compatibility::Backward).await;
+        let id = registry.register(SUBJECT, &record(AMOUNT_WITH_DEFAULT)).await.unwrap();
+
+        let fetched = serde_json::to_value(registry.schema_by_id(id).await.unwrap()).unwrap();
+        assert_eq!(fetched["fields"][0]["default"], 0.0);
+
+        let market_data = Schema::parse_str(AVRO_SCHEMA).unwrap();
+        let id = registry.register("market.index-value", &market_data).await.unwrap();
+        let fetched = serde_json::to_value(registry.schema_by_id(id).await.unwrap()).unwrap();
+        assert!(fetched["doc"].as_str().unwrap().starts_with("Enveloped MarketData message"));
+    }
+
+    #[tokio::test]
+    async fn market_data_schema_evolves_with_a_defaulted_field() {
+        let current = Schema::parse_str(AVRO_SCHEMA).unwrap();
+        let evolved = evolved_market_data_schema();
+
+        let registry = registry_with(Compatibility::Backward).await;
+        let first = registry.register(SUBJECT, &current).await.unwrap();
+        let second = registry.register(SUBJECT, &evolved).await.unwrap();
+        assert_ne!(first, second);
+        assert_eq!(registry.versions(SUBJECT), vec![first, second]);
+
+        // Rolling back needs the stored evolved schema to still carry its default
+        let registry = registry_with(Compatibility::Full).await;
+        registry.register(SUBJECT, &evolved).await.unwrap();
+        registry.register(SUBJECT, &current).await.unwrap();
+        assert_eq!(registry.versions(SUBJECT).len(), 2);
+    }
+}
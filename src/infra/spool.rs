@@ -0,0 +1,922 @@
+use crate::config::SpoolConfig;
+use crate::errors::{MarketDataError, Result};
+use market_data_types::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+const SEGMENT_EXTENSION: &str = "seg";
+const CURSOR_FILE: &str = "cursor";
+
+/// Body length and CRC32 in front of every record
+const FRAME_HEADER_LEN: u64 = 8;
+
+/// What `append` does with a record that would take the spool past `max_bytes`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Delete the oldest segment to make room
+    #[default]
+    DropOldest,
+    /// Discard the incoming record
+    DropNewest,
+    /// Wait until replay frees space; stalls the stream that is sending
+    Block,
+}
+
+impl OverflowPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverflowPolicy::DropOldest => "drop_oldest",
+            OverflowPolicy::DropNewest => "drop_newest",
+            OverflowPolicy::Block => "block",
+        }
+    }
+}
+
+/// A Kafka record ready to produce, as written to the spool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KafkaRecord {
+    pub topic: String,
+    pub key: String,
+    pub payload: Vec<u8>,
+    pub headers: Vec<(String, Vec<u8>)>,
+}
+
+/// A record read back from the spool, acknowledged with `Spool::ack` once delivered
+#[derive(Debug, Clone)]
+pub struct SpooledRecord {
+    pub record: KafkaRecord,
+    pub spooled_at: Timestamp,
+    segment: u64,
+    /// Offsets of the frame within its segment
+    start: u64,
+    end: u64,
+}
+
+/// Spool depth as reported by the health endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct SpoolStats {
+    /// Records waiting to be delivered
+    pub records: u64,
+    /// Bytes of undelivered records on disk
+    pub bytes: u64,
+    pub segments: usize,
+    /// Upper bound on the age of the oldest waiting record
+    pub oldest_age_ms: Option<i64>,
+    /// Records discarded by the overflow policy since startup
+    pub dropped: u64,
+    /// Records discarded for exceeding `max_age_secs` since startup
+    pub expired: u64,
+}
+
+#[derive(Debug)]
+struct Segment {
+    id: u64,
+    /// Valid bytes in the file, delivered or not
+    bytes: u64,
+    /// Undelivered records
+    records: u64,
+    oldest: Timestamp,
+    newest: Timestamp,
+}
+
+#[derive(Debug)]
+struct SpoolState {
+    /// Oldest first; the last segment is the one appended to
+    segments: VecDeque<Segment>,
+    /// Offset of the next undelivered record in the front segment
+    read_offset: u64,
+    writer: Option<File>,
+    next_id: u64,
+    /// Set while records are being discarded, so the overflow is logged once
+    overflowing: bool,
+}
+
+impl SpoolState {
+    fn records(&self) -> u64 {
+        self.segments.iter().map(|s| s.records).sum()
+    }
+
+    fn pending_bytes(&self) -> u64 {
+        self.segments.iter().map(|s| s.bytes).sum::<u64>() - self.read_offset
+    }
+}
+
+/// Append-only write-ahead log of Kafka records that could not be delivered.
+///
+/// Records are framed as `[len u32][crc32 u32][body]` in numbered segment files; `cursor`
+/// holds the segment and offset of the next undelivered record. Delivery is at least once:
+/// a record sent just before a crash may be replayed again after restart.
+///
+/// File reads, writes and fsyncs run on the blocking pool, so the async methods never stall
+/// a runtime worker; only `stats` takes the state lock from the caller's thread.
+pub struct Spool {
+    dir: PathBuf,
+    config: SpoolConfig,
+    state: Mutex<SpoolState>,
+    /// Undelivered records, readable without waiting on the state lock
+    pending: AtomicU64,
+    /// Signalled on every append, wakes the replay task
+    appended: Notify,
+    /// Signalled whenever delivered or dropped records free space
+    freed: Notify,
+    dropped: AtomicU64,
+    expired: AtomicU64,
+}
+
+impl Spool {
+    /// Open the spool in `config.dir`, recovering what a previous run left behind
+    pub fn open(config: &SpoolConfig) -> Result<Self> {
+        let dir = PathBuf::from(&config.dir);
+        fs::create_dir_all(&dir)?;
+
+        let (cursor_segment, cursor_offset) = read_cursor(&dir)?;
+        let mut segments = VecDeque::new();
+        let mut read_offset = 0;
+
+        for id in segment_ids(&dir)? {
+            let path = segment_path(&dir, id);
+            if id < cursor_segment {
+                // Fully delivered before the last shutdown
+                fs::remove_file(&path)?;
+                continue;
+            }
+            let start = if id == cursor_segment { cursor_offset } else { 0 };
+            let segment = recover_segment(&path, id, start)?;
+            if segments.is_empty() {
+                read_offset = start.min(segment.bytes);
+            }
+            segments.push_back(segment);
+        }
+
+        let next_id = segments.back().map_or(cursor_segment, |s| s.id) + 1;
+        let spool = Self {
+            dir,
+            config: config.clone(),
+            state: Mutex::new(SpoolState {
+                segments,
+                read_offset,
+                writer: None,
+                next_id,
+                overflowing: false,
+            }),
+            pending: AtomicU64::new(0),
+            appended: Notify::new(),
+            freed: Notify::new(),
+            dropped: AtomicU64::new(0),
+            expired: AtomicU64::new(0),
+        };
+
+        spool.sync_pending(&spool.state());
+        let stats = spool.stats();
+        info!(
+            component = "spool",
+            dir = %spool.dir.display(),
+            records = stats.records,
+            bytes = stats.bytes,
+            overflow = spool.config.overflow.as_str(),
+            "Opened Kafka spool"
+        );
+        Ok(spool)
+    }
+
+    fn state(&self) -> MutexGuard<'_, SpoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run `f` on the blocking pool, where file I/O cannot hold up the async runtime
+    async fn blocking<T, F>(self: &Arc<Self>, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Spool) -> Result<T> + Send + 'static,
+    {
+        let spool = self.clone();
+        tokio::task::spawn_blocking(move || f(&spool))
+            .await
+            .map_err(|e| spool_error(format!("spool task failed: {}", e)))?
+    }
+
+    /// Publish the record count after the state changed; call with the lock still held
+    fn sync_pending(&self, state: &SpoolState) {
+        self.pending.store(state.records(), Ordering::Relaxed);
+    }
+
+    /// Undelivered records
+    pub fn records(&self) -> u64 {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records() == 0
+    }
+
+    pub fn stats(&self) -> SpoolStats {
+        let state = self.state();
+        let now = Timestamp::now();
+        SpoolStats {
+            records: state.records(),
+            bytes: state.pending_bytes(),
+            segments: state.segments.len(),
+            oldest_age_ms: state
+                .segments
+                .iter()
+                .find(|s| s.records > 0)
+                .map(|s| now.as_millis() - s.oldest.as_millis()),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            expired: self.expired.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Store a record durably; what happens when the spool is full is set by `overflow`
+    pub async fn append(self: &Arc<Self>, record: &KafkaRecord) -> Result<()> {
+        let spooled_at = Timestamp::now();
+        let frame = Arc::new(encode_frame(record, spooled_at));
+
+        loop {
+            // Registered before trying so a free between the attempt and the wait is not missed
+            let freed = self.freed.notified();
+            let attempt = frame.clone();
+            if self.blocking(move |spool| spool.try_append(&attempt, spooled_at)).await? {
+                self.appended.notify_one();
+                return Ok(());
+            }
+            freed.await;
+        }
+    }
+
+    /// False when the record has to wait for space
+    fn try_append(&self, frame: &[u8], spooled_at: Timestamp) -> Result<bool> {
+        let mut state = self.state();
+        let appended = self.write_frame(&mut state, frame, spooled_at);
+        self.sync_pending(&state);
+        appended
+    }
+
+    fn write_frame(&self, state: &mut SpoolState, frame: &[u8], spooled_at: Timestamp) -> Result<bool> {
+        let len = frame.len() as u64;
+
+        if state.pending_bytes() + len > self.config.max_bytes {
+            let policy = if len > self.config.max_bytes {
+                // Could never fit, whatever is dropped
+                OverflowPolicy::DropNewest
+            } else {
+                self.config.overflow
+            };
+            if !state.overflowing {
+                state.overflowing = true;
+                warn!(
+                    component = "spool",
+                    max_bytes = self.config.max_bytes,
+                    overflow = policy.as_str(),
+                    "Kafka spool is full"
+                );
+            }
+            match policy {
+                OverflowPolicy::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Ok(true);
+                }
+                OverflowPolicy::Block => return Ok(false),
+                OverflowPolicy::DropOldest => self.drop_oldest(state, len)?,
+            }
+        } else if state.overflowing {
+            state.overflowing = false;
+            info!(component = "spool", "Kafka spool has room again");
+        }
+
+        let roll = state
+            .segments
+            .back()
+            .is_none_or(|s| s.bytes + len > self.config.segment_bytes && s.bytes > 0);
+        if roll || state.writer.is_none() {
+            self.open_writer(state, roll)?;
+        }
+
+        let writer = state.writer.as_mut().expect("writer opened above");
+        writer.write_all(frame)?;
+        if self.config.fsync {
+            writer.sync_data()?;
+        }
+
+        let segment = state.segments.back_mut().expect("segment opened above");
+        if segment.records == 0 {
+            segment.oldest = spooled_at;
+        }
+        segment.bytes += len;
+        segment.records += 1;
+        segment.newest = spooled_at;
+        Ok(true)
+    }
+
+    /// Open the last segment for appending, or a new one when `roll` is set
+    fn open_writer(&self, state: &mut SpoolState, roll: bool) -> Result<()> {
+        if roll {
+            let id = state.next_id;
+            state.next_id += 1;
+            state.segments.push_back(Segment {
+                id,
+                bytes: 0,
+                records: 0,
+                oldest: Timestamp::now(),
+                newest: Timestamp::now(),
+            });
+        }
+        let id = state.segments.back().map(|s| s.id).expect("at least one segment");
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(segment_path(&self.dir, id))?;
+        state.writer = Some(file);
+        Ok(())
+    }
+
+    /// Delete segments from the front until `needed` more bytes fit
+    fn drop_oldest(&self, state: &mut SpoolState, needed: u64) -> Result<()> {
+        while state.pending_bytes() + needed > self.config.max_bytes {
+            if state.segments.len() == 1 {
+                // The oldest data is in the segment being written; start a new one first
+                self.open_writer(state, true)?;
+            }
+            let Some(segment) = self.remove_front(state)? else {
+                break;
+            };
+            self.dropped.fetch_add(segment.records, Ordering::Relaxed);
+            warn!(
+                component = "spool",
+                segment = segment.id,
+                records = segment.records,
+                "Dropped oldest spool segment to make room"
+            );
+        }
+        Ok(())
+    }
+
+    fn remove_front(&self, state: &mut SpoolState) -> Result<Option<Segment>> {
+        let Some(segment) = state.segments.pop_front() else {
+            return Ok(None);
+        };
+        fs::remove_file(segment_path(&self.dir, segment.id))?;
+        state.read_offset = 0;
+        if let Some(front) = state.segments.front() {
+            self.write_cursor(front.id, 0)?;
+        }
+        self.freed.notify_waiters();
+        Ok(Some(segment))
+    }
+
+    /// Up to `max` of the oldest undelivered records, in order, skipping those older than
+    /// `max_age_secs`. Empty when nothing is waiting; a batch never spans two segments.
+    pub async fn read(self: &Arc<Self>, max: usize) -> Result<Vec<SpooledRecord>> {
+        self.blocking(move |spool| {
+            let mut state = spool.state();
+            let batch = spool.read_locked(&mut state, max.max(1));
+            spool.sync_pending(&state);
+            batch
+        })
+        .await
+    }
+
+    fn read_locked(&self, state: &mut SpoolState, max: usize) -> Result<Vec<SpooledRecord>> {
+        let max_age_nanos = Duration::from_secs(self.config.max_age_secs).as_nanos() as i64;
+        let expired_before = Timestamp::now().as_nanos() - max_age_nanos;
+
+        loop {
+            let Some(front) = state.segments.front() else {
+                return Ok(Vec::new());
+            };
+            let is_write_segment = state.segments.len() == 1;
+
+            if front.records == 0 {
+                if is_write_segment {
+                    return Ok(Vec::new());
+                }
+                self.remove_front(state)?;
+                continue;
+            }
+            if !is_write_segment && front.newest.as_nanos() < expired_before {
+                let segment = self.remove_front(state)?.expect("front checked above");
+                self.note_expired(segment.records);
+                continue;
+            }
+
+            let id = front.id;
+            let count = max.min(front.records as usize);
+            let mut batch = read_frames(&segment_path(&self.dir, id), id, state.read_offset, count)?;
+
+            // Records are oldest first, so expired ones are all at the head
+            let expired = batch
+                .iter()
+                .take_while(|spooled| spooled.spooled_at.as_nanos() < expired_before)
+                .count();
+            if expired > 0 {
+                let end = batch[expired - 1].end;
+                let front = state.segments.front_mut().expect("front checked above");
+                front.records -= expired as u64;
+                state.read_offset = end;
+                self.write_cursor(id, end)?;
+                self.note_expired(expired as u64);
+                batch.drain(..expired);
+            }
+            if !batch.is_empty() {
+                return Ok(batch);
+            }
+        }
+    }
+
+    /// Mark records returned by `read` as delivered; they must be a prefix of that batch
+    pub async fn ack(self: &Arc<Self>, delivered: &[SpooledRecord]) -> Result<()> {
+        let (Some(first), Some(last)) = (delivered.first(), delivered.last()) else {
+            return Ok(());
+        };
+        let (segment, start, end) = (first.segment, first.start, last.end);
+        let (records, spooled_at) = (delivered.len() as u64, last.spooled_at);
+
+        let acked = self
+            .blocking(move |spool| spool.advance(segment, start, end, records, spooled_at))
+            .await?;
+        if acked {
+            self.freed.notify_waiters();
+        }
+        Ok(())
+    }
+
+    /// Move the cursor from `start` past `records` delivered records ending at `end`; false
+    /// when the cursor is elsewhere, e.g. the segment was dropped while they were in flight
+    fn advance(
+        &self,
+        segment: u64,
+        start: u64,
+        end: u64,
+        records: u64,
+        spooled_at: Timestamp,
+    ) -> Result<bool> {
+        let mut guard = self.state();
+        let state = &mut *guard;
+        match state.segments.front_mut() {
+            Some(front) if front.id == segment && state.read_offset == start => {
+                front.records -= records;
+                front.oldest = spooled_at;
+                state.read_offset = end;
+            }
+            _ => return Ok(false),
+        }
+        self.sync_pending(state);
+        self.write_cursor(segment, end)?;
+        Ok(true)
+    }
+
+    /// Wait until something is appended, or `timeout` passes
+    pub async fn wait_for_records(&self, timeout: Duration) {
+        let _ = tokio::time::timeout(timeout, self.appended.notified()).await;
+    }
+
+    fn note_expired(&self, records: u64) {
+        self.expired.fetch_add(records, Ordering::Relaxed);
+        warn!(
+            component = "spool",
+            records,
+            max_age_secs = self.config.max_age_secs,
+            "Discarded spooled records older than the age limit"
+        );
+        self.freed.notify_waiters();
+    }
+
+    /// Replace the cursor file atomically, so a crash leaves the old or the new position
+    fn write_cursor(&self, segment: u64, offset: u64) -> Result<()> {
+        let tmp = self.dir.join(format!("{}.tmp", CURSOR_FILE));
+        let mut file = File::create(&tmp)?;
+        write!(file, "{} {}", segment, offset)?;
+        if self.config.fsync {
+            file.sync_data()?;
+        }
+        fs::rename(&tmp, self.dir.join(CURSOR_FILE))?;
+        Ok(())
+    }
+}
+
+fn spool_error(message: impl Into<String>) -> MarketDataError {
+    MarketDataError::SpoolError(message.into())
+}
+
+fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", id, SEGMENT_EXTENSION))
+}
+
+/// Ids of the segment files in `dir`, oldest first
+fn segment_ids(dir: &Path) -> Result<Vec<u64>> {
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+        if let Some(id) = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse().ok()) {
+            ids.push(id);
+        }
+    }
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+fn read_cursor(dir: &Path) -> Result<(u64, u64)> {
+    let text = match fs::read_to_string(dir.join(CURSOR_FILE)) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+        Err(e) => return Err(e.into()),
+    };
+    let mut parts = text.split_whitespace().map(str::parse::<u64>);
+    match (parts.next(), parts.next()) {
+        (Some(Ok(segment)), Some(Ok(offset))) => Ok((segment, offset)),
+        _ => Err(spool_error(format!("invalid cursor file: {:?}", text))),
+    }
+}
+
+/// Scan a segment, counting the records from `start` and cutting off a torn tail
+fn recover_segment(path: &Path, id: u64, start: u64) -> Result<Segment> {
+    let bytes = fs::read(path)?;
+    let mut offset = 0u64;
+    let mut segment = Segment {
+        id,
+        bytes: 0,
+        records: 0,
+        oldest: Timestamp::now(),
+        newest: Timestamp::now(),
+    };
+
+    while let Some((body, end)) = frame_at(&bytes, offset) {
+        let Ok((_, spooled_at)) = decode_body(body) else {
+            break;
+        };
+        if offset >= start {
+            if segment.records == 0 {
+                segment.oldest = spooled_at;
+            }
+            segment.records += 1;
+            segment.newest = spooled_at;
+        }
+        offset = end;
+    }
+
+    if offset < bytes.len() as u64 {
+        warn!(
+            component = "spool",
+            segment = id,
+            valid_bytes = offset,
+            discarded_bytes = bytes.len() as u64 - offset,
+            "Truncating incomplete spool segment"
+        );
+        OpenOptions::new().write(true).open(path)?.set_len(offset)?;
+    }
+    segment.bytes = offset;
+    Ok(segment)
+}
+
+/// Body of the frame at `offset` and the offset after it; None if missing or corrupt
+fn frame_at(bytes: &[u8], offset: u64) -> Option<(&[u8], u64)> {
+    let header = bytes.get(offset as usize..(offset + FRAME_HEADER_LEN) as usize)?;
+    let len = u32::from_be_bytes(header[0..4].try_into().ok()?) as u64;
+    let crc = u32::from_be_bytes(header[4..8].try_into().ok()?);
+    let start = offset + FRAME_HEADER_LEN;
+    let body = bytes.get(start as usize..(start + len) as usize)?;
+    (crc32fast::hash(body) == crc).then_some((body, start + len))
+}
+
+/// `count` consecutive records of segment `id` starting at `offset`
+fn read_frames(path: &Path, id: u64, offset: u64, count: usize) -> Result<Vec<SpooledRecord>> {
+    let mut file = BufReader::new(File::open(path)?);
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut records = Vec::with_capacity(count);
+    let mut start = offset;
+    for _ in 0..count {
+        let mut header = [0u8; FRAME_HEADER_LEN as usize];
+        file.read_exact(&mut header)?;
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        let crc = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+
+        let mut body = vec![0u8; len as usize];
+        file.read_exact(&mut body)?;
+        if crc32fast::hash(&body) != crc {
+            return Err(spool_error(format!(
+                "checksum mismatch in {} at offset {}",
+                path.display(),
+                start
+            )));
+        }
+
+        let (record, spooled_at) = decode_body(&body)?;
+        let end = start + FRAME_HEADER_LEN + len as u64;
+        records.push(SpooledRecord {
+            record,
+            spooled_at,
+            segment: id,
+            start,
+            end,
+        });
+        start = end;
+    }
+    Ok(records)
+}
+
+fn encode_frame(record: &KafkaRecord, spooled_at: Timestamp) -> Vec<u8> {
+    let mut body = Vec::with_capacity(64 + record.key.len() + record.payload.len());
+    body.extend_from_slice(&spooled_at.as_nanos().to_be_bytes());
+    put_bytes(&mut body, record.topic.as_bytes());
+    put_bytes(&mut body, record.key.as_bytes());
+    body.extend_from_slice(&(record.headers.len() as u32).to_be_bytes());
+    for (name, value) in &record.headers {
+        put_bytes(&mut body, name.as_bytes());
+        put_bytes(&mut body, value);
+    }
+    put_bytes(&mut body, &record.payload);
+
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN as usize + body.len());
+    frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&crc32fast::hash(&body).to_be_bytes());
+    frame.extend_from_slice(&body);
+    frame
+}
+
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+fn decode_body(body: &[u8]) -> Result<(KafkaRecord, Timestamp)> {
+    let mut reader = BodyReader { bytes: body };
+    let spooled_at = Timestamp::from_nanos(i64::from_be_bytes(reader.array()?));
+    let topic = reader.string()?;
+    let key = reader.string()?;
+    let header_count = u32::from_be_bytes(reader.array()?);
+    let mut headers = Vec::with_capacity(header_count as usize);
+    for _ in 0..header_count {
+        headers.push((reader.string()?, reader.bytes()?.to_vec()));
+    }
+    let payload = reader.bytes()?.to_vec();
+
+    Ok((
+        KafkaRecord {
+            topic,
+            key,
+            payload,
+            headers,
+        },
+        spooled_at,
+    ))
+}
+
+struct BodyReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> BodyReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(spool_error("truncated spool record"));
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = u32::from_be_bytes(self.array()?) as usize;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|e| spool_error(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config for an empty spool directory of its own
+    fn config(name: &str) -> SpoolConfig {
+        let dir = std::env::temp_dir().join(format!("market-data-spool-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        SpoolConfig {
+            dir: dir.to_string_lossy().into_owned(),
+            fsync: false,
+            ..SpoolConfig::default()
+        }
+    }
+
+    fn open(config: &SpoolConfig) -> Arc<Spool> {
+        Arc::new(Spool::open(config).unwrap())
+    }
+
+    fn record(n: usize) -> KafkaRecord {
+        KafkaRecord {
+            topic: "market.trades".to_string(),
+            key: format!("BTC-PERPETUAL-{}", n),
+            payload: vec![n as u8; 32],
+            headers: vec![("codec".to_string(), b"json".to_vec())],
+        }
+    }
+
+    /// Bytes one `record(n)` takes on disk, for n below 10
+    fn frame_len() -> u64 {
+        encode_frame(&record(0), Timestamp::now()).len() as u64
+    }
+
+    async fn keys(spool: &Arc<Spool>, max: usize) -> Vec<String> {
+        spool
+            .read(max)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|spooled| spooled.record.key)
+            .collect()
+    }
+
+    async fn deliver(spool: &Arc<Spool>, max: usize) {
+        let batch = spool.read(max).await.unwrap();
+        spool.ack(&batch).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn recovers_undelivered_records_after_restart() {
+        let config = config("restart");
+        let spool = open(&config);
+        for n in 0..3 {
+            spool.append(&record(n)).await.unwrap();
+        }
+        deliver(&spool, 1).await;
+        drop(spool);
+
+        let spool = open(&config);
+        assert_eq!(spool.records(), 2);
+        let batch = spool.read(10).await.unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[0].record, record(1));
+        assert_eq!(batch[1].record, record(2));
+    }
+
+    #[tokio::test]
+    async fn truncates_a_torn_tail() {
+        let config = config("torn");
+        let spool = open(&config);
+        for n in 0..2 {
+            spool.append(&record(n)).await.unwrap();
+        }
+        drop(spool);
+
+        // A crash halfway through writing the third frame
+        let path = segment_path(Path::new(&config.dir), segment_ids(Path::new(&config.dir)).unwrap()[0]);
+        let valid_len = fs::metadata(&path).unwrap().len();
+        let torn = encode_frame(&record(2), Timestamp::now());
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(&torn[..torn.len() / 2])
+            .unwrap();
+
+        let spool = open(&config);
+        assert_eq!(fs::metadata(&path).unwrap().len(), valid_len);
+        assert_eq!(spool.records(), 2);
+
+        // New records follow the last complete one
+        spool.append(&record(3)).await.unwrap();
+        assert_eq!(keys(&spool, 10).await, ["BTC-PERPETUAL-0", "BTC-PERPETUAL-1", "BTC-PERPETUAL-3"]);
+    }
+
+    #[tokio::test]
+    async fn cursor_survives_restart_across_segments() {
+        let config = SpoolConfig {
+            // One record per segment
+            segment_bytes: 1,
+            ..config("segments")
+        };
+        let spool = open(&config);
+        for n in 0..4 {
+            spool.append(&record(n)).await.unwrap();
+        }
+        assert_eq!(spool.stats().segments, 4);
+
+        // A batch stops at the end of its segment
+        assert_eq!(keys(&spool, 10).await, ["BTC-PERPETUAL-0"]);
+        deliver(&spool, 10).await;
+        deliver(&spool, 10).await;
+        drop(spool);
+
+        let spool = open(&config);
+        assert_eq!(spool.records(), 2);
+        assert_eq!(segment_ids(Path::new(&config.dir)).unwrap().len(), 3);
+        assert_eq!(keys(&spool, 10).await, ["BTC-PERPETUAL-2"]);
+        // Reading past the delivered segment removed it
+        assert_eq!(segment_ids(Path::new(&config.dir)).unwrap().len(), 2);
+        deliver(&spool, 10).await;
+        assert_eq!(keys(&spool, 10).await, ["BTC-PERPETUAL-3"]);
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_discards_the_front_segment() {
+        let config = SpoolConfig {
+            segment_bytes: frame_len(),
+            max_bytes: 2 * frame_len(),
+            overflow: OverflowPolicy::DropOldest,
+            ..config("drop-oldest")
+        };
+        let spool = open(&config);
+        for n in 0..3 {
+            spool.append(&record(n)).await.unwrap();
+        }
+
+        assert_eq!(spool.stats().dropped, 1);
+        assert_eq!(spool.records(), 2);
+        assert_eq!(keys(&spool, 10).await, ["BTC-PERPETUAL-1"]);
+        deliver(&spool, 10).await;
+        assert_eq!(keys(&spool, 10).await, ["BTC-PERPETUAL-2"]);
+    }
+
+    #[tokio::test]
+    async fn drop_newest_discards_the_incoming_record() {
+        let config = SpoolConfig {
+            max_bytes: 2 * frame_len(),
+            overflow: OverflowPolicy::DropNewest,
+            ..config("drop-newest")
+        };
+        let spool = open(&config);
+        for n in 0..3 {
+            spool.append(&record(n)).await.unwrap();
+        }
+
+        assert_eq!(spool.stats().dropped, 1);
+        assert_eq!(keys(&spool, 10).await, ["BTC-PERPETUAL-0", "BTC-PERPETUAL-1"]);
+    }
+
+    #[tokio::test]
+    async fn block_waits_for_delivery_to_free_space() {
+        let config = SpoolConfig {
+            max_bytes: 2 * frame_len(),
+            overflow: OverflowPolicy::Block,
+            ..config("block")
+        };
+        let spool = open(&config);
+        for n in 0..2 {
+            spool.append(&record(n)).await.unwrap();
+        }
+
+        let blocked = tokio::spawn({
+            let spool = spool.clone();
+            async move { spool.append(&record(2)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!blocked.is_finished());
+
+        deliver(&spool, 1).await;
+        tokio::time::timeout(Duration::from_secs(5), blocked)
+            .await
+            .expect("append still blocked after delivery")
+            .unwrap()
+            .unwrap();
+        assert_eq!(spool.stats().dropped, 0);
+        assert_eq!(keys(&spool, 10).await, ["BTC-PERPETUAL-1", "BTC-PERPETUAL-2"]);
+    }
+
+    #[tokio::test]
+    async fn discards_records_past_the_age_limit() {
+        let config = SpoolConfig {
+            segment_bytes: 1,
+            ..config("expiry")
+        };
+        let spool = open(&config);
+        for n in 0..2 {
+            spool.append(&record(n)).await.unwrap();
+        }
+        drop(spool);
+
+        let expiring = SpoolConfig {
+            max_age_secs: 0,
+            ..config.clone()
+        };
+        let spool = open(&expiring);
+        assert!(spool.read(10).await.unwrap().is_empty());
+        assert_eq!(spool.stats().expired, 2);
+        assert!(spool.is_empty());
+        drop(spool);
+
+        // The cursor moved past them for good
+        let spool = open(&config);
+        spool.append(&record(2)).await.unwrap();
+        assert_eq!(keys(&spool, 10).await, ["BTC-PERPETUAL-2"]);
+    }
+}
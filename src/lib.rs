@@ -3,3 +3,6 @@ pub mod errors;
 pub mod exchanges;
 pub mod health_check;
 pub mod infra;
+
+#[cfg(test)]
+mod test_support;
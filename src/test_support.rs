@@ -0,0 +1,29 @@
+//! Fixtures shared by unit tests across modules
+
+use market_data_types::envelope::SCHEMA_VERSION;
+use market_data_types::models::IndexPrice;
+use market_data_types::{Envelope, MarketData, Timestamp};
+
+/// Deribit price index update for `index_name`
+pub fn index_price(index_name: &str) -> MarketData {
+    MarketData::IndexPrice(IndexPrice {
+        index_name: index_name.to_string(),
+        venue: "deribit".to_string(),
+        price: 43500.5,
+        timestamp: Timestamp::from_millis(1_700_000_000_000),
+        ingestion_timestamp: Timestamp::from_millis(1_700_000_000_010),
+        publish_timestamp: None,
+    })
+}
+
+/// `payload` as sealed by connection 3 of instance `test`
+pub fn envelope(payload: MarketData) -> Envelope {
+    Envelope {
+        schema_version: SCHEMA_VERSION,
+        instance_id: "test".to_string(),
+        connection_id: Some(3),
+        sequence: 42,
+        channel: Some(format!("deribit_price_index.{}", payload.stream_key())),
+        payload,
+    }
+}